@@ -5,24 +5,31 @@
 //! and executes commands based on user input.
 
 use nest_core::constants::{
-    CMD_CHECK, CMD_LIST, FLAG_CHECK, FLAG_CLEAN, FLAG_COMPLETE, FLAG_DOCTOR, FLAG_EXAMPLE,
-    FLAG_INIT, FLAG_LIST, FLAG_SHOW, FLAG_STD, FLAG_UNINSTALL, FLAG_UPDATE, FLAG_VERBOSE,
-    FORMAT_AST, FORMAT_JSON,
+    BOOL_TRUE, CMD_CHECK, CMD_COMPLETION, CMD_DEPS, CMD_LIST, CMD_LOCK, ENV_NEST_PREFIX_MATCH,
+    FLAG_CHECK, FLAG_CLEAN, FLAG_COLOR, FLAG_COMPLETE, FLAG_COMPLETE_VALUES, FLAG_DOCTOR,
+    FLAG_EVENTS, FLAG_EVENTS_FILE, FLAG_EXAMPLE, FLAG_INIT, FLAG_INTERACTIVE, FLAG_LIST,
+    FLAG_OFFLINE, FLAG_SHOW, FLAG_STD, FLAG_UNINSTALL, FLAG_UPDATE, FLAG_VERBOSE, FORMAT_AST,
+    FORMAT_JSON, SUBCMD_UPDATE,
 };
 use nest_core::nestparse::cli::CliGenerator;
 use nest_core::nestparse::command_handler::CommandHandler;
 use nest_core::nestparse::completion::CompletionManager;
-use nest_core::nestparse::file::read_file_unchecked;
+use nest_core::nestparse::events::JsonlEvents;
+use nest_core::nestparse::executor::OutputStream;
 use nest_core::nestparse::handlers::{
     handle_example, handle_init, handle_json, handle_show_ast, handle_update, handle_version,
 };
-use nest_core::nestparse::include::process_includes;
-use nest_core::nestparse::parser::{ParseError, ParseResult, Parser};
-use nest_core::nestparse::path::find_config_file;
+use nest_core::nestparse::lock::RemoteMode;
+use nest_core::nestparse::output::{color_choice, set_color_choice, ColorChoice, OutputFormatter};
 use nest_core::nestparse::standard_commands::{
-    handle_check, handle_clean, handle_doctor, handle_list, handle_uninstall,
+    handle_check, handle_clean, handle_completion, handle_deps_update, handle_doctor, handle_list,
+    handle_lock_update, handle_uninstall,
 };
-use nest_core::nestparse::validator::{print_validation_errors, validate_commands};
+use nest_core::nestparse::validator::{
+    check_merge_conflicts, check_symbol_collisions, print_validation_errors,
+};
+use nest_core::{LoadOptions, NestError, Project};
+use std::path::Path;
 use std::process;
 use std::sync::atomic::{AtomicU32, Ordering};
 
@@ -67,7 +74,12 @@ fn main() {
             break;
         }
         // Skip values for known global flags that take them
-        if arg == "--config" || arg == "-c" {
+        if arg == "--config"
+            || arg == "-c"
+            || arg == &format!("--{}", FLAG_COLOR)
+            || arg == &format!("--{}", FLAG_EVENTS)
+            || arg == &format!("--{}", FLAG_EVENTS_FILE)
+        {
             i += 1;
         }
         i += 1;
@@ -77,6 +89,11 @@ fn main() {
     let global_args = &args[..first_command_idx];
     let has_global_flag = |flag: &str| global_args.iter().any(|a| a == &format!("--{}", flag));
 
+    // --color applies to everything printed from here on; clap reports a bad value
+    if let Some(choice) = global_flag_value(global_args, FLAG_COLOR).and_then(|v| v.parse().ok()) {
+        set_color_choice(choice);
+    }
+
     // --version or -V
     if global_args.iter().any(|a| a == "--version" || a == "-V") {
         handle_version();
@@ -152,17 +169,43 @@ fn main() {
         }
     }
 
-    let (parse_result, config_path) = match load_and_parse_config(config_path_arg) {
-        Ok(result) => result,
-        Err(e) => {
-            // User request: simplify error message for missing config
-            if config_path_arg.is_none() && e.contains("Configuration file not found") {
-                println!("nestfile not found");
-                println!("Run 'nest --init' to create one.");
-                println!("Run 'nest --std' to see standard commands.");
+    // lock update / deps update (re-fetch remote includes, so they must run before the pinned load)
+    let builtin = args.get(first_command_idx).map(String::as_str);
+    if matches!(builtin, Some(CMD_LOCK) | Some(CMD_DEPS))
+        && args.get(first_command_idx + 1).map(String::as_str) == Some(SUBCMD_UPDATE)
+    {
+        match Project::locate(config_path_arg.map(Path::new)) {
+            Ok(config_path) if builtin == Some(CMD_LOCK) => handle_lock_update(&config_path),
+            Ok(config_path) => handle_deps_update(&config_path),
+            Err(e) => {
+                nest_core::nestparse::output::OutputFormatter::error(&e.to_string());
                 process::exit(1);
             }
+        }
+        return;
+    }
 
+    let remote_mode = if has_global_flag(FLAG_OFFLINE) {
+        RemoteMode::Offline
+    } else {
+        RemoteMode::Default
+    };
+
+    let options = LoadOptions::new().remote_mode(remote_mode);
+    let project = match Project::load(config_path_arg.map(Path::new), options) {
+        Ok(project) => project,
+        // User request: simplify error message for missing config
+        Err(NestError::NotFound(None)) => {
+            println!("nestfile not found");
+            println!("Run 'nest --init' to create one.");
+            println!("Run 'nest --std' to see standard commands.");
+            process::exit(1);
+        }
+        Err(NestError::Validation { path, errors }) => {
+            print_validation_errors(&errors, &path);
+            process::exit(1);
+        }
+        Err(e) => {
             nest_core::nestparse::output::OutputFormatter::error(&e.to_string());
             if config_path_arg.is_none() {
                 nest_core::nestparse::output::OutputFormatter::info(
@@ -172,18 +215,21 @@ fn main() {
             process::exit(1);
         }
     };
-
-    // Validate configuration
-    if let Err(validation_errors) = validate_commands(&parse_result.commands, &config_path) {
-        print_validation_errors(&validation_errors, &config_path);
-        process::exit(1);
-    }
+    let config_path = project.path.clone();
 
     // --check (requires config)
     if has_global_flag(FLAG_CHECK) {
         // If user defined 'check', let them run it. Otherwise run built-in check.
-        if !parse_result.commands.iter().any(|c| c.name == CMD_CHECK) {
-            handle_check(&config_path);
+        if !project.commands.iter().any(|c| c.name == CMD_CHECK) {
+            let mut warnings = check_merge_conflicts(&project.conflicts);
+            warnings.extend(check_symbol_collisions(
+                &project.commands,
+                &project.variables,
+                &project.constants,
+                &project.functions,
+                &config_path,
+            ));
+            handle_check(&config_path, &warnings);
             return;
         }
     }
@@ -191,32 +237,104 @@ fn main() {
     // --list (requires config)
     if has_global_flag(FLAG_LIST) {
         // If user defined 'list', let them run it. Otherwise run built-in list.
-        if !parse_result.commands.iter().any(|c| c.name == CMD_LIST) {
-            handle_list(&parse_result.commands);
+        if !project.commands.iter().any(|c| c.name == CMD_LIST) {
+            handle_list(&project.commands);
             return;
         }
     }
 
-    let generator = CliGenerator::new(parse_result.commands.clone());
+    // --complete-values <command> <param> (called back by completion scripts)
+    if has_global_flag(FLAG_COMPLETE_VALUES) {
+        let flag = format!("--{}", FLAG_COMPLETE_VALUES);
+        let position = args.iter().position(|a| a == &flag).unwrap_or(0);
+        let (Some(command), Some(param)) = (args.get(position + 1), args.get(position + 2)) else {
+            process::exit(1);
+        };
+        let command_path: Vec<String> = command.split(':').map(str::to_string).collect();
+
+        let runtime = nest_core::nestparse::runtime::Runtime::new(
+            project.commands.clone(),
+            project.variables.clone(),
+            project.constants.clone(),
+            project.functions.clone(),
+            None,
+        );
+        let values = CompletionManager::new().and_then(|manager| {
+            manager.complete_values(
+                &runtime,
+                &project.commands,
+                &config_path,
+                &command_path,
+                param,
+            )
+        });
+        // Errors stay silent: there is nowhere sensible to show them at tab time
+        match values {
+            Ok(values) => {
+                for value in values {
+                    println!("{}", value);
+                }
+                return;
+            }
+            Err(_) => process::exit(1),
+        }
+    }
+
+    let generator = CliGenerator::new(project.commands.clone())
+        .interactive(has_global_flag(FLAG_INTERACTIVE))
+        .prefix_matching(
+            std::env::var(ENV_NEST_PREFIX_MATCH)
+                .is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case(BOOL_TRUE)),
+        );
 
     let runtime = nest_core::nestparse::runtime::Runtime::new(
-        parse_result.commands.clone(),
-        parse_result.variables.clone(),
-        parse_result.constants.clone(),
-        parse_result.functions.clone(),
+        project.commands.clone(),
+        project.variables.clone(),
+        project.constants.clone(),
+        project.functions.clone(),
         Some(Box::new(|pid: u32| {
             CHILD_PID.store(pid, Ordering::SeqCst);
         })),
     );
 
     let mut cli = match generator.build_cli() {
-        Ok(cli) => cli,
+        Ok(cli) => cli.color(match color_choice() {
+            ColorChoice::Auto => clap::ColorChoice::Auto,
+            ColorChoice::Always => clap::ColorChoice::Always,
+            ColorChoice::Never => clap::ColorChoice::Never,
+        }),
         Err(e) => {
             nest_core::nestparse::output::OutputFormatter::error(&e.to_string());
             process::exit(1);
         }
     };
-    let matches = cli.clone().get_matches();
+    let args = match generator
+        .expand_command_prefixes(&args, first_command_idx)
+        .and_then(|args| {
+            generator.check_private_command(&args, first_command_idx)?;
+            Ok(args)
+        }) {
+        Ok(args) => args,
+        Err(e) => {
+            nest_core::nestparse::output::OutputFormatter::error(&e);
+            process::exit(1);
+        }
+    };
+
+    // completion install|uninstall|status (unless the nestfile defines `completion`)
+    if args.get(first_command_idx).map(String::as_str) == Some(CMD_COMPLETION)
+        && !project
+            .commands
+            .iter()
+            .any(|c| c.matches_name(CMD_COMPLETION))
+    {
+        handle_completion(&args[first_command_idx + 1..], &mut cli, &project.commands);
+        return;
+    }
+
+    let matches = cli
+        .clone()
+        .get_matches_from(generator.expand_preset_shorthand(&args, first_command_idx));
 
     // Handle --complete flag
     if let Some(shell_name) = matches.get_one::<String>(FLAG_COMPLETE) {
@@ -224,6 +342,7 @@ fn main() {
         if let Err(e) =
             nest_core::nestparse::completion::CompletionManager::handle_completion_request(
                 &mut cli,
+                &project.commands,
                 shell_name,
                 verbose,
                 &config_path,
@@ -239,18 +358,43 @@ fn main() {
     if let Ok(completion_manager) = CompletionManager::new() {
         if let Ok(true) = completion_manager.needs_regeneration(&config_path) {
             if completion_manager
-                .generate_all_completions(&mut cli, &config_path)
+                .generate_all_completions(&mut cli, &project.commands, &config_path)
                 .is_ok()
             {
-                if let Ok(Some(_)) = completion_manager.auto_install_completion(&config_path) {}
+                completion_manager.refresh_installed(&mut cli, &project.commands);
             }
         }
     }
 
-    if handle_special_flags(&matches, &parse_result.commands) {
+    if handle_special_flags(&matches, &project.commands) {
         return;
     }
 
+    // --events: the run is reported as events; script output goes into them
+    // and `require_confirm` can't be answered, so it declines
+    let events = matches.get_one::<String>(FLAG_EVENTS).is_some();
+    let runtime = if events {
+        let writer: Box<dyn std::io::Write + Send> =
+            match matches.get_one::<String>(FLAG_EVENTS_FILE) {
+                Some(path) => match std::fs::File::create(path) {
+                    Ok(file) => Box::new(file),
+                    Err(e) => {
+                        nest_core::nestparse::output::OutputFormatter::error(&format!(
+                            "Cannot open events file {}: {}",
+                            path, e
+                        ));
+                        process::exit(1);
+                    }
+                },
+                None => Box::new(std::io::stdout()),
+            };
+        runtime
+            .with_confirm_handler(Box::new(|_, _| false))
+            .with_observer(Box::new(JsonlEvents::new(writer)))
+    } else {
+        runtime
+    };
+
     let command_path = extract_command_path(&matches);
 
     if command_path.is_empty() {
@@ -303,6 +447,7 @@ fn main() {
             let config = nest_core::nestparse::watcher::WatcherConfig {
                 patterns,
                 debounce_ms: 200, // Slightly higher debounce for Safety
+                clear_screen: !events,
             };
 
             let exec_closure = || {
@@ -313,7 +458,7 @@ fn main() {
                 // OR WE NEED TO CATCH IT.
                 // Refactoring CommandHandler to return Result instead of exit is better.
                 // For now, let's assume we can't easily change CommandHandler's exit behavior without big refactor.
-                // Wait, CommandHandler::handle_regular_command returns Result<(), String>.
+                // Wait, CommandHandler::handle_regular_command returns a Result.
                 // It only exits on error inside main.rs logic below.
 
                 // Let's create a wrapper that doesn't exit process
@@ -325,6 +470,13 @@ fn main() {
                     &runtime,
                     &matches,
                 )
+                .map_err(|e| {
+                    if events {
+                        e.to_string()
+                    } else {
+                        OutputFormatter::adapt(&e.render(), OutputStream::Stderr).into_owned()
+                    }
+                })
             };
 
             if let Err(e) = nest_core::nestparse::watcher::run_watch_loop(config, exec_closure) {
@@ -342,6 +494,7 @@ fn main() {
                 &generator,
                 &runtime,
                 &matches,
+                events,
             );
         }
     } else {
@@ -362,11 +515,10 @@ fn handle_command_execution_no_exit(
     generator: &CliGenerator,
     runtime: &nest_core::nestparse::runtime::Runtime,
     root_matches: &clap::ArgMatches,
-) -> Result<(), String> {
+) -> Result<(), NestError> {
     if !command.children.is_empty() {
         if !generator.has_default_command(command) {
             CommandHandler::handle_group_without_default(command, command_path)
-                .map_err(|_| "Failed to handle group command".to_string())
         } else {
             CommandHandler::handle_default_command(
                 matches,
@@ -397,59 +549,6 @@ fn handle_command_execution_no_exit(
     }
 }
 
-fn load_and_parse_config(
-    config_path_arg: Option<&str>,
-) -> Result<(ParseResult, std::path::PathBuf), String> {
-    let config_path = if let Some(path_str) = config_path_arg {
-        let path = std::path::PathBuf::from(path_str);
-        if !path.exists() {
-            return Err(format!("Configuration file not found: {}", path.display()));
-        }
-        if !path.is_file() {
-            return Err(format!("Path is not a file: {}", path.display()));
-        }
-        path
-    } else {
-        find_config_file().ok_or_else(|| {
-            "Configuration file not found. Searched for: nestfile, Nestfile, nest, Nest".to_string()
-        })?
-    };
-
-    let content =
-        read_file_unchecked(&config_path).map_err(|e| format!("Error reading file: {}", e))?;
-
-    let mut visited = std::collections::HashSet::new();
-    let processed_content = process_includes(&content, &config_path, &mut visited)
-        .map_err(|e| format!("Include error: {}", e))?;
-
-    let mut content_with_source = String::new();
-    if let Ok(canonical_path) = config_path.canonicalize() {
-        content_with_source.push_str(&format!("# @source: {}\n", canonical_path.display()));
-    }
-    content_with_source.push_str(&processed_content);
-
-    let mut parser = Parser::new(&content_with_source);
-    let mut parse_result = parser.parse().map_err(|e| match e {
-        ParseError::UnexpectedEndOfFile(line) => {
-            format!("Parse error at line {}: Unexpected end of file.", line)
-        }
-        ParseError::InvalidSyntax(msg, line) => {
-            format!("Parse error at line {}: {}", line, msg)
-        }
-        ParseError::InvalidIndent(line) => {
-            format!("Parse error at line {}: Invalid indentation.", line)
-        }
-        ParseError::DeprecatedSyntax(msg, line) => {
-            format!("Deprecated syntax error at line {}:\n{}", line, msg)
-        }
-    })?;
-
-    // Merge duplicate commands
-    parse_result.commands = nest_core::nestparse::merge::merge_commands(parse_result.commands);
-
-    Ok((parse_result, config_path))
-}
-
 fn handle_special_flags(
     matches: &clap::ArgMatches,
     commands: &[nest_core::nestparse::ast::Command],
@@ -503,6 +602,7 @@ fn handle_command_execution(
     generator: &CliGenerator,
     runtime: &nest_core::nestparse::runtime::Runtime,
     root_matches: &clap::ArgMatches,
+    events: bool,
 ) {
     if !command.children.is_empty() {
         if !generator.has_default_command(command) {
@@ -518,7 +618,7 @@ fn handle_command_execution(
                 runtime,
                 root_matches,
             ) {
-                eprint!("{}", e);
+                report_error(&e, events);
                 process::exit(1);
             }
             return;
@@ -541,7 +641,29 @@ fn handle_command_execution(
         command_path,
         root_matches,
     ) {
-        eprint!("{}", e);
+        report_error(&e, events);
         process::exit(1);
     }
 }
+
+/// Prints why the command failed; with `--events` the stream already has the
+/// details, so only a plain line goes to stderr.
+fn report_error(error: &NestError, events: bool) {
+    if events {
+        eprintln!("{}", error);
+    } else {
+        OutputFormatter::eprint(&error.render());
+    }
+}
+
+/// The value of a global flag given as `--flag value` or `--flag=value`.
+fn global_flag_value<'a>(global_args: &'a [String], flag: &str) -> Option<&'a str> {
+    let long = format!("--{}", flag);
+    global_args.iter().enumerate().find_map(|(idx, arg)| {
+        if *arg == long {
+            global_args.get(idx + 1).map(String::as_str)
+        } else {
+            arg.strip_prefix(&long)?.strip_prefix('=')
+        }
+    })
+}
@@ -0,0 +1,77 @@
+//! End-to-end checks of the `--events` stream written by the `nest` binary.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+/// Waits for the next `command_finished` event, collecting every line read.
+fn wait_for_finish(lines: &Receiver<String>, seen: &mut Vec<String>) -> bool {
+    while let Ok(line) = lines.recv_timeout(Duration::from_secs(10)) {
+        let finished = line.contains("\"command_finished\"");
+        seen.push(line);
+        if finished {
+            return true;
+        }
+    }
+    false
+}
+
+#[test]
+fn test_watch_with_events_writes_only_json_lines() {
+    let dir = std::env::temp_dir().join(format!("nest-events-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let nestfile = dir.join("nestfile");
+    std::fs::write(
+        &nestfile,
+        "build:\n    watch: nestfile\n    script: echo built\n",
+    )
+    .unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nest"))
+        .args(["--events", "jsonl", "build"])
+        .current_dir(&dir)
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let (tx, rx) = channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut seen = Vec::new();
+    let first = wait_for_finish(&rx, &mut seen);
+    // Saving the watched file re-runs the command
+    std::thread::sleep(Duration::from_millis(300));
+    std::fs::write(
+        &nestfile,
+        "build:\n    watch: nestfile\n    script: echo rebuilt\n",
+    )
+    .unwrap();
+    let second = first && wait_for_finish(&rx, &mut seen);
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert!(
+        first && second,
+        "watch run did not finish twice: {:?}",
+        seen
+    );
+    for line in &seen {
+        let event: serde_json::Value = serde_json::from_str(line)
+            .unwrap_or_else(|e| panic!("not a JSON line ({}): {:?}", e, line));
+        assert!(event["event"].is_string(), "{:?}", line);
+    }
+    assert!(seen.iter().any(|line| line.contains("\"line\":\"built\"")));
+}
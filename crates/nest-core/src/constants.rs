@@ -6,6 +6,11 @@
 /// are included.
 pub const CONFIG_NAMES: [&str; 4] = ["nestfile", "Nestfile", "nest", "Nest"];
 
+/// Name of the lockfile that pins remote includes.
+///
+/// It is stored next to the root configuration file.
+pub const LOCK_FILE_NAME: &str = "Nestfile.lock";
+
 /// The number of spaces used for indentation in the configuration file.
 ///
 /// Commands are nested using this indentation level.
@@ -23,9 +28,18 @@ pub const RESERVED_WORDS: [&str; 2] = ["nest", "default"];
 /// - `str` - String values
 /// - `bool` - Boolean values (true/false)
 /// - `num` - Numeric values
-/// - `arr` - Array of strings
+/// - `arr` - Array of strings (`arr<T>` for typed items)
+/// - `int` - Integers (`int(1..10)` for a range)
+/// - `enum` - One of a fixed set of values (`enum("a", "b")`)
+/// - `path`, `file`, `dir` - Paths (`file` and `dir` must exist)
+/// - `url` - URLs
+/// - `duration` - Durations (`30s`, `1h30m`)
+/// - `semver` - Semantic versions
 #[allow(dead_code)]
-pub const DATA_TYPES: [&str; 4] = ["str", "bool", "num", "arr"];
+pub const DATA_TYPES: [&str; 13] = [
+    "str", "bool", "num", "arr", "map", "int", "enum", "path", "file", "dir", "url", "duration",
+    "semver",
+];
 
 // CLI Application Constants
 
@@ -62,6 +76,15 @@ pub const FLAG_VERBOSE: &str = "verbose";
 /// Flag name for generating shell completion.
 pub const FLAG_COMPLETE: &str = "complete";
 
+/// Flag name used by completion scripts to ask for a parameter's values at tab time.
+pub const FLAG_COMPLETE_VALUES: &str = "complete-values";
+
+/// Flag name for selecting a preset of a command (`--preset NAME`, or `@NAME`).
+pub const FLAG_PRESET: &str = "preset";
+
+/// Prefix of the preset shorthand (`nest deploy @prod`).
+pub const PRESET_PREFIX: char = '@';
+
 /// Flag name for showing standard command help.
 pub const FLAG_STD: &str = "std";
 
@@ -72,15 +95,39 @@ pub const FLAG_CLEAN: &str = "clean";
 pub const FLAG_UNINSTALL: &str = "uninstall";
 pub const FLAG_INIT: &str = "init";
 
+/// Flag name for resolving remote includes from the cache only.
+pub const FLAG_OFFLINE: &str = "offline";
+
+/// Flag name for prompting for missing parameter values on a TTY.
+pub const FLAG_INTERACTIVE: &str = "interactive";
+
+/// Flag name for writing execution events instead of human output.
+pub const FLAG_EVENTS: &str = "events";
+
+/// Flag name for the file `--events` writes to instead of stdout.
+pub const FLAG_EVENTS_FILE: &str = "events-file";
+
+/// Flag name for choosing when output is colored.
+pub const FLAG_COLOR: &str = "color";
+
+/// Values of `--color`.
+pub const COLOR_CHOICES: [&str; 3] = ["auto", "always", "never"];
+
 /// Format option for JSON output.
 pub const FORMAT_JSON: &str = "json";
 
 /// Format option for AST output.
 pub const FORMAT_AST: &str = "ast";
 
+/// Format option for one JSON object per line.
+pub const FORMAT_JSONL: &str = "jsonl";
+
 /// Short option for version flag.
 pub const SHORT_VERSION: char = 'V';
 
+/// How long (in seconds) values produced by a `complete.PARAM` source are cached.
+pub const COMPLETION_VALUES_TTL_SECS: u64 = 60;
+
 // Command Structure Constants
 
 /// Name of the default subcommand in group commands.
@@ -92,6 +139,26 @@ pub const CMD_CHECK: &str = "check";
 pub const CMD_DOCTOR: &str = "doctor";
 pub const CMD_CLEAN: &str = "clean";
 pub const CMD_UNINSTALL: &str = "uninstall";
+pub const CMD_LOCK: &str = "lock";
+pub const CMD_DEPS: &str = "deps";
+
+/// Built-in command managing the installed shell completion.
+pub const CMD_COMPLETION: &str = "completion";
+
+/// Subcommand of `completion` that installs the completion script.
+pub const SUBCMD_INSTALL: &str = "install";
+
+/// Subcommand of `completion` that removes an installed completion.
+pub const SUBCMD_UNINSTALL: &str = "uninstall";
+
+/// Subcommand of `completion` that shows where completion is installed.
+pub const SUBCMD_STATUS: &str = "status";
+
+/// Flag of `completion install` that prints the script instead of installing it.
+pub const FLAG_PRINT: &str = "print";
+
+/// Subcommand of `lock` and `deps` that refreshes pinned remote sources.
+pub const SUBCMD_UPDATE: &str = "update";
 
 // Boolean Values
 
@@ -143,3 +210,6 @@ pub const DEFAULT_USER: &str = "unknown";
 
 /// Environment variable name for tracking recursion depth and cycles.
 pub const ENV_NEST_CALL_STACK: &str = "NEST_CALL_STACK";
+
+/// Environment variable that enables unique-prefix command matching (`nest dep` → `deploy`).
+pub const ENV_NEST_PREFIX_MATCH: &str = "NEST_PREFIX_MATCH";
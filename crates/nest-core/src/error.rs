@@ -0,0 +1,287 @@
+//! Errors of the nest-core library.
+//!
+//! Every failure of loading a Nestfile or running a command is a [`NestError`],
+//! so embedders can match on what went wrong. `Display` gives a plain,
+//! one-paragraph message; the colored boxes `nest` prints are built only on
+//! request by [`NestError::render`].
+
+use crate::nestparse::include::IncludeError;
+use crate::nestparse::output::{colors, OutputFormatter};
+use crate::nestparse::parser::ParseError;
+use crate::nestparse::validator::{format_validation_errors, ValidationError};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Why loading a Nestfile or running a command failed.
+#[derive(Debug)]
+pub enum NestError {
+    /// No config at the given path, or none in the current directory with `None`
+    NotFound(Option<PathBuf>),
+    /// The given config path isn't a regular file
+    NotAFile(PathBuf),
+    /// Reading or writing a file (the Nestfile, the lockfile, a log) failed
+    Io(String),
+    /// An include couldn't be resolved
+    Include(IncludeError),
+    /// The Nestfile doesn't parse; `source` is the file the line came from
+    Parse {
+        error: ParseError,
+        source: Option<PathBuf>,
+    },
+    /// The commands of the Nestfile at `path` parse but don't pass validation
+    Validation {
+        path: PathBuf,
+        errors: Vec<ValidationError>,
+    },
+    /// Arguments that don't fit their type or the command's `validate` rules
+    InvalidArguments {
+        command_path: Vec<String>,
+        errors: Vec<String>,
+    },
+    /// The command line doesn't select a runnable command (clap's message, or
+    /// a group without a default subcommand)
+    Usage(String),
+    /// A `depends` entry names no command
+    DependencyNotFound {
+        dependency: Vec<String>,
+        required_by: Vec<String>,
+    },
+    /// Commands that end up running themselves, in call order; the last one
+    /// is the command seen twice
+    Cycle(Vec<String>),
+    /// A script exited non-zero
+    ScriptExit(Box<ScriptFailure>),
+    /// A `privileged` command run without elevated privileges
+    Privilege { command_path: Vec<String> },
+    /// The `require_confirm` prompt was answered no
+    ConfirmationDeclined { command_path: Vec<String> },
+    /// The `before`, `after` or `fallback` script of a command failed
+    Hook {
+        hook: &'static str,
+        source: Box<NestError>,
+    },
+    /// A dependency run in parallel failed
+    Dependency {
+        dependency: String,
+        source: Box<NestError>,
+    },
+    /// Several parallel dependencies failed
+    Multiple(Vec<NestError>),
+    /// Anything else, e.g. a script that couldn't be started
+    Other(String),
+}
+
+/// The script behind a [`NestError::ScriptExit`] and how it ended.
+#[derive(Debug)]
+pub struct ScriptFailure {
+    pub command_path: Vec<String>,
+    /// `None` when the script was killed by a signal
+    pub code: Option<i32>,
+    pub script: String,
+    pub cwd: Option<String>,
+    pub args: HashMap<String, String>,
+}
+
+impl NestError {
+    /// The exit code of the script behind the failure, if one exited non-zero.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            NestError::ScriptExit(failure) => failure.code,
+            NestError::Hook { source, .. } | NestError::Dependency { source, .. } => {
+                source.exit_code()
+            }
+            NestError::Multiple(errors) => errors.iter().find_map(NestError::exit_code),
+            _ => None,
+        }
+    }
+
+    /// The error the way `nest` prints it: boxed and colored for the terminal.
+    pub fn render(&self) -> String {
+        match self {
+            NestError::ScriptExit(failure) => crate::nestparse::executor::format_error_message(
+                &failure.command_path,
+                &failure.args,
+                failure.cwd.as_deref(),
+                &failure.script,
+                failure.code.unwrap_or(-1),
+                "(See output above)",
+            ),
+            NestError::Privilege { command_path } => {
+                crate::nestparse::executor::CommandExecutor::format_privileged_error(command_path)
+            }
+            NestError::Validation { path, errors } => format_validation_errors(errors, path),
+            NestError::InvalidArguments {
+                command_path,
+                errors,
+            } => OutputFormatter::format_error_box(
+                &format!(
+                    "Validation error in command 'nest {}'",
+                    command_path.join(" ")
+                ),
+                &errors.join("\n"),
+            ),
+            NestError::ConfirmationDeclined { .. } => {
+                format!("{}ℹ{} {}\n", colors::BRIGHT_CYAN, colors::RESET, self)
+            }
+            NestError::Hook { hook, source } => {
+                format!("{} script failed: {}", capitalize(hook), source.render())
+            }
+            NestError::Dependency { dependency, source } => {
+                format!("Dependency '{}' failed: {}", dependency, source.render())
+            }
+            NestError::Multiple(errors) => errors.iter().map(NestError::render).collect(),
+            _ => OutputFormatter::format_error_box("Error", &self.to_string()),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn command_display(command_path: &[String]) -> String {
+    format!("nest {}", command_path.join(" "))
+}
+
+impl std::fmt::Display for NestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NestError::NotFound(Some(path)) => {
+                write!(f, "Configuration file not found: {}", path.display())
+            }
+            NestError::NotFound(None) => write!(
+                f,
+                "Configuration file not found. Searched for: nestfile, Nestfile, nest, Nest"
+            ),
+            NestError::NotAFile(path) => write!(f, "Path is not a file: {}", path.display()),
+            NestError::Io(msg) | NestError::Usage(msg) | NestError::Other(msg) => {
+                write!(f, "{}", msg)
+            }
+            NestError::Include(e) => write!(f, "Include error: {}", e),
+            NestError::Parse { error, .. } => match error {
+                ParseError::UnexpectedEndOfFile(line) => {
+                    write!(f, "Parse error at line {}: Unexpected end of file.", line)
+                }
+                ParseError::InvalidSyntax(msg, line) => {
+                    write!(f, "Parse error at line {}: {}", line, msg)
+                }
+                ParseError::InvalidIndent(line) => {
+                    write!(f, "Parse error at line {}: Invalid indentation.", line)
+                }
+                ParseError::DeprecatedSyntax(msg, line) => {
+                    write!(f, "Deprecated syntax error at line {}:\n{}", line, msg)
+                }
+            },
+            NestError::Validation { errors, .. } => {
+                let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+                write!(f, "{}", messages.join("\n"))
+            }
+            NestError::InvalidArguments { errors, .. } => write!(f, "{}", errors.join("\n")),
+            NestError::DependencyNotFound {
+                dependency,
+                required_by,
+            } => write!(
+                f,
+                "Dependency not found: {} (required by {})",
+                dependency.join(" "),
+                required_by.join(" ")
+            ),
+            NestError::Cycle(chain) => {
+                write!(f, "Circular dependency detected: {}", chain.join(" -> "))
+            }
+            NestError::ScriptExit(failure) => match failure.code {
+                Some(code) => write!(
+                    f,
+                    "'{}' failed with exit code {}",
+                    command_display(&failure.command_path),
+                    code
+                ),
+                None => write!(
+                    f,
+                    "'{}' was terminated by a signal",
+                    command_display(&failure.command_path)
+                ),
+            },
+            NestError::Privilege { command_path } => write!(
+                f,
+                "'{}' requires privileged access; run it with sudo or as Administrator",
+                command_display(command_path)
+            ),
+            NestError::ConfirmationDeclined { command_path } => write!(
+                f,
+                "'{}' was not confirmed; nothing was run",
+                command_display(command_path)
+            ),
+            NestError::Hook { hook, source } => {
+                write!(f, "{} script failed: {}", capitalize(hook), source)
+            }
+            NestError::Dependency { dependency, source } => {
+                write!(f, "Dependency '{}' failed: {}", dependency, source)
+            }
+            NestError::Multiple(errors) => {
+                let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "{}", messages.join("\n"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for NestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NestError::Include(e) => Some(e),
+            NestError::Hook { source, .. } | NestError::Dependency { source, .. } => {
+                Some(source.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<IncludeError> for NestError {
+    fn from(error: IncludeError) -> Self {
+        NestError::Include(error)
+    }
+}
+
+/// Messages from the parts of the library that still report plain strings
+/// (templates, value capture) become [`NestError::Other`].
+impl From<String> for NestError {
+    fn from(message: String) -> Self {
+        NestError::Other(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code_and_plain_display() {
+        let exit = NestError::ScriptExit(Box::new(ScriptFailure {
+            command_path: vec!["db".to_string(), "migrate".to_string()],
+            code: Some(3),
+            script: "exit 3".to_string(),
+            cwd: None,
+            args: HashMap::new(),
+        }));
+        assert_eq!(
+            exit.to_string(),
+            "'nest db migrate' failed with exit code 3"
+        );
+        assert!(exit.render().contains("Exit code:"));
+
+        let hook = NestError::Hook {
+            hook: "before",
+            source: Box::new(exit),
+        };
+        assert_eq!(hook.exit_code(), Some(3));
+        assert!(hook.to_string().starts_with("Before script failed: "));
+        assert!(!hook.to_string().contains('\x1b'));
+        assert!(std::error::Error::source(&hook).is_some());
+    }
+}
@@ -1,2 +1,7 @@
 pub mod constants;
+pub mod error;
 pub mod nestparse;
+pub mod project;
+
+pub use error::{NestError, ScriptFailure};
+pub use project::{LoadOptions, Project};
@@ -3,10 +3,15 @@
 //! This module handles extracting command arguments from clap's ArgMatches
 //! and converting them into a format suitable for script execution.
 
-use super::ast::{ParamKind, Parameter};
+use super::ast::{ParamKind, Parameter, Value};
 use super::cli::CliGenerator;
-use super::type_validator;
-use crate::constants::BOOL_TRUE;
+use super::directives::DirectiveResolver;
+use super::input;
+use super::runtime_validator::RuntimeValidator;
+use super::template::TemplateContext;
+use super::type_validator::{self, ParamType};
+use crate::constants::{BOOL_TRUE, FLAG_PRESET};
+use clap::parser::ValueSource;
 use clap::ArgMatches;
 use std::collections::HashMap;
 
@@ -41,11 +46,25 @@ impl ArgumentExtractor {
     ) -> Result<HashMap<String, String>, Vec<String>> {
         let mut args = HashMap::new();
         let mut custom_errors: Vec<String> = Vec::new();
+        let preset = Self::selected_preset(matches, generator, command_path);
 
         for param in parameters {
             match &param.kind {
                 ParamKind::Normal => {
                     if param.param_type == "bool" {
+                        if let Some(result) = Self::bool_from_preset_or_prompt(
+                            &mut args,
+                            param,
+                            &preset,
+                            matches,
+                            generator,
+                            command_path,
+                        ) {
+                            if let Err(e) = result {
+                                custom_errors.push(e);
+                            }
+                            continue;
+                        }
                         let (bool_value, flag_mode) = if param.is_named {
                             Self::extract_bool_flag_with_mode(matches, param, generator)
                         } else {
@@ -72,16 +91,16 @@ impl ArgumentExtractor {
                             Self::extract_value_arg_positional(matches, param)
                         };
 
-                        if let Some(mut value) = value {
-                            // Convert comma-separated array values to space-separated
-                            if param.param_type == "arr" {
-                                value = value.replace(",", " ");
-                            }
-                            args.insert(param.name.clone(), value);
-                        } else if let Some(default) = &param.default {
-                            if let Some(default_str) = generator.value_to_string(default) {
-                                args.insert(param.name.clone(), default_str);
-                            }
+                        if let Err(e) = Self::store_value(
+                            &mut args,
+                            param,
+                            value,
+                            &preset,
+                            matches,
+                            generator,
+                            command_path,
+                        ) {
+                            custom_errors.push(e);
                         }
                     }
                 }
@@ -154,11 +173,25 @@ impl ArgumentExtractor {
     ) -> Result<HashMap<String, String>, Vec<String>> {
         let mut args = HashMap::new();
         let mut custom_errors: Vec<String> = Vec::new();
+        let preset = Self::selected_preset(matches, generator, command_path);
 
         for param in parameters {
             match &param.kind {
                 ParamKind::Normal => {
                     if param.param_type == "bool" {
+                        if let Some(result) = Self::bool_from_preset_or_prompt(
+                            &mut args,
+                            param,
+                            &preset,
+                            matches,
+                            generator,
+                            command_path,
+                        ) {
+                            if let Err(e) = result {
+                                custom_errors.push(e);
+                            }
+                            continue;
+                        }
                         let (bool_value, flag_mode) = if param.is_named {
                             Self::extract_bool_flag_for_default_with_mode(matches, param, generator)
                         } else {
@@ -184,16 +217,16 @@ impl ArgumentExtractor {
                             Self::extract_value_arg_for_default_positional(matches, param)
                         };
 
-                        if let Some(mut value) = value {
-                            // Convert comma-separated array values to space-separated
-                            if param.param_type == "arr" {
-                                value = value.replace(",", " ");
-                            }
-                            args.insert(param.name.clone(), value);
-                        } else if let Some(default) = &param.default {
-                            if let Some(default_str) = generator.value_to_string(default) {
-                                args.insert(param.name.clone(), default_str);
-                            }
+                        if let Err(e) = Self::store_value(
+                            &mut args,
+                            param,
+                            value,
+                            &preset,
+                            matches,
+                            generator,
+                            command_path,
+                        ) {
+                            custom_errors.push(e);
                         }
                     }
                 }
@@ -240,6 +273,273 @@ impl ArgumentExtractor {
         }
     }
 
+    /// Fills a bool parameter from the selected preset, or by prompting when
+    /// it wasn't passed.
+    ///
+    /// Returns `None` when the value comes from the command line instead.
+    fn bool_from_preset_or_prompt(
+        args: &mut HashMap<String, String>,
+        param: &Parameter,
+        preset: &[(String, Value)],
+        matches: &ArgMatches,
+        generator: &CliGenerator,
+        command_path: &[String],
+    ) -> Option<Result<(), String>> {
+        if let Some(values) = Self::preset_values(preset, matches, &param.name, param, generator) {
+            args.insert(param.name.clone(), values.join(" "));
+            return Some(Ok(()));
+        }
+        if matches.contains_id(&param.name) {
+            return None;
+        }
+        Self::prompt_into(args, param, generator, command_path)
+    }
+
+    /// Stores the value of a non-bool parameter in `args`.
+    ///
+    /// The selected preset wins over `cli_value` unless that was typed on the
+    /// command line; without either the default is used, and without a
+    /// default the value is prompted for.
+    fn store_value(
+        args: &mut HashMap<String, String>,
+        param: &Parameter,
+        cli_value: Option<Vec<String>>,
+        preset: &[(String, Value)],
+        matches: &ArgMatches,
+        generator: &CliGenerator,
+        command_path: &[String],
+    ) -> Result<(), String> {
+        let id = if param.is_named {
+            generator.get_param_id(&param.name)
+        } else {
+            param.name.as_str()
+        };
+        let values = Self::preset_values(preset, matches, id, param, generator)
+            .or(cli_value)
+            .or_else(|| {
+                param
+                    .default
+                    .as_ref()
+                    .and_then(|default| Self::default_values(default, param, generator))
+            });
+
+        match values {
+            Some(values) => Self::insert_values(args, param, &values, command_path),
+            None => Self::prompt_into(args, param, generator, command_path)
+                .unwrap_or_else(|| Self::check_not_missing(param, preset, command_path)),
+        }
+    }
+
+    /// Prompts for a missing parameter and stores the answer in `args`.
+    ///
+    /// Returns `None` when prompting doesn't apply to `param`.
+    fn prompt_into(
+        args: &mut HashMap<String, String>,
+        param: &Parameter,
+        generator: &CliGenerator,
+        command_path: &[String],
+    ) -> Option<Result<(), String>> {
+        let answer = Self::prompt_missing_value(param, generator, command_path, args)?;
+        Some(answer.and_then(|answer| Self::insert_values(args, param, &[answer], command_path)))
+    }
+
+    /// Asks for a missing parameter value on the terminal.
+    ///
+    /// Returns `None` when prompting doesn't apply to `param` (it has a default,
+    /// hasn't opted in, or stdin is not a TTY), leaving the usual missing-value error.
+    /// Enums are picked from a list, bools answered with y/n and `@secret` values
+    /// read without echo. Every answer is checked against the parameter type and the
+    /// command's `validate:` rules for it before being accepted.
+    fn prompt_missing_value(
+        param: &Parameter,
+        generator: &CliGenerator,
+        command_path: &[String],
+        args: &HashMap<String, String>,
+    ) -> Option<Result<String, String>> {
+        if param.default.is_some() || !generator.can_prompt(param) {
+            return None;
+        }
+
+        // Rules using templates depend on runtime context and are checked when the command runs
+        let rules: Vec<(String, String)> = generator
+            .find_command(command_path)
+            .map(|command| DirectiveResolver::get_validate_directives(&command.directives))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(target, rule)| target == &param.name && !rule.contains("{{"))
+            .collect();
+
+        let check = |value: &str| -> Result<(), String> {
+            type_validator::validate_argument_type(value, param)?;
+            if rules.is_empty() {
+                return Ok(());
+            }
+
+            let mut candidate = args.clone();
+            candidate.insert(param.name.clone(), value.to_string());
+            let tpl_context = TemplateContext {
+                global_variables: &[],
+                global_constants: &[],
+                local_variables: &[],
+                local_constants: &[],
+                parent_variables: &[],
+                parent_constants: &[],
+                namespace_variables: &[],
+            };
+            RuntimeValidator::validate(
+                &rules,
+                &candidate,
+                &HashMap::new(),
+                &tpl_context,
+                command_path,
+                &HashMap::new(),
+            )
+            .map_err(|e| e.to_string())
+        };
+
+        if param.param_type == "bool" {
+            let question = format!("{}?", param.name);
+            return Some(Self::ask_until_valid(&check, || {
+                input::prompt_confirmation(Some(&question), None).map(|answer| answer.to_string())
+            }));
+        }
+
+        let param_type = ParamType::parse(&param.param_type).ok();
+        if let Some(options) = param_type.as_ref().and_then(|t| t.possible_values()) {
+            return Some(Self::ask_until_valid(&check, || {
+                input::prompt_select(&param.name, options)
+            }));
+        }
+
+        let label = format!("{} ({})", param.name, param.param_type);
+        Some(input::prompt_value(&label, param.secret, &check))
+    }
+
+    /// Repeats `ask` until its answer passes `check`, showing why each rejected
+    /// answer was refused.
+    fn ask_until_valid(
+        check: &dyn Fn(&str) -> Result<(), String>,
+        ask: impl Fn() -> Result<String, String>,
+    ) -> Result<String, String> {
+        loop {
+            let answer = ask()?;
+            match check(&answer) {
+                Ok(()) => return Ok(answer),
+                Err(message) => eprintln!("{}", message),
+            }
+        }
+    }
+
+    /// Stores the values of a parameter in `args`.
+    ///
+    /// Arrays are joined with spaces. Maps are stored both as a whole
+    /// (`key=value` pairs joined with spaces) and per key as `name.key`,
+    /// which is what `{{set.key}}` and `{{set|each:"..."}}` read.
+    fn insert_values(
+        args: &mut HashMap<String, String>,
+        param: &Parameter,
+        values: &[String],
+        command_path: &[String],
+    ) -> Result<(), String> {
+        match ParamType::parse(&param.param_type) {
+            Ok(ParamType::Map) => {
+                let pairs = type_validator::parse_map(values).map_err(|reason| {
+                    format!(
+                        "❌ Type validation error in command 'nest {}':\n   Invalid value for parameter '{}'. {}",
+                        command_path.join(" "),
+                        param.name,
+                        reason
+                    )
+                })?;
+                let joined: Vec<String> =
+                    pairs.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                args.insert(param.name.clone(), joined.join(" "));
+                for (key, value) in pairs {
+                    args.insert(format!("{}.{}", param.name, key), value);
+                }
+            }
+            Ok(param_type) if param_type.is_array() => {
+                args.insert(
+                    param.name.clone(),
+                    type_validator::parse_array(values).join(" "),
+                );
+            }
+            _ => {
+                args.insert(param.name.clone(), values.join(" "));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the argument values of the preset selected with `--preset` (or `@NAME`).
+    fn selected_preset(
+        matches: &ArgMatches,
+        generator: &CliGenerator,
+        command_path: &[String],
+    ) -> Vec<(String, Value)> {
+        let Some(name) = matches.try_get_one::<String>(FLAG_PRESET).ok().flatten() else {
+            return Vec::new();
+        };
+        generator
+            .find_command(command_path)
+            .and_then(|command| DirectiveResolver::get_preset(&command.directives, name))
+            .unwrap_or_default()
+    }
+
+    /// Returns the value the selected preset gives a parameter.
+    ///
+    /// Values passed on the command line win over the preset, which in turn
+    /// wins over `@env(NAME)` variables and defaults.
+    fn preset_values(
+        preset: &[(String, Value)],
+        matches: &ArgMatches,
+        id: &str,
+        param: &Parameter,
+        generator: &CliGenerator,
+    ) -> Option<Vec<String>> {
+        if matches.value_source(id) == Some(ValueSource::CommandLine) {
+            return None;
+        }
+        let (_, value) = preset.iter().find(|(name, _)| name == &param.name)?;
+        Self::default_values(value, param, generator)
+    }
+
+    /// Reports a required parameter that clap let through because a preset was
+    /// selected, but which that preset doesn't set.
+    fn check_not_missing(
+        param: &Parameter,
+        preset: &[(String, Value)],
+        command_path: &[String],
+    ) -> Result<(), String> {
+        if preset.is_empty() || param.default.is_some() {
+            return Ok(());
+        }
+        Err(format!(
+            "❌ Missing value for parameter '{}' in command 'nest {}': it is required and not set by the selected preset.",
+            param.name,
+            command_path.join(" ")
+        ))
+    }
+
+    /// Returns the default of a parameter as argument values.
+    ///
+    /// Array defaults of `arr`/`map` parameters keep one value per item.
+    fn default_values(
+        default: &Value,
+        param: &Parameter,
+        generator: &CliGenerator,
+    ) -> Option<Vec<String>> {
+        match default {
+            Value::Array(items) if Self::is_repeatable_param(param) => Some(items.clone()),
+            _ => generator.value_to_string(default).map(|value| vec![value]),
+        }
+    }
+
+    /// Returns `true` for `arr`, typed `arr<T>` and `map` parameters.
+    fn is_repeatable_param(param: &Parameter) -> bool {
+        ParamType::parse(&param.param_type).is_ok_and(|t| t.is_repeatable())
+    }
+
     /// Extracts boolean flag value and returns (bool_value, was_passed_as_flag).
     /// was_passed_as_flag is true if the parameter was passed as a flag without explicit value (e.g., --build),
     /// false if it was passed with explicit value (e.g., --build=true or --build=false).
@@ -307,19 +607,36 @@ impl ArgumentExtractor {
         }
     }
 
+    /// Returns the values passed on the command line or through the parameter's
+    /// `@env(NAME)` variable. An empty variable counts as unset, so the default applies.
+    ///
+    /// Repeated flags (`--tag a --tag b`) yield one value per occurrence.
+    fn get_values(matches: &ArgMatches, id: &str) -> Option<Vec<String>> {
+        let values: Vec<String> = matches.get_many::<String>(id)?.cloned().collect();
+        if matches.value_source(id) == Some(ValueSource::EnvVariable)
+            && values.iter().all(String::is_empty)
+        {
+            return None;
+        }
+        Some(values)
+    }
+
     fn extract_value_arg_named(
         matches: &ArgMatches,
         _param: &Parameter,
         param_id: &str,
-    ) -> Option<String> {
+    ) -> Option<Vec<String>> {
         // For named arguments, clap uses the param_id (parameter name) as the ID
         // The alias is only used for the short option, but the ID remains the parameter name
-        matches.get_one::<String>(param_id).cloned()
+        Self::get_values(matches, param_id)
     }
 
-    fn extract_value_arg_positional(matches: &ArgMatches, param: &Parameter) -> Option<String> {
+    fn extract_value_arg_positional(
+        matches: &ArgMatches,
+        param: &Parameter,
+    ) -> Option<Vec<String>> {
         // Positional arguments are accessible by their name
-        matches.get_one::<String>(&param.name).cloned()
+        Self::get_values(matches, &param.name)
     }
 
     fn extract_bool_positional(
@@ -344,16 +661,19 @@ impl ArgumentExtractor {
         }
     }
 
-    fn extract_value_arg_for_default_named(matches: &ArgMatches, param_id: &str) -> Option<String> {
+    fn extract_value_arg_for_default_named(
+        matches: &ArgMatches,
+        param_id: &str,
+    ) -> Option<Vec<String>> {
         // For named arguments, use the param_id directly
-        matches.get_one::<String>(param_id).cloned()
+        Self::get_values(matches, param_id)
     }
 
     fn extract_value_arg_for_default_positional(
         matches: &ArgMatches,
         param: &Parameter,
-    ) -> Option<String> {
+    ) -> Option<Vec<String>> {
         // Positional arguments are accessible by their name
-        matches.get_one::<String>(&param.name).cloned()
+        Self::get_values(matches, &param.name)
     }
 }
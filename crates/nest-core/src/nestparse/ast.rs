@@ -84,7 +84,8 @@ pub struct Parameter {
     pub name: String,
     /// Optional alias (e.g., "f" for "force")
     pub alias: Option<String>,
-    /// The parameter type: "str", "bool", "num", or "arr"
+    /// The parameter type as written, e.g. "str", "int(1..10)" or "enum(\"a\", \"b\")"
+    /// (see `type_validator::ParamType`)
     pub param_type: String,
     /// Optional default value
     pub default: Option<Value>,
@@ -92,6 +93,12 @@ pub struct Parameter {
     pub is_named: bool,
     /// The kind of this parameter (normal or wildcard).
     pub kind: ParamKind,
+    /// Whether a missing value is asked for on a TTY (`@prompt`)
+    pub prompt: bool,
+    /// Whether the value is sensitive and read without echo when prompted (`@secret`)
+    pub secret: bool,
+    /// Environment variable used when the argument is not passed (`@env(NAME)`)
+    pub env: Option<String>,
 }
 
 /// Represents a dependency with optional arguments.
@@ -115,6 +122,12 @@ pub struct Dependency {
 pub enum Directive {
     /// Description of the command (used in help text)
     Desc(String),
+    /// Alternative names the command can be invoked by (`alias: b, bld`)
+    Alias(Vec<String>),
+    /// Help section the command is listed under (`category: Database`)
+    Category(String),
+    /// Position of the command within its help section (`help.order: 10`)
+    HelpOrder(i64),
     /// Working directory for command execution
     Cwd(String),
     /// Environment variable assignment (name, value, hide)
@@ -133,12 +146,20 @@ pub enum Directive {
     Finally(String, Option<String>, bool),
     /// Whether this command requires privileged access (sudo/administrator)
     Privileged(bool),
+    /// Whether this command is private: hidden from listings and only
+    /// reachable through `depends:` or calls from other scripts
+    Private(bool),
     /// Dependencies - commands that must be executed before this command
     /// Each dependency can have arguments (e.g., "build(target=\"x86_64\")")
     /// Second element is true if dependencies should run in parallel
     Depends(Vec<Dependency>, bool),
     /// Validation rules for parameters (target_param, rule)
     Validate(String, String),
+    /// Shell completion source for a parameter (param, `$(command)` or function name)
+    Complete(String, String),
+    /// Named set of argument values (`preset prod: region="eu", replicas=3`),
+    /// selected with `--preset NAME` or `@NAME`
+    Preset(String, Vec<(String, Value)>),
     /// Logging directive - logs command output to a file
     /// First String is the file path, second is the format ("json" or "txt")
     Logs(String, String),
@@ -190,6 +211,18 @@ pub struct Function {
     pub local_variables: Vec<Variable>,
 }
 
+/// How a command definition combines with an earlier one of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeMode {
+    /// No keyword: the later definition wins (reported by `--check`)
+    #[default]
+    Implicit,
+    /// `override name:` - intentionally replaces the earlier definition
+    Override,
+    /// `append name:` - adds to `depends`, `env` and `script` instead of replacing them
+    Append,
+}
+
 /// Represents a command in the configuration file.
 ///
 /// Commands can have:
@@ -218,6 +251,10 @@ pub struct Command {
     pub local_constants: Vec<Constant>,
     /// Source file path where this command was defined (for proper path resolution)
     pub source_file: Option<std::path::PathBuf>,
+    /// How this definition merges with an earlier one of the same name
+    pub merge_mode: MergeMode,
+    /// Command this one inherits from (`build extends base_build:`)
+    pub extends: Option<String>,
 }
 
 impl Default for Command {
@@ -231,10 +268,45 @@ impl Default for Command {
             local_variables: Vec::new(),
             local_constants: Vec::new(),
             source_file: None,
+            merge_mode: MergeMode::Implicit,
+            extends: None,
         }
     }
 }
 
+impl Command {
+    /// Returns true if the command is private, either through the `private`
+    /// keyword or a name starting with an underscore.
+    pub fn is_private(&self) -> bool {
+        self.name.starts_with('_')
+            || self
+                .directives
+                .iter()
+                .any(|d| matches!(d, Directive::Private(true)))
+    }
+
+    /// Returns true if `name` is the command's name or one of its aliases.
+    pub fn matches_name(&self, name: &str) -> bool {
+        self.name == name
+            || self.directives.iter().any(|d| match d {
+                Directive::Alias(aliases) => aliases.iter().any(|alias| alias == name),
+                _ => false,
+            })
+    }
+}
+
+/// Finds the command at `path`, matching each segment against the names and
+/// aliases of the commands at that level.
+pub(crate) fn find_command<'a>(commands: &'a [Command], path: &[String]) -> Option<&'a Command> {
+    let (first, rest) = path.split_first()?;
+    let command = commands.iter().find(|c| c.matches_name(first))?;
+    if rest.is_empty() {
+        Some(command)
+    } else {
+        find_command(&command.children, rest)
+    }
+}
+
 impl fmt::Display for Command {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.name)?;
@@ -254,8 +326,12 @@ impl fmt::Display for Command {
                             s.push_str(&format!("|{}", alias));
                         }
                         s.push_str(&format!(": {}", p.param_type));
-                        if let Some(default) = &p.default {
-                            s.push_str(&format!(" = {:?}", default));
+                        match &p.default {
+                            Some(Value::String(default)) => {
+                                s.push_str(&format!(" = {:?}", default))
+                            }
+                            Some(default) => s.push_str(&format!(" = {}", default)),
+                            None => {}
                         }
                         s
                     }
@@ -4,14 +4,18 @@
 //! command structure. It handles nested commands, parameters, flags, and
 //! special cases like default subcommands.
 
-use super::ast::{Command, Directive, Parameter, Value};
-
+use super::ast::{find_command, Command, Directive, ParamKind, Parameter, Value};
+use super::directives::DirectiveResolver;
+use super::help::HelpFormatter;
+use super::type_validator::ParamType;
 use crate::constants::{
-    APP_NAME, BOOL_FALSE, BOOL_TRUE, DEFAULT_SUBCOMMAND, FLAG_COMPLETE, FLAG_CONFIG, FLAG_DRY_RUN,
-    FLAG_EXAMPLE, FLAG_SHOW, FLAG_UPDATE, FLAG_VERBOSE, FLAG_VERSION, FORMAT_AST, FORMAT_JSON,
-    SHORT_VERSION,
+    APP_NAME, BOOL_FALSE, BOOL_TRUE, COLOR_CHOICES, DEFAULT_SUBCOMMAND, FLAG_COLOR, FLAG_COMPLETE,
+    FLAG_CONFIG, FLAG_DRY_RUN, FLAG_EVENTS, FLAG_EVENTS_FILE, FLAG_EXAMPLE, FLAG_INTERACTIVE,
+    FLAG_OFFLINE, FLAG_PRESET, FLAG_SHOW, FLAG_UPDATE, FLAG_VERBOSE, FLAG_VERSION, FORMAT_AST,
+    FORMAT_JSON, FORMAT_JSONL, PRESET_PREFIX, SHORT_VERSION,
 };
-use clap::{Arg, ArgAction, Command as ClapCommand};
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgAction, Command as ClapCommand, ValueHint};
 
 // Removed: ShortAliasConflict - validation is now done in validator module
 
@@ -35,6 +39,12 @@ pub struct CliGenerator {
     commands: Vec<Command>,
     /// Pre-allocated static strings for default command parameters
     default_param_ids: std::collections::HashMap<String, &'static str>,
+    /// Whether every missing parameter is prompted for (`--interactive`)
+    interactive: bool,
+    /// Whether a unique prefix of a command name selects that command
+    prefix_matching: bool,
+    /// Whether missing values may be asked for on the terminal at all
+    prompting: bool,
 }
 
 impl CliGenerator {
@@ -52,9 +62,38 @@ impl CliGenerator {
         Self {
             commands,
             default_param_ids,
+            interactive: false,
+            prefix_matching: false,
+            prompting: true,
         }
     }
 
+    /// Enables prompting for every missing parameter, not only `@prompt` ones.
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Lets a unique prefix of a command name or alias stand for the command.
+    pub fn prefix_matching(mut self, prefix_matching: bool) -> Self {
+        self.prefix_matching = prefix_matching;
+        self
+    }
+
+    /// Turns terminal prompts off, e.g. when the terminal is owned by a TUI.
+    pub fn prompting(mut self, prompting: bool) -> Self {
+        self.prompting = prompting;
+        self
+    }
+
+    /// Returns `true` if a missing value for `param` should be asked for.
+    ///
+    /// Prompting needs the parameter to opt in with `@prompt` (or `--interactive`)
+    /// and a terminal on stdin; otherwise clap reports the missing value as before.
+    pub fn can_prompt(&self, param: &Parameter) -> bool {
+        self.prompting && (param.prompt || self.interactive) && super::input::is_interactive()
+    }
+
     fn preallocate_default_param_ids(
         commands: &[Command],
     ) -> std::collections::HashMap<String, &'static str> {
@@ -116,11 +155,12 @@ impl CliGenerator {
         // If validator is called first, this should not be needed
         let mut app = Self::create_base_cli();
 
-        for command in &self.commands {
+        // Private commands only run as dependencies or from other scripts
+        for command in self.commands.iter().filter(|c| !c.is_private()) {
             app = self.add_command_to_clap(app, command);
         }
 
-        Ok(app)
+        Ok(Self::add_command_table(app, &self.commands))
     }
 
     // Removed: validate_short_aliases, collect_short_aliases, collect_short_aliases_recursive
@@ -162,6 +202,13 @@ impl CliGenerator {
                     .hide(true)
                     .help("Specify path to configuration file"),
             )
+            .arg(
+                Arg::new(FLAG_OFFLINE)
+                    .long(FLAG_OFFLINE)
+                    .action(ArgAction::SetTrue)
+                    .hide(true)
+                    .help("Resolve remote includes from the local cache only"),
+            )
             .subcommand(
                 ClapCommand::new(FLAG_UPDATE)
                     .hide(true)
@@ -174,6 +221,12 @@ impl CliGenerator {
                     .action(ArgAction::SetTrue)
                     .help("Show what would be executed without actually running it"),
             )
+            .arg(
+                Arg::new(FLAG_INTERACTIVE)
+                    .long(FLAG_INTERACTIVE)
+                    .action(ArgAction::SetTrue)
+                    .help("Prompt for missing parameter values"),
+            )
             .arg(
                 Arg::new(FLAG_VERBOSE)
                     .long(FLAG_VERBOSE)
@@ -181,12 +234,34 @@ impl CliGenerator {
                     .action(ArgAction::SetTrue)
                     .help("Show detailed output including environment variables and working directory"),
             )
+            .arg(
+                Arg::new(FLAG_COLOR)
+                    .long(FLAG_COLOR)
+                    .value_name("WHEN")
+                    .value_parser(COLOR_CHOICES)
+                    .help("When to color output (auto, always, never)"),
+            )
+            .arg(
+                Arg::new(FLAG_EVENTS)
+                    .long(FLAG_EVENTS)
+                    .value_name("FORMAT")
+                    .value_parser([FORMAT_JSONL])
+                    .help("Write execution events (jsonl) to stdout instead of human output"),
+            )
+            .arg(
+                Arg::new(FLAG_EVENTS_FILE)
+                    .long(FLAG_EVENTS_FILE)
+                    .value_name("PATH")
+                    .requires(FLAG_EVENTS)
+                    .value_hint(ValueHint::FilePath)
+                    .help("Write the --events stream to PATH (e.g. /dev/fd/3) instead of stdout"),
+            )
             .arg(
                 Arg::new(FLAG_COMPLETE)
                     .long(FLAG_COMPLETE)
                     .value_name("SHELL")
                     .hide(true)
-                    .help("Generate and install shell completion (bash, zsh, fish, powershell, elvish). Use -V to show script content."),
+                    .help("Generate shell completion (bash, zsh, fish, powershell, elvish). Use --verbose to show script content; install it with `nest completion install`."),
             )
     }
 
@@ -194,18 +269,50 @@ impl CliGenerator {
         let cmd_name: &'static str = Box::leak(command.name.clone().into_boxed_str());
         let mut subcmd = ClapCommand::new(cmd_name).arg_required_else_help(false);
 
+        let aliases: Vec<&'static str> = DirectiveResolver::get_aliases(&command.directives)
+            .into_iter()
+            .map(|alias| &*Box::leak(alias.into_boxed_str()))
+            .collect();
+        subcmd = subcmd.visible_aliases(aliases);
+
         subcmd = Self::add_description(subcmd, &command.directives);
         subcmd = Self::add_parameters(subcmd, &command.parameters, self);
         subcmd = Self::add_default_args_if_needed(subcmd, command, self);
+        subcmd = Self::add_preset_arg(subcmd, Self::preset_owner(command));
 
-        for child in &command.children {
+        for child in command.children.iter().filter(|c| !c.is_private()) {
             subcmd = self.add_command_to_clap(subcmd, child);
         }
+        subcmd = Self::add_command_table(subcmd, &command.children);
 
         app = app.subcommand(subcmd);
         app
     }
 
+    /// Replaces clap's flat subcommand list in `--help` with the sectioned
+    /// command table from `HelpFormatter`.
+    fn add_command_table(cmd: ClapCommand, commands: &[Command]) -> ClapCommand {
+        let table = HelpFormatter::command_table(commands, false);
+        if table.is_empty() {
+            return cmd;
+        }
+
+        let header = cmd.get_styles().get_header();
+        let heading =
+            |title: &str| format!("{}{}{}", header.render(), title, header.render_reset());
+        let mut template =
+            String::from("{before-help}{about-with-newline}\n{usage-heading} {usage}\n\n");
+        template.push_str(&table);
+        if cmd.get_positionals().next().is_some() {
+            template.push_str(&format!("\n{}\n{{positionals}}\n", heading("Arguments:")));
+        }
+        template.push_str(&format!(
+            "\n{}\n{{options}}{{after-help}}",
+            heading("Options:")
+        ));
+        cmd.help_template(template)
+    }
+
     fn add_description(mut subcmd: ClapCommand, directives: &[Directive]) -> ClapCommand {
         if let Some(desc) = Self::get_directive_value(directives, "desc") {
             subcmd = subcmd.about(desc);
@@ -218,8 +325,6 @@ impl CliGenerator {
         parameters: &[Parameter],
         generator: &CliGenerator,
     ) -> ClapCommand {
-        use super::ast::ParamKind;
-
         // First, add all named arguments (they don't use indices)
         for param in parameters {
             if param.is_named {
@@ -236,11 +341,16 @@ impl CliGenerator {
         let mut positional_index = 1; // Start from 1 (0 is command name)
         let positional_params: Vec<&Parameter> =
             parameters.iter().filter(|p| !p.is_named).collect();
+        let prompts = generator.positional_prompts(&positional_params);
 
         for (idx, param) in positional_params.iter().enumerate() {
             match &param.kind {
                 ParamKind::Normal => {
-                    let arg = generator.parameter_to_arg_positional(param, positional_index);
+                    let arg = generator.parameter_to_arg_positional(
+                        param,
+                        positional_index,
+                        prompts[idx],
+                    );
                     subcmd = subcmd.arg(arg);
                     positional_index += 1;
                 }
@@ -284,6 +394,177 @@ impl CliGenerator {
         subcmd
     }
 
+    /// Adds `--preset NAME` to commands that define presets.
+    ///
+    /// Required arguments that some preset sets are only required without
+    /// `--preset`; a preset lacking them is reported when arguments are extracted.
+    fn add_preset_arg(subcmd: ClapCommand, command: &Command) -> ClapCommand {
+        let presets = DirectiveResolver::get_presets(&command.directives);
+        if presets.is_empty() {
+            return subcmd;
+        }
+
+        let preset_params: Vec<&str> = presets
+            .iter()
+            .flat_map(|(_, values)| values.iter().map(|(param, _)| param.as_str()))
+            .collect();
+        let names: Vec<&'static str> = presets
+            .iter()
+            .map(|(name, _)| &*Box::leak(name.clone().into_boxed_str()))
+            .collect();
+
+        subcmd
+            .mut_args(|arg| {
+                if arg.is_required_set() && preset_params.contains(&arg.get_id().as_str()) {
+                    arg.required(false).required_unless_present(FLAG_PRESET)
+                } else {
+                    arg
+                }
+            })
+            .arg(
+                Arg::new(FLAG_PRESET)
+                    .long(FLAG_PRESET)
+                    .value_name("NAME")
+                    .value_parser(PossibleValuesParser::new(names))
+                    .help("Use a saved set of argument values (also: @NAME)"),
+            )
+    }
+
+    /// Returns the command whose presets apply when `command` is invoked:
+    /// groups take the presets of their default subcommand, like its arguments.
+    fn preset_owner(command: &Command) -> &Command {
+        command
+            .children
+            .iter()
+            .find(|c| c.name == DEFAULT_SUBCOMMAND)
+            .unwrap_or(command)
+    }
+
+    /// Expands command name prefixes in `args` when prefix matching is enabled.
+    ///
+    /// Starting at `start`, each argument that is not an exact command name or
+    /// alias but a prefix of exactly one command is replaced by its name
+    /// (`nest dep` → `nest deploy`). An ambiguous prefix is an error listing the
+    /// candidates; an unknown one is left for clap to report with its suggestion.
+    pub fn expand_command_prefixes(
+        &self,
+        args: &[String],
+        start: usize,
+    ) -> Result<Vec<String>, String> {
+        let mut expanded = args.to_vec();
+        if !self.prefix_matching {
+            return Ok(expanded);
+        }
+
+        let mut current = &self.commands;
+        for arg in expanded.iter_mut().skip(start) {
+            if arg.starts_with('-') || current.is_empty() {
+                break;
+            }
+            if let Some(found) = current.iter().find(|c| c.matches_name(arg)) {
+                current = &found.children;
+                continue;
+            }
+
+            let candidates: Vec<&Command> = current
+                .iter()
+                .filter(|c| !c.is_private())
+                .filter(|c| {
+                    c.name.starts_with(arg.as_str())
+                        || DirectiveResolver::get_aliases(&c.directives)
+                            .iter()
+                            .any(|alias| alias.starts_with(arg.as_str()))
+                })
+                .collect();
+            match candidates.as_slice() {
+                [] => break,
+                [found] => {
+                    *arg = found.name.clone();
+                    current = &found.children;
+                }
+                _ => {
+                    let names: Vec<&str> = candidates.iter().map(|c| c.name.as_str()).collect();
+                    return Err(format!(
+                        "Command '{}' is ambiguous. Did you mean one of: {}?",
+                        arg,
+                        names.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Rejects a command path in `args` (starting at `start`) that leads to a
+    /// private command, which may only run as a dependency or from a script.
+    pub fn check_private_command(&self, args: &[String], start: usize) -> Result<(), String> {
+        let mut current = &self.commands;
+        let mut path = Vec::new();
+        for arg in args.iter().skip(start) {
+            let Some(found) = current.iter().find(|c| c.matches_name(arg)) else {
+                break;
+            };
+            path.push(found.name.as_str());
+            if found.is_private() {
+                return Err(format!(
+                    "Command '{}' is private and can only run as a dependency or from another command's script",
+                    path.join(" ")
+                ));
+            }
+            current = &found.children;
+        }
+        Ok(())
+    }
+
+    /// Rewrites the preset shorthand `nest deploy @prod` into `--preset prod`.
+    ///
+    /// Only commands defining presets are affected. The first `@NAME` naming one
+    /// of their presets is rewritten, as is an unknown `@NAME` right after the
+    /// command path (so clap lists the valid ones); other values beginning with
+    /// `@` are passed through untouched.
+    pub fn expand_preset_shorthand(&self, args: &[String], start: usize) -> Vec<String> {
+        let mut current = &self.commands;
+        let mut command = None;
+        let mut index = start;
+        while let Some(found) = args
+            .get(index)
+            .and_then(|arg| current.iter().find(|c| c.matches_name(arg)))
+        {
+            command = Some(found);
+            current = &found.children;
+            index += 1;
+        }
+
+        let presets: Vec<String> = command
+            .map(|c| DirectiveResolver::get_presets(&Self::preset_owner(c).directives))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        let mut expanded = args.to_vec();
+        if presets.is_empty() {
+            return expanded;
+        }
+
+        let shorthand = |arg: &String| {
+            arg.strip_prefix(PRESET_PREFIX)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+        };
+        let found = args[index..]
+            .iter()
+            .take_while(|arg| arg.as_str() != "--")
+            .position(|arg| shorthand(arg).is_some_and(|name| presets.contains(&name)))
+            .map(|offset| index + offset)
+            .or_else(|| args.get(index).and_then(shorthand).map(|_| index));
+
+        if let Some(position) = found {
+            let name = shorthand(&args[position]).unwrap_or_default();
+            expanded.splice(position..=position, [format!("--{}", FLAG_PRESET), name]);
+        }
+        expanded
+    }
+
     fn add_default_args_if_needed(
         mut subcmd: ClapCommand,
         command: &Command,
@@ -305,13 +586,15 @@ impl CliGenerator {
                 }
 
                 // Then, add all positional arguments with sequential indices
-                let mut positional_index = 1;
-                for param in &default_cmd.parameters {
-                    if !param.is_named {
-                        let arg = generator.parameter_to_arg_positional(param, positional_index);
-                        subcmd = subcmd.arg(arg);
-                        positional_index += 1;
-                    }
+                let positional_params: Vec<&Parameter> = default_cmd
+                    .parameters
+                    .iter()
+                    .filter(|p| !p.is_named)
+                    .collect();
+                let prompts = generator.positional_prompts(&positional_params);
+                for (idx, param) in positional_params.iter().enumerate() {
+                    let arg = generator.parameter_to_arg_positional(param, idx + 1, prompts[idx]);
+                    subcmd = subcmd.arg(arg);
                 }
             }
         }
@@ -323,13 +606,41 @@ impl CliGenerator {
 
         match param.param_type.as_str() {
             "bool" => Self::build_bool_flag(&mut arg, param, param_id),
-            _ => Self::build_value_arg(&mut arg, param, param_id),
+            _ => {
+                Self::build_value_arg(&mut arg, param, param_id, self.can_prompt(param));
+                arg = Self::apply_value_type(arg, param);
+                // `--tag a --tag b` collects every occurrence for arr and map params
+                if ParamType::parse(&param.param_type).is_ok_and(|t| t.is_repeatable()) {
+                    arg = arg.action(ArgAction::Append);
+                }
+            }
         }
 
-        arg
+        Self::apply_env(arg, param)
     }
 
-    fn parameter_to_arg_positional(&self, param: &Parameter, index: usize) -> Arg {
+    /// Decides which positional parameters may be left out for prompting.
+    ///
+    /// clap rejects an optional positional before a required one, so a
+    /// positional is only prompted for when every later one is optional too.
+    fn positional_prompts(&self, positionals: &[&Parameter]) -> Vec<bool> {
+        let mut later_required = false;
+        let mut prompts: Vec<bool> = positionals
+            .iter()
+            .rev()
+            .map(|param| {
+                let prompt = self.can_prompt(param) && !later_required;
+                if matches!(param.kind, ParamKind::Normal) && param.default.is_none() && !prompt {
+                    later_required = true;
+                }
+                prompt
+            })
+            .collect();
+        prompts.reverse();
+        prompts
+    }
+
+    fn parameter_to_arg_positional(&self, param: &Parameter, index: usize, prompt: bool) -> Arg {
         let param_name: &'static str = Box::leak(param.name.clone().into_boxed_str());
         let mut arg = Arg::new(param_name).index(index);
 
@@ -337,8 +648,8 @@ impl CliGenerator {
             "bool" => {
                 // Positional bool arguments are not common, but we'll support them
                 let help_text = format!("Positional argument: {} (bool)", param.name);
-                // If no default value, make it required
-                if param.default.is_none() {
+                // If no default value, make it required (unless it can be prompted for)
+                if param.default.is_none() && !prompt {
                     arg = arg.required(true).help(help_text);
                 } else {
                     arg = arg.required(false).help(help_text);
@@ -353,16 +664,61 @@ impl CliGenerator {
                         param.name, param.param_type
                     )
                 };
-                // If no default value, make it required
-                if param.default.is_none() {
+                // If no default value, make it required (unless it can be prompted for)
+                if param.default.is_none() && !prompt {
                     arg = arg.required(true).help(help_text);
                 } else {
                     arg = arg.required(false).help(help_text);
                 }
+                arg = Self::apply_value_type(arg, param);
             }
         }
 
-        arg
+        Self::apply_env(arg, param)
+    }
+
+    /// Falls back to the parameter's `@env(NAME)` variable when the argument is
+    /// not passed; `--help` lists it as `[env: NAME]` without the current value.
+    fn apply_env(arg: Arg, param: &Parameter) -> Arg {
+        match &param.env {
+            Some(env) => arg
+                .env(&*Box::leak(env.clone().into_boxed_str()))
+                .hide_env_values(true),
+            None => arg,
+        }
+    }
+
+    /// Attaches a value parser and completion hint matching the parameter type.
+    ///
+    /// Enums use clap's possible values, so `--help` and shell completion list
+    /// them. Other typed parameters are checked by [`ParamType::validate`]
+    /// while parsing; values are still passed on as strings.
+    fn apply_value_type(arg: Arg, param: &Parameter) -> Arg {
+        let Ok(param_type) = ParamType::parse(&param.param_type) else {
+            return arg;
+        };
+
+        let arg = match param_type {
+            ParamType::Path | ParamType::File => arg.value_hint(ValueHint::FilePath),
+            ParamType::Dir => arg.value_hint(ValueHint::DirPath),
+            ParamType::Url => arg.value_hint(ValueHint::Url),
+            _ => arg,
+        };
+
+        if let Some(values) = param_type.possible_values() {
+            let values: Vec<&'static str> = values
+                .iter()
+                .map(|v| &*Box::leak(v.clone().into_boxed_str()))
+                .collect();
+            return arg.value_parser(PossibleValuesParser::new(values));
+        }
+
+        match param_type {
+            ParamType::Str | ParamType::Path | ParamType::Arr(None) => arg,
+            _ => arg.value_parser(move |value: &str| {
+                param_type.validate(value).map(|_| value.to_string())
+            }),
+        }
     }
 
     fn build_bool_flag(arg: &mut Arg, param: &Parameter, param_id: &'static str) {
@@ -386,8 +742,8 @@ impl CliGenerator {
         *arg = new_arg;
     }
 
-    fn build_value_arg(arg: &mut Arg, param: &Parameter, param_id: &'static str) {
-        if param.default.is_some() {
+    fn build_value_arg(arg: &mut Arg, param: &Parameter, param_id: &'static str, prompt: bool) {
+        if param.default.is_some() || prompt {
             Self::build_optional_arg(arg, param, param_id);
         } else {
             Self::build_required_arg(arg, param, param_id);
@@ -502,19 +858,7 @@ impl CliGenerator {
 
     /// Finds a command by its path.
     pub fn find_command(&self, path: &[String]) -> Option<&Command> {
-        let mut current = &self.commands;
-        let mut found: Option<&Command> = None;
-
-        for name in path {
-            found = current.iter().find(|c| &c.name == name);
-            if let Some(cmd) = found {
-                current = &cmd.children;
-            } else {
-                return None;
-            }
-        }
-
-        found
+        find_command(&self.commands, path)
     }
 
     pub fn has_default_command(&self, command: &Command) -> bool {
@@ -524,3 +868,136 @@ impl CliGenerator {
             .any(|c| c.name == DEFAULT_SUBCOMMAND)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nestparse::args::ArgumentExtractor;
+    use crate::nestparse::parser::Parser;
+
+    #[test]
+    fn test_preset_shorthand_expansion() {
+        let content = r#"deploy(!env: str = "dev", !tags: arr = []):
+    preset prod: env="prod", tags=["a", "b"]
+    script: echo $env
+"#;
+        let mut parser = Parser::new(content);
+        let commands = parser.parse().unwrap().commands;
+        let presets = DirectiveResolver::get_presets(&commands[0].directives);
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].1[0].0, "env");
+        assert!(matches!(&presets[0].1[1].1, Value::Array(items) if items == &["a", "b"]));
+
+        let generator = CliGenerator::new(commands);
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            generator.expand_preset_shorthand(&args(&["nest", "deploy", "--env", "x", "@prod"]), 1),
+            args(&["nest", "deploy", "--env", "x", "--preset", "prod"])
+        );
+        assert_eq!(
+            generator.expand_preset_shorthand(&args(&["nest", "deploy", "--", "@prod"]), 1),
+            args(&["nest", "deploy", "--", "@prod"])
+        );
+    }
+
+    #[test]
+    fn test_env_bound_parameter_values() {
+        const VAR: &str = "NEST_TEST_DEPLOY_REGION";
+        let content = r#"deploy(!region: str = "eu" @env(NEST_TEST_DEPLOY_REGION)):
+    script: echo $region
+"#;
+        let mut parser = Parser::new(content);
+        let generator = CliGenerator::new(parser.parse().unwrap().commands);
+        let path = vec!["deploy".to_string()];
+        let parameters = &generator.find_command(&path).unwrap().parameters;
+
+        let region = |env: Option<&str>, cli_args: &[&str]| {
+            match env {
+                Some(value) => std::env::set_var(VAR, value),
+                None => std::env::remove_var(VAR),
+            }
+            let matches = generator
+                .build_cli()
+                .unwrap()
+                .get_matches_from(["nest", "deploy"].iter().chain(cli_args));
+            let (_, sub_matches) = matches.subcommand().unwrap();
+            ArgumentExtractor::extract_from_matches(sub_matches, parameters, &generator, &path)
+                .unwrap()
+                .remove("region")
+        };
+
+        assert_eq!(region(Some("us"), &[]), Some("us".to_string()));
+        // An empty variable counts as unset, so the default applies
+        assert_eq!(region(Some(""), &[]), Some("eu".to_string()));
+        assert_eq!(region(None, &[]), Some("eu".to_string()));
+        assert_eq!(
+            region(Some("us"), &["--region", "ap"]),
+            Some("ap".to_string())
+        );
+        std::env::remove_var(VAR);
+    }
+
+    #[test]
+    fn test_command_aliases_and_prefixes() {
+        let content = r#"deploy:
+    alias: ship
+    script: echo d
+dev:
+    serve:
+        alias: s
+        script: echo s
+"#;
+        let mut parser = Parser::new(content);
+        let commands = parser.parse().unwrap().commands;
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let generator = CliGenerator::new(commands.clone());
+        assert!(generator.find_command(&args(&["dev", "s"])).is_some());
+        assert_eq!(
+            generator.expand_command_prefixes(&args(&["nest", "dep"]), 1),
+            Ok(args(&["nest", "dep"]))
+        );
+
+        let generator = CliGenerator::new(commands).prefix_matching(true);
+        assert_eq!(
+            generator.expand_command_prefixes(&args(&["nest", "sh", "-x"]), 1),
+            Ok(args(&["nest", "deploy", "-x"]))
+        );
+        assert_eq!(
+            generator.expand_command_prefixes(&args(&["nest", "dev", "se", "dep"]), 1),
+            Ok(args(&["nest", "dev", "serve", "dep"]))
+        );
+        assert!(generator
+            .expand_command_prefixes(&args(&["nest", "de"]), 1)
+            .unwrap_err()
+            .contains("deploy, dev"));
+    }
+
+    #[test]
+    fn test_private_commands_are_hidden() {
+        let content = r#"_setup:
+    script: echo s
+prepare:
+    private
+    script: echo p
+build:
+    depends: _setup, prepare
+    script: echo b
+"#;
+        let mut parser = Parser::new(content);
+        let generator = CliGenerator::new(parser.parse().unwrap().commands);
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let cli = generator.build_cli().unwrap();
+        assert!(cli.find_subcommand("build").is_some());
+        assert!(cli.find_subcommand("_setup").is_none());
+        assert!(cli.find_subcommand("prepare").is_none());
+        assert!(generator.find_command(&args(&["prepare"])).is_some());
+        assert!(generator
+            .check_private_command(&args(&["nest", "build"]), 1)
+            .is_ok());
+        assert!(generator
+            .check_private_command(&args(&["nest", "_setup"]), 1)
+            .is_err());
+    }
+}
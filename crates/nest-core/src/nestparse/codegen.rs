@@ -3,7 +3,7 @@
 //! This module is responsible for reconstructing valid Nestfile syntax from
 //! parsed Command structures. This is primarily used by the filtered include system.
 
-use super::ast::{Command, Directive, ParamKind, Value};
+use super::ast::{Command, Directive, MergeMode, ParamKind, Value};
 use crate::constants::INDENT_SIZE;
 
 /// Converts a Command AST back to a Nestfile string representation.
@@ -33,8 +33,13 @@ pub fn to_nestfile_string(command: &Command, indent: usize) -> String {
         ));
     }
 
-    // 3. Command signature
+    // 3. Command signature (`extends` is already applied, only the merge mode is kept)
     result.push_str(&indent_str);
+    match command.merge_mode {
+        MergeMode::Override => result.push_str("override "),
+        MergeMode::Append => result.push_str("append "),
+        MergeMode::Implicit => {}
+    }
     result.push_str(&command.name);
 
     if !command.parameters.is_empty() {
@@ -58,6 +63,15 @@ pub fn to_nestfile_string(command: &Command, indent: usize) -> String {
                         s.push_str(" = ");
                         s.push_str(&value_to_string(default));
                     }
+                    if p.prompt {
+                        s.push_str(" @prompt");
+                    }
+                    if p.secret {
+                        s.push_str(" @secret");
+                    }
+                    if let Some(env) = &p.env {
+                        s.push_str(&format!(" @env({})", env));
+                    }
                     s
                 }
                 ParamKind::Wildcard { name, count } => {
@@ -88,6 +102,19 @@ pub fn to_nestfile_string(command: &Command, indent: usize) -> String {
             Directive::Desc(s) => {
                 result.push_str(&format!("{}desc: {}\n", inner_indent_str, s));
             }
+            Directive::Category(category) => {
+                result.push_str(&format!("{}category: {}\n", inner_indent_str, category));
+            }
+            Directive::HelpOrder(order) => {
+                result.push_str(&format!("{}help.order: {}\n", inner_indent_str, order));
+            }
+            Directive::Alias(aliases) => {
+                result.push_str(&format!(
+                    "{}alias: {}\n",
+                    inner_indent_str,
+                    aliases.join(", ")
+                ));
+            }
             Directive::Cwd(s) => {
                 result.push_str(&format!("{}cwd: {}\n", inner_indent_str, s));
             }
@@ -129,6 +156,11 @@ pub fn to_nestfile_string(command: &Command, indent: usize) -> String {
             Directive::Privileged(val) => {
                 result.push_str(&format!("{}privileged: {}\n", inner_indent_str, val));
             }
+            Directive::Private(val) => {
+                if *val {
+                    result.push_str(&format!("{}private\n", inner_indent_str));
+                }
+            }
             Directive::Logs(path, fmt) => {
                 result.push_str(&format!("{}logs.{}: {}\n", inner_indent_str, fmt, path));
             }
@@ -146,6 +178,24 @@ pub fn to_nestfile_string(command: &Command, indent: usize) -> String {
                     inner_indent_str, target, rule
                 ));
             }
+            Directive::Complete(target, source) => {
+                result.push_str(&format!(
+                    "{}complete.{}: {}\n",
+                    inner_indent_str, target, source
+                ));
+            }
+            Directive::Preset(name, values) => {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|(param, value)| format!("{}={}", param, value_to_string(value)))
+                    .collect();
+                result.push_str(&format!(
+                    "{}preset {}: {}\n",
+                    inner_indent_str,
+                    name,
+                    values.join(", ")
+                ));
+            }
             // Script directives
             Directive::Script(s, os, hide) => {
                 let mut name = String::from("script");
@@ -11,6 +11,7 @@ use super::cli::CliGenerator;
 use super::help::HelpFormatter;
 use super::runtime::Runtime;
 use crate::constants::{DEFAULT_SUBCOMMAND, FLAG_DRY_RUN, FLAG_VERBOSE};
+use crate::error::NestError;
 use clap::ArgMatches;
 
 /// Handles command execution routing and orchestration.
@@ -32,12 +33,11 @@ impl CommandHandler {
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` if help was displayed successfully,
-    /// `Err(())` otherwise.
+    /// Returns `Ok(())` if help was displayed successfully.
     pub fn handle_group_without_default(
         command: &Command,
         command_path: &[String],
-    ) -> Result<(), String> {
+    ) -> Result<(), NestError> {
         HelpFormatter::print_group_help(command, command_path);
         Ok(())
     }
@@ -58,7 +58,7 @@ impl CommandHandler {
     /// # Returns
     ///
     /// Returns `Ok(())` if execution succeeded,
-    /// `Err(message)` if execution failed.
+    /// `Err(error)` if execution failed.
     ///
     /// # Errors
     ///
@@ -71,7 +71,7 @@ impl CommandHandler {
         generator: &CliGenerator,
         runtime: &Runtime,
         root_matches: &ArgMatches,
-    ) -> Result<(), String> {
+    ) -> Result<(), NestError> {
         let default_path = {
             let mut path = command_path.to_vec();
             path.push(DEFAULT_SUBCOMMAND.to_string());
@@ -82,24 +82,16 @@ impl CommandHandler {
         // But generator is used for parsing args, so let's stick with generator for lookups where consistent.
         let default_cmd = generator
             .find_command(&default_path)
-            .ok_or_else(|| "Default command not found".to_string())?;
+            .ok_or_else(|| NestError::Other("Default command not found".to_string()))?;
 
         let matches_for_args = Self::get_group_matches(matches);
-        let args = match ArgumentExtractor::extract_for_default_command(
+        let args = ArgumentExtractor::extract_for_default_command(
             matches_for_args,
             &default_cmd.parameters,
             generator,
             &default_path,
-        ) {
-            Ok(args) => args,
-            Err(validation_errors) => {
-                use super::output::OutputFormatter;
-                for error in &validation_errors {
-                    OutputFormatter::error(error);
-                }
-                return Err("Type validation failed".to_string());
-            }
-        };
+        )
+        .map_err(|errors| Self::invalid_arguments(&default_path, errors))?;
 
         // Extract parent command arguments (from the group command)
         let parent_args = Self::extract_parent_args(root_matches, command_path, generator);
@@ -135,7 +127,7 @@ impl CommandHandler {
     /// # Returns
     ///
     /// Returns `Ok(())` if execution succeeded,
-    /// `Err(message)` if execution failed.
+    /// `Err(error)` if execution failed.
     ///
     /// # Errors
     ///
@@ -149,22 +141,14 @@ impl CommandHandler {
         runtime: &Runtime,
         command_path: &[String],
         root_matches: &ArgMatches,
-    ) -> Result<(), String> {
-        let args = match ArgumentExtractor::extract_from_matches(
+    ) -> Result<(), NestError> {
+        let args = ArgumentExtractor::extract_from_matches(
             matches,
             &command.parameters,
             generator,
             command_path,
-        ) {
-            Ok(args) => args,
-            Err(validation_errors) => {
-                use super::output::OutputFormatter;
-                for error in &validation_errors {
-                    OutputFormatter::error(error);
-                }
-                return Err("Type validation failed".to_string());
-            }
-        };
+        )
+        .map_err(|errors| Self::invalid_arguments(command_path, errors))?;
 
         // Extract parent command arguments (if this is a nested command)
         // Use root_matches to access parent command arguments
@@ -185,6 +169,161 @@ impl CommandHandler {
         )
     }
 
+    /// Parses a full command line and runs the command it selects.
+    ///
+    /// `args` starts with the program name, like `std::env::args()`. This is the
+    /// path the `nest` binary takes minus everything that needs the terminal or
+    /// exits the process, so embedders (the TUI) can run commands in-process:
+    /// parse errors, `--help` output and groups without a default subcommand
+    /// all come back as [`NestError::Usage`] instead of being printed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the arguments don't select a runnable command or the
+    /// command fails.
+    pub fn execute_args(
+        args: &[String],
+        generator: &CliGenerator,
+        runtime: &Runtime,
+    ) -> Result<(), NestError> {
+        let (matches, command_path) = Self::parse_args(args, generator)?;
+        Self::execute_matches(&matches, &command_path, generator, runtime)
+    }
+
+    /// The parsing half of [`execute_args`](Self::execute_args).
+    ///
+    /// Returns the matches and the path of the selected command.
+    pub fn parse_args(
+        args: &[String],
+        generator: &CliGenerator,
+    ) -> Result<(ArgMatches, Vec<String>), NestError> {
+        let start = args
+            .iter()
+            .skip(1)
+            .position(|arg| !arg.starts_with('-'))
+            .map_or(args.len(), |i| i + 1);
+        let args = generator
+            .expand_command_prefixes(args, start)
+            .map_err(NestError::Usage)?;
+        generator
+            .check_private_command(&args, start)
+            .map_err(NestError::Usage)?;
+        let args = generator.expand_preset_shorthand(&args, start);
+
+        let matches = generator
+            .build_cli()?
+            .try_get_matches_from(&args)
+            .map_err(|e| NestError::Usage(e.render().to_string()))?;
+
+        let mut command_path = Vec::new();
+        let mut current = &matches;
+        while let Some((name, sub_matches)) = current.subcommand() {
+            command_path.push(name.to_string());
+            current = sub_matches;
+        }
+        if command_path.is_empty() {
+            return Err(NestError::Usage("No command given".to_string()));
+        }
+        Ok((matches, command_path))
+    }
+
+    /// The running half of [`execute_args`](Self::execute_args).
+    pub fn execute_matches(
+        matches: &ArgMatches,
+        command_path: &[String],
+        generator: &CliGenerator,
+        runtime: &Runtime,
+    ) -> Result<(), NestError> {
+        let command = generator.find_command(command_path).ok_or_else(|| {
+            NestError::Usage(format!("Command not found: {}", command_path.join(" ")))
+        })?;
+        if command.children.is_empty() {
+            let mut current = matches;
+            while let Some((_, sub_matches)) = current.subcommand() {
+                current = sub_matches;
+            }
+            Self::handle_regular_command(
+                current,
+                command,
+                generator,
+                runtime,
+                command_path,
+                matches,
+            )
+        } else if generator.has_default_command(command) {
+            Self::handle_default_command(matches, command_path, generator, runtime, matches)
+        } else {
+            Err(NestError::Usage(format!(
+                "'{}' is a group; choose one of its commands",
+                command_path.join(" ")
+            )))
+        }
+    }
+
+    /// Checks a full command line the way [`execute_args`](Self::execute_args)
+    /// would, without running anything: parsing, argument types and the
+    /// command's `validate` rules.
+    ///
+    /// Errors are plain messages, one per line for invalid arguments, so
+    /// callers can show them inline (the TUI's argument form).
+    pub fn check_args(
+        args: &[String],
+        generator: &CliGenerator,
+        runtime: &Runtime,
+    ) -> Result<(), NestError> {
+        let (matches, command_path) = Self::parse_args(args, generator)?;
+        let mut current = &matches;
+        while let Some((_, sub_matches)) = current.subcommand() {
+            current = sub_matches;
+        }
+
+        let command = generator.find_command(&command_path).ok_or_else(|| {
+            NestError::Usage(format!("Command not found: {}", command_path.join(" ")))
+        })?;
+        let (command, target_path) = if command.children.is_empty() {
+            (command, command_path.clone())
+        } else if generator.has_default_command(command) {
+            let mut default_path = command_path.clone();
+            default_path.push(DEFAULT_SUBCOMMAND.to_string());
+            let default_cmd = generator
+                .find_command(&default_path)
+                .ok_or_else(|| NestError::Other("Default command not found".to_string()))?;
+            (default_cmd, default_path)
+        } else {
+            return Err(NestError::Usage(format!(
+                "'{}' is a group; choose one of its commands",
+                command_path.join(" ")
+            )));
+        };
+
+        let args = if target_path.len() > command_path.len() {
+            ArgumentExtractor::extract_for_default_command(
+                current,
+                &command.parameters,
+                generator,
+                &target_path,
+            )
+        } else {
+            ArgumentExtractor::extract_from_matches(
+                current,
+                &command.parameters,
+                generator,
+                &target_path,
+            )
+        }
+        .map_err(|errors| Self::invalid_arguments(&target_path, errors))?;
+
+        let parent_args = Self::extract_parent_args(&matches, &command_path, generator);
+        runtime.validate_arguments(command, &args, &target_path, &parent_args)
+    }
+
+    fn invalid_arguments(command_path: &[String], errors: Vec<String>) -> NestError {
+        NestError::InvalidArguments {
+            command_path: command_path.to_vec(),
+            errors,
+        }
+    }
+
     fn get_group_matches(matches: &ArgMatches) -> &ArgMatches {
         matches
             .subcommand()
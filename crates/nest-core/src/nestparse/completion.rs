@@ -7,49 +7,67 @@
 //!
 //! - **Automatic generation**: Completion scripts are automatically generated when nestfile changes
 //! - **Multi-shell support**: Supports bash, zsh, fish, PowerShell, and elvish
-//! - **Auto-installation**: Automatically installs completion in shell configuration files
+//! - **Explicit installation**: `nest completion install` writes the script where the shell
+//!   loads it from; shell startup files are only touched when the shell needs it
 //! - **Smart caching**: Uses SHA256 hashing to detect nestfile changes and regenerate only when needed
-//! - **Manual control**: Use `nest --complete <shell>` to manually generate and install completion
 //!
 //! ## Usage
 //!
-//! ### Automatic Installation
+//! ```bash
+//! # Install completion for the current shell (or name one: bash, zsh, fish, ...)
+//! nest completion install
+//! nest completion install zsh
+//!
+//! # Print the script instead, e.g. for dotfiles managed elsewhere
+//! nest completion install zsh --print
 //!
-//! Completion is automatically installed when you run any `nest` command. The system:
-//! 1. Detects your current shell
-//! 2. Generates completion scripts for all supported shells
-//! 3. Installs completion in your shell's configuration file
-//! 4. Sources the completion in the current terminal session (if possible)
+//! # Show where completion is installed, and remove it again
+//! nest completion status
+//! nest completion uninstall zsh
+//! ```
 //!
-//! ### Manual Installation
+//! Installed scripts are rewritten whenever they are regenerated for a changed nestfile.
 //!
-//! ```bash
-//! # Generate and install completion for current shell
-//! nest --complete zsh
+//! ## Dynamic Values
 //!
-//! # View completion script content
-//! nest --complete zsh -V
+//! A `complete.PARAM` directive gives a parameter a completion source: a
+//! `$(command)` or the name of a function returning whitespace-separated values.
 //!
-//! # Generate for specific shell
-//! nest --complete bash
-//! nest --complete fish
+//! ```text
+//! deploy(!ns: str):
+//!     complete.ns: $(kubectl get ns -o name)
+//!     script: ...
 //! ```
 //!
+//! The bash, zsh and fish scripts call back into `nest --complete-values <command> <param>`
+//! at tab time; results are cached for a minute. PowerShell and elvish only get the
+//! static completion. `enum` and path-like types complete through the static scripts.
+//!
 //! ## File Locations
 //!
 //! - **Completion scripts**: `~/.cache/nest/completions/`
 //! - **Hash file**: `~/.cache/nest/completions/nestfile.hash`
-//! - **Shell configs**: Automatically added to `.zshrc`, `.bashrc`, etc.
+//! - **Cached values**: `~/.cache/nest/values/`
 //!
-//! ## Supported Shells
+//! ## Installed Locations
 //!
-//! - **Bash**: Completion added to `~/.bashrc` or `~/.bash_profile`
-//! - **Zsh**: Completion added to `~/.zshrc`
-//! - **Fish**: Completion copied to `~/.config/fish/completions/nest.fish`
-//! - **PowerShell**: Completion added to PowerShell profile
-//! - **Elvish**: Completion added to `~/.elvish/rc.elv`
-
-use crate::constants::APP_NAME;
+//! `$XDG_DATA_HOME` defaults to `~/.local/share`.
+//!
+//! - **Bash**: `$XDG_DATA_HOME/bash-completion/completions/nest` (or `$BASH_COMPLETION_USER_DIR`),
+//!   loaded by the bash-completion package
+//! - **Zsh**: `$XDG_DATA_HOME/zsh/site-functions/_nest`, added to `fpath` by a block in `.zshrc`
+//! - **Fish**: `$XDG_DATA_HOME/fish/vendor_completions.d/nest.fish`
+//! - **PowerShell**: `$XDG_DATA_HOME/nest/completions/nest.ps1`, sourced from the profile
+//! - **Elvish**: `$XDG_DATA_HOME/nest/completions/nest.elv`, evaluated from `rc.elv`
+//!
+//! Blocks in startup files sit between `# >>> nest completion >>>` and
+//! `# <<< nest completion <<<`, so `nest completion uninstall` can remove them cleanly.
+
+use super::ast::{find_command, Command, ParamKind, Parameter};
+use super::directives::DirectiveResolver;
+use super::runtime::Runtime;
+use super::type_validator::ParamType;
+use crate::constants::{APP_NAME, COMPLETION_VALUES_TTL_SECS, DEFAULT_SUBCOMMAND};
 use clap::Command as ClapCommand;
 use sha2::{Digest, Sha256};
 use std::fs;
@@ -183,16 +201,9 @@ impl CompletionManager {
         Ok(current_hash.trim() != stored_hash.trim())
     }
 
-    /// Generate completion script for a specific shell
-    pub fn generate_completion(
-        &self,
-        shell: Shell,
-        cli: &mut ClapCommand,
-        nestfile_path: &Path,
-    ) -> Result<PathBuf, String> {
-        let script_path = self.get_completion_script_path(shell, nestfile_path);
-
-        // Generate completion script
+    /// Render the completion script for a shell, followed by the dynamic
+    /// value completion glue when the nestfile declares `complete.` sources.
+    pub fn render_script(shell: Shell, cli: &mut ClapCommand, commands: &[Command]) -> Vec<u8> {
         let mut buffer = Vec::new();
         match shell {
             Shell::Bash => {
@@ -217,6 +228,126 @@ impl CompletionManager {
             }
         }
 
+        let mut targets = CompletionTargets::default();
+        collect_targets(commands, "", &mut targets);
+        if let Some(glue) = targets.render(shell) {
+            if let Shell::Zsh = shell {
+                // The glue registers itself in place of clap's trailing `compdef`
+                let script = String::from_utf8_lossy(&buffer).into_owned();
+                if let Some(end) = script.rfind("if [ \"$funcstack[1]\" = \"_nest\" ]") {
+                    buffer.truncate(end);
+                }
+            }
+            buffer.extend_from_slice(glue.as_bytes());
+        }
+
+        buffer
+    }
+
+    /// Values offered for a parameter at tab time (for `--complete-values`).
+    ///
+    /// Parameters with a `complete.PARAM` source run it through the runtime and
+    /// cache the result for [`COMPLETION_VALUES_TTL_SECS`]; enum parameters
+    /// offer their variants. Anything else completes to nothing.
+    pub fn complete_values(
+        &self,
+        runtime: &Runtime,
+        commands: &[Command],
+        nestfile_path: &Path,
+        command_path: &[String],
+        param: &str,
+    ) -> Result<Vec<String>, String> {
+        let command = find_command(commands, command_path)
+            .ok_or_else(|| format!("Unknown command: {}", command_path.join(":")))?;
+        let parameter = command
+            .parameters
+            .iter()
+            .find(|p| p.name == param)
+            .ok_or_else(|| format!("Unknown parameter: {}", param))?;
+
+        if let Some(source) = DirectiveResolver::get_complete_directive(&command.directives, param)
+        {
+            let cache_path =
+                self.get_values_cache_path(nestfile_path, command_path, param, &source);
+            if let Some(values) = Self::read_cached_values(&cache_path) {
+                return Ok(values);
+            }
+
+            let values = runtime.completion_values(command_path, &source)?;
+            if let Some(parent) = cache_path.parent() {
+                // Caching is best effort; a failure only costs a re-run next time
+                if fs::create_dir_all(parent).is_ok() {
+                    let _ = fs::write(&cache_path, values.join("\n"));
+                }
+            }
+            return Ok(values);
+        }
+
+        Ok(ParamType::parse(&parameter.param_type)
+            .ok()
+            .and_then(|t| t.possible_values().map(|v| v.to_vec()))
+            .unwrap_or_default())
+    }
+
+    /// Get the cache file for the values of one completion source
+    fn get_values_cache_path(
+        &self,
+        nestfile_path: &Path,
+        command_path: &[String],
+        param: &str,
+        source: &str,
+    ) -> PathBuf {
+        let nestfile = nestfile_path
+            .canonicalize()
+            .unwrap_or_else(|_| nestfile_path.to_path_buf());
+
+        let mut hasher = Sha256::new();
+        hasher.update(nestfile.to_string_lossy().as_bytes());
+        hasher.update(b"|");
+        hasher.update(command_path.join(":").as_bytes());
+        hasher.update(b"|");
+        hasher.update(param.as_bytes());
+        hasher.update(b"|");
+        hasher.update(source.as_bytes());
+
+        let mut cache_path = self
+            .cache_dir
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| self.cache_dir.clone());
+        cache_path.push("values");
+        cache_path.push(format!("{:x}", hasher.finalize()));
+        cache_path
+    }
+
+    /// Read cached completion values if they are younger than the TTL
+    fn read_cached_values(cache_path: &Path) -> Option<Vec<String>> {
+        let age = fs::metadata(cache_path)
+            .ok()?
+            .modified()
+            .ok()?
+            .elapsed()
+            .ok()?;
+        if age.as_secs() >= COMPLETION_VALUES_TTL_SECS {
+            return None;
+        }
+
+        let content = fs::read_to_string(cache_path).ok()?;
+        Some(content.lines().map(str::to_string).collect())
+    }
+
+    /// Generate completion script for a specific shell
+    pub fn generate_completion(
+        &self,
+        shell: Shell,
+        cli: &mut ClapCommand,
+        commands: &[Command],
+        nestfile_path: &Path,
+    ) -> Result<PathBuf, String> {
+        let script_path = self.get_completion_script_path(shell, nestfile_path);
+
+        let buffer = Self::render_script(shell, cli, commands);
+
         // Write script to file
         fs::write(&script_path, buffer)
             .map_err(|e| format!("Failed to write completion script: {}", e))?;
@@ -234,21 +365,15 @@ impl CompletionManager {
     pub fn generate_all_completions(
         &self,
         cli: &mut ClapCommand,
+        commands: &[Command],
         nestfile_path: &Path,
     ) -> Result<Vec<(Shell, PathBuf)>, String> {
-        let shells = [
-            Shell::Bash,
-            Shell::Zsh,
-            Shell::Fish,
-            Shell::PowerShell,
-            Shell::Elvish,
-        ];
         let mut generated = Vec::new();
 
-        for shell in &shells {
+        for shell in &ALL_SHELLS {
             // Clone CLI for each shell to avoid borrowing issues
             let mut cli_clone = cli.clone();
-            let path = self.generate_completion(*shell, &mut cli_clone, nestfile_path)?;
+            let path = self.generate_completion(*shell, &mut cli_clone, commands, nestfile_path)?;
             generated.push((*shell, path));
         }
 
@@ -258,6 +383,7 @@ impl CompletionManager {
     /// Handle completion request (for --complete flag)
     pub fn handle_completion_request(
         cli: &mut ClapCommand,
+        commands: &[Command],
         shell_name: &str,
         verbose: bool,
         nestfile_path: &Path,
@@ -269,65 +395,21 @@ impl CompletionManager {
         // Generate completion script if needed
         let script_path = manager.get_completion_script_path(shell, nestfile_path);
         if !script_path.exists() || manager.needs_regeneration(nestfile_path)? {
-            manager.generate_completion(shell, cli, nestfile_path)?;
+            manager.generate_completion(shell, cli, commands, nestfile_path)?;
         }
 
         // If verbose, output the script content
         if verbose {
-            match shell {
-                Shell::Bash => {
-                    clap_complete::generate(
-                        clap_complete::shells::Bash,
-                        cli,
-                        APP_NAME,
-                        &mut std::io::stdout(),
-                    );
-                }
-                Shell::Zsh => {
-                    clap_complete::generate(
-                        clap_complete::shells::Zsh,
-                        cli,
-                        APP_NAME,
-                        &mut std::io::stdout(),
-                    );
-                }
-                Shell::Fish => {
-                    clap_complete::generate(
-                        clap_complete::shells::Fish,
-                        cli,
-                        APP_NAME,
-                        &mut std::io::stdout(),
-                    );
-                }
-                Shell::PowerShell => {
-                    clap_complete::generate(
-                        clap_complete::shells::PowerShell,
-                        cli,
-                        APP_NAME,
-                        &mut std::io::stdout(),
-                    );
-                }
-                Shell::Elvish => {
-                    clap_complete::generate(
-                        clap_complete::shells::Elvish,
-                        cli,
-                        APP_NAME,
-                        &mut std::io::stdout(),
-                    );
-                }
-            }
+            use std::io::Write;
+            std::io::stdout()
+                .write_all(&Self::render_script(shell, cli, commands))
+                .map_err(|e| format!("Failed to write completion script: {}", e))?;
             return Ok(());
         }
 
-        // Otherwise, show informational message and install
+        // Otherwise, show informational message
         Self::print_completion_info(&manager, shell, &script_path, nestfile_path)?;
 
-        // Try to install automatically
-        if let Ok(true) = Self::install_completion(shell, &script_path) {
-            // Installation successful, try to source in current terminal
-            Self::source_completion_in_current_shell(shell, &script_path)?;
-        }
-
         Ok(())
     }
 
@@ -351,14 +433,7 @@ impl CompletionManager {
         let cache_dir = manager.cache_dir.clone();
         if cache_dir.exists() {
             println!("  All generated completion scripts:");
-            let shells = [
-                Shell::Bash,
-                Shell::Zsh,
-                Shell::Fish,
-                Shell::PowerShell,
-                Shell::Elvish,
-            ];
-            for s in &shells {
+            for s in &ALL_SHELLS {
                 let path = manager.get_completion_script_path(*s, nestfile_path);
                 if path.exists() {
                     println!("    {}: {}", s.as_str(), path.display());
@@ -368,97 +443,28 @@ impl CompletionManager {
         }
 
         // Show installation status
-        if Self::is_completion_installed(shell, script_path) {
-            let config_path = Self::get_shell_config_path(shell)
-                .map(|p| p.display().to_string())
-                .unwrap_or_else(|| "unknown".to_string());
-            OutputFormatter::info(&format!(
-                "  ✓ Completion already installed in: {}",
-                config_path
-            ));
-        } else {
-            OutputFormatter::info("  → Completion will be installed automatically");
+        match Self::install_location(shell) {
+            Some(location) if location.is_installed() => {
+                OutputFormatter::info(&format!(
+                    "  ✓ Completion installed in: {}",
+                    location.script.display()
+                ));
+            }
+            _ => OutputFormatter::info(&format!(
+                "  → Install with: nest completion install {}",
+                shell.as_str()
+            )),
         }
         println!();
 
         // Show instructions
         println!("  To view the completion script, use:");
-        println!("    nest --complete {} -V", shell.as_str());
+        println!("    nest --complete {} --verbose", shell.as_str());
         println!();
 
         Ok(())
     }
 
-    /// Source completion script in current shell (if possible)
-    fn source_completion_in_current_shell(shell: Shell, script_path: &Path) -> Result<(), String> {
-        // Only try to source for bash/zsh in interactive shells
-        match shell {
-            Shell::Bash | Shell::Zsh => {
-                // Check if we're in an interactive shell
-                if std::env::var("PS1").is_ok() || std::env::var("ZSH").is_ok() {
-                    // Try to source the script
-                    let source_cmd = format!("source {}", script_path.display());
-                    let output = std::process::Command::new(shell.as_str())
-                        .arg("-c")
-                        .arg(&source_cmd)
-                        .output();
-
-                    if output.is_ok() {
-                        use crate::nestparse::output::OutputFormatter;
-                        OutputFormatter::info("  ✓ Completion loaded in current shell session");
-                    }
-                }
-            }
-            _ => {
-                // For other shells, just inform user
-                use crate::nestparse::output::OutputFormatter;
-                OutputFormatter::info(&format!(
-                    "  → Restart your {} shell or run: source {}",
-                    shell.as_str(),
-                    script_path.display()
-                ));
-            }
-        }
-        Ok(())
-    }
-
-    /// Get instructions for setting up completion in user's shell
-    #[allow(dead_code)]
-    pub fn get_setup_instructions(shell: Shell, script_path: &Path) -> String {
-        match shell {
-            Shell::Bash => {
-                format!(
-                    "Add to your ~/.bashrc:\n  source {}\nThen reload: source ~/.bashrc",
-                    script_path.display()
-                )
-            }
-            Shell::Zsh => {
-                format!(
-                    "Add to your ~/.zshrc:\n  source {}\nThen reload: source ~/.zshrc",
-                    script_path.display()
-                )
-            }
-            Shell::Fish => {
-                format!(
-                    "Copy to fish completions:\n  cp {} ~/.config/fish/completions/nest.fish",
-                    script_path.display()
-                )
-            }
-            Shell::PowerShell => {
-                format!(
-                    "Run in PowerShell:\n  . {}\nOr add to your PowerShell profile",
-                    script_path.display()
-                )
-            }
-            Shell::Elvish => {
-                format!(
-                    "Add to your ~/.elvish/rc.elv:\n  eval (slurp < {})",
-                    script_path.display()
-                )
-            }
-        }
-    }
-
     /// Detect current shell from environment
     pub fn detect_shell() -> Option<Shell> {
         // Try SHELL environment variable first
@@ -490,243 +496,645 @@ impl CompletionManager {
         None
     }
 
-    /// Get shell configuration file path
-    pub fn get_shell_config_path(shell: Shell) -> Option<PathBuf> {
-        let home = std::env::var("HOME")
-            .or_else(|_| std::env::var("USERPROFILE"))
-            .ok()?;
-
-        let home_path = PathBuf::from(home);
-
-        match shell {
-            Shell::Bash => {
-                // Try .bashrc first, then .bash_profile
-                let bashrc = home_path.join(".bashrc");
-                if bashrc.exists() {
-                    return Some(bashrc);
-                }
-                let bash_profile = home_path.join(".bash_profile");
-                if bash_profile.exists() {
-                    return Some(bash_profile);
+    /// Where the completion for a shell is installed.
+    ///
+    /// Scripts go to the directories the shells load completions from on their
+    /// own (bash-completion's user dir, fish's vendor dir). Zsh, PowerShell and
+    /// elvish also need a marker-delimited block in their startup file.
+    pub fn install_location(shell: Shell) -> Option<InstallLocation> {
+        let home = home_dir()?;
+        let data_home = xdg_dir("XDG_DATA_HOME").unwrap_or_else(|| home.join(".local/share"));
+        let config_home = xdg_dir("XDG_CONFIG_HOME").unwrap_or_else(|| home.join(".config"));
+        let nest_data = data_home.join(APP_NAME).join("completions");
+
+        let location = match shell {
+            Shell::Bash => InstallLocation {
+                script: xdg_dir("BASH_COMPLETION_USER_DIR")
+                    .unwrap_or_else(|| data_home.join("bash-completion"))
+                    .join("completions")
+                    .join(APP_NAME),
+                rc_file: None,
+                rc_block: None,
+            },
+            Shell::Zsh => {
+                let functions = data_home.join("zsh/site-functions");
+                InstallLocation {
+                    script: functions.join("_nest"),
+                    rc_file: Some(
+                        xdg_dir("ZDOTDIR")
+                            .unwrap_or_else(|| home.clone())
+                            .join(".zshrc"),
+                    ),
+                    rc_block: Some(format!(
+                        "fpath=(\"{}\" $fpath)\n(( $+functions[compdef] )) && autoload -Uz _nest && compdef _nest nest",
+                        functions.display()
+                    )),
                 }
-                // Return .bashrc even if it doesn't exist (will be created)
-                Some(bashrc)
-            }
-            Shell::Zsh => Some(home_path.join(".zshrc")),
-            Shell::Fish => {
-                // Fish uses a different approach - completions go to completions directory
-                Some(home_path.join(".config/fish/completions/nest.fish"))
             }
+            Shell::Fish => InstallLocation {
+                script: data_home.join("fish/vendor_completions.d/nest.fish"),
+                rc_file: None,
+                rc_block: None,
+            },
             Shell::PowerShell => {
-                // PowerShell profile location
-                #[cfg(windows)]
-                {
-                    if let Ok(profile) = std::env::var("PROFILE") {
-                        return Some(PathBuf::from(profile));
-                    }
+                let script = nest_data.join("nest.ps1");
+                let profile = std::env::var("PROFILE")
+                    .ok()
+                    .filter(|_| cfg!(windows))
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| {
+                        config_home.join("powershell/Microsoft.PowerShell_profile.ps1")
+                    });
+                InstallLocation {
+                    rc_block: Some(format!(". \"{}\"", script.display())),
+                    script,
+                    rc_file: Some(profile),
                 }
-                #[cfg(not(windows))]
-                {
-                    // On Unix, PowerShell might be installed via PowerShell Core
-                    if let Ok(home) = std::env::var("HOME") {
-                        return Some(
-                            PathBuf::from(home)
-                                .join(".config/powershell/Microsoft.PowerShell_profile.ps1"),
-                        );
-                    }
+            }
+            Shell::Elvish => {
+                let script = nest_data.join("nest.elv");
+                // Older elvish versions only read ~/.elvish/rc.elv
+                let legacy_rc = home.join(".elvish/rc.elv");
+                let rc_file = if legacy_rc.exists() {
+                    legacy_rc
+                } else {
+                    config_home.join("elvish/rc.elv")
+                };
+                InstallLocation {
+                    rc_block: Some(format!("eval (slurp < {})", script.display())),
+                    script,
+                    rc_file: Some(rc_file),
                 }
-                None
             }
-            Shell::Elvish => Some(home_path.join(".elvish/rc.elv")),
-        }
-    }
-
-    /// Check if completion is already installed in shell config
-    pub fn is_completion_installed(shell: Shell, script_path: &Path) -> bool {
-        let config_path = match Self::get_shell_config_path(shell) {
-            Some(path) => path,
-            None => return false,
         };
 
-        if !config_path.exists() {
-            return false;
+        Some(location)
+    }
+
+    /// Install the completion script for a shell (for `nest completion install`)
+    pub fn install(
+        &self,
+        shell: Shell,
+        cli: &mut ClapCommand,
+        commands: &[Command],
+    ) -> Result<InstallLocation, String> {
+        let location = Self::install_location(shell)
+            .ok_or("HOME or USERPROFILE environment variable not set")?;
+
+        write_file(
+            &location.script,
+            &String::from_utf8_lossy(&Self::render_script(shell, cli, commands)),
+        )?;
+
+        if let (Some(rc_file), Some(block)) = (&location.rc_file, &location.rc_block) {
+            let content = read_optional(rc_file)?;
+            let updated = replace_marker_block(&remove_legacy_block(&content), Some(block));
+            if updated != content {
+                write_file(rc_file, &updated)?;
+            }
         }
 
-        let content = match fs::read_to_string(&config_path) {
-            Ok(c) => c,
-            Err(_) => return false,
-        };
+        Ok(location)
+    }
 
-        // Check for different patterns depending on shell
-        match shell {
-            Shell::Bash | Shell::Zsh => {
-                // Check for source command pointing to our script
-                let script_str = script_path.to_string_lossy();
-                content.contains(script_str.as_ref())
-                    || content.contains("nest/completions")
-                    || content.contains("Nest CLI completion")
+    /// Remove the completion of a shell installed by [`CompletionManager::install`],
+    /// including the blocks older versions appended to shell startup files.
+    ///
+    /// Returns the paths that were removed or edited.
+    pub fn uninstall(shell: Shell) -> Result<Vec<PathBuf>, String> {
+        let location = Self::install_location(shell)
+            .ok_or("HOME or USERPROFILE environment variable not set")?;
+        let mut changed = Vec::new();
+
+        let mut scripts = vec![location.script.clone()];
+        if let (Shell::Fish, Some(home)) = (shell, home_dir()) {
+            // Older versions copied the script into the user completions dir
+            scripts.push(home.join(".config/fish/completions/nest.fish"));
+        }
+        for script in scripts {
+            if script.exists() {
+                fs::remove_file(&script)
+                    .map_err(|e| format!("Failed to remove {}: {}", script.display(), e))?;
+                changed.push(script);
             }
-            Shell::Fish => {
-                // Fish completions are separate files, check if file exists
-                config_path.exists()
+        }
+
+        let rc_files = [location.rc_file.clone(), legacy_rc_file(shell)];
+        for rc_file in rc_files.iter().flatten() {
+            let content = read_optional(rc_file)?;
+            let updated = replace_marker_block(&remove_legacy_block(&content), None);
+            if updated != content {
+                write_file(rc_file, &updated)?;
+                if !changed.contains(rc_file) {
+                    changed.push(rc_file.clone());
+                }
             }
-            Shell::PowerShell => content.contains("nest") && content.contains("completion"),
-            Shell::Elvish => {
-                content.contains("nest")
-                    && (content.contains("completion") || content.contains("eval"))
+        }
+
+        Ok(changed)
+    }
+
+    /// Rewrite the scripts of every shell whose completion is installed, so
+    /// they follow changes to the nestfile.
+    pub fn refresh_installed(&self, cli: &mut ClapCommand, commands: &[Command]) {
+        for shell in ALL_SHELLS {
+            if let Some(location) = Self::install_location(shell) {
+                if location.script.exists() {
+                    let script = Self::render_script(shell, &mut cli.clone(), commands);
+                    let _ = fs::write(&location.script, script);
+                }
             }
         }
     }
+}
 
-    /// Automatically install completion in shell configuration file
-    pub fn install_completion(shell: Shell, script_path: &Path) -> Result<bool, String> {
-        // Check if already installed
-        if Self::is_completion_installed(shell, script_path) {
-            return Ok(false); // Already installed, skip
+/// Every shell completion can be generated for.
+pub const ALL_SHELLS: [Shell; 5] = [
+    Shell::Bash,
+    Shell::Zsh,
+    Shell::Fish,
+    Shell::PowerShell,
+    Shell::Elvish,
+];
+
+/// First line of the block `nest completion install` adds to startup files.
+const MARKER_START: &str = "# >>> nest completion >>>";
+
+/// Last line of the block `nest completion install` adds to startup files.
+const MARKER_END: &str = "# <<< nest completion <<<";
+
+/// Comment that opened the block older versions appended automatically.
+const LEGACY_MARKER: &str = "# Nest CLI completion (auto-generated)";
+
+/// Installed location of a shell completion.
+#[derive(Debug, Clone)]
+pub struct InstallLocation {
+    /// The completion script
+    pub script: PathBuf,
+    /// Startup file that has to load the script, if the shell does not find it on its own
+    pub rc_file: Option<PathBuf>,
+    /// Lines placed between the markers in `rc_file`
+    pub rc_block: Option<String>,
+}
+
+impl InstallLocation {
+    /// Whether the script is present and, when needed, loaded from the startup file.
+    pub fn is_installed(&self) -> bool {
+        if !self.script.exists() {
+            return false;
         }
+        match &self.rc_file {
+            Some(rc_file) => fs::read_to_string(rc_file)
+                .map(|content| content.lines().any(|line| line.trim() == MARKER_START))
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+}
 
-        match shell {
-            Shell::Bash | Shell::Zsh => {
-                let config_path = Self::get_shell_config_path(shell)
-                    .ok_or_else(|| "Could not determine shell config path".to_string())?;
-
-                // Ensure parent directory exists
-                if let Some(parent) = config_path.parent() {
-                    fs::create_dir_all(parent)
-                        .map_err(|e| format!("Failed to create config directory: {}", e))?;
-                }
+fn home_dir() -> Option<PathBuf> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+        .map(PathBuf::from)
+}
 
-                // Read existing content
-                let mut content = if config_path.exists() {
-                    fs::read_to_string(&config_path)
-                        .map_err(|e| format!("Failed to read config file: {}", e))?
-                } else {
-                    String::new()
-                };
+/// Reads a directory from the environment, ignoring empty and relative values
+/// as the XDG base directory spec asks.
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    std::env::var(var)
+        .ok()
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
 
-                // Add completion setup
-                if !content.ends_with('\n') && !content.is_empty() {
-                    content.push('\n');
-                }
-                content.push_str("\n# Nest CLI completion (auto-generated)\n");
-                content.push_str(&format!("if [ -f {} ]; then\n", script_path.display()));
-                content.push_str(&format!("    source {}\n", script_path.display()));
-                content.push_str("fi\n");
+/// Startup file older versions wrote to, when it differs from the current one.
+fn legacy_rc_file(shell: Shell) -> Option<PathBuf> {
+    let home = home_dir()?;
+    match shell {
+        Shell::Bash => [".bashrc", ".bash_profile"]
+            .iter()
+            .map(|name| home.join(name))
+            .find(|path| path.exists()),
+        Shell::Zsh => Some(home.join(".zshrc")),
+        Shell::PowerShell => Some(home.join(".config/powershell/Microsoft.PowerShell_profile.ps1")),
+        Shell::Elvish => Some(home.join(".elvish/rc.elv")),
+        Shell::Fish => None,
+    }
+}
+
+fn read_optional(path: &Path) -> Result<String, String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
 
-                // Write back
-                fs::write(&config_path, content)
-                    .map_err(|e| format!("Failed to write config file: {}", e))?;
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
 
-                Ok(true)
-            }
-            Shell::Fish => {
-                // Fish uses completions directory
-                let completion_file = Self::get_shell_config_path(shell)
-                    .ok_or_else(|| "Could not determine fish completions path".to_string())?;
-
-                // Ensure directory exists
-                if let Some(parent) = completion_file.parent() {
-                    fs::create_dir_all(parent).map_err(|e| {
-                        format!("Failed to create fish completions directory: {}", e)
-                    })?;
+/// Replaces the marker-delimited block in `content` with `block`, appending it
+/// when there is none yet. `None` removes the block.
+fn replace_marker_block(content: &str, block: Option<&str>) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.iter().position(|line| line.trim() == MARKER_START);
+    let end = start.and_then(|start| {
+        lines[start..]
+            .iter()
+            .position(|line| line.trim() == MARKER_END)
+            .map(|offset| start + offset)
+    });
+
+    let new_block = block.map(|block| format!("{}\n{}\n{}", MARKER_START, block, MARKER_END));
+    let mut out: Vec<String> = Vec::new();
+    match (start, end) {
+        (Some(start), Some(end)) => {
+            out.extend(lines[..start].iter().map(|line| line.to_string()));
+            out.extend(new_block);
+            out.extend(lines[end + 1..].iter().map(|line| line.to_string()));
+        }
+        _ => {
+            out.extend(lines.iter().map(|line| line.to_string()));
+            if let Some(new_block) = new_block {
+                if out.last().is_some_and(|line| !line.trim().is_empty()) {
+                    out.push(String::new());
                 }
+                out.push(new_block);
+            }
+        }
+    }
 
-                // Copy completion script
-                fs::copy(script_path, &completion_file)
-                    .map_err(|e| format!("Failed to copy completion script: {}", e))?;
+    // Don't leave a dangling blank line behind a removed block
+    while out.last().is_some_and(|line| line.trim().is_empty()) {
+        out.pop();
+    }
+    if out.is_empty() {
+        String::new()
+    } else {
+        out.join("\n") + "\n"
+    }
+}
+
+/// Removes the blocks older versions appended automatically: the legacy
+/// comment followed by either a single line or an `if ... fi` block.
+fn remove_legacy_block(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].trim() != LEGACY_MARKER {
+            out.push(lines[i]);
+            i += 1;
+            continue;
+        }
 
-                Ok(true)
+        i += 1;
+        if lines
+            .get(i)
+            .is_some_and(|line| line.trim_start().starts_with("if "))
+        {
+            while i < lines.len() && lines[i].trim() != "fi" {
+                i += 1;
             }
-            Shell::PowerShell => {
-                let config_path = Self::get_shell_config_path(shell)
-                    .ok_or_else(|| "Could not determine PowerShell profile path".to_string())?;
-
-                // Ensure parent directory exists
-                if let Some(parent) = config_path.parent() {
-                    fs::create_dir_all(parent).map_err(|e| {
-                        format!("Failed to create PowerShell profile directory: {}", e)
-                    })?;
-                }
+        }
+        i += 1;
+        if out.last().is_some_and(|line| line.trim().is_empty()) {
+            out.pop();
+        }
+    }
 
-                // Read existing content
-                let mut content = if config_path.exists() {
-                    fs::read_to_string(&config_path)
-                        .map_err(|e| format!("Failed to read PowerShell profile: {}", e))?
-                } else {
-                    String::new()
-                };
+    if out.len() == lines.len() {
+        return content.to_string();
+    }
+    if out.is_empty() {
+        return String::new();
+    }
+    out.join("\n") + "\n"
+}
 
-                // Add completion setup
-                if !content.ends_with('\n') && !content.is_empty() {
-                    content.push('\n');
-                }
-                content.push_str("\n# Nest CLI completion (auto-generated)\n");
-                content.push_str(&format!(". {}\n", script_path.display()));
+impl Default for CompletionManager {
+    fn default() -> Self {
+        Self::new().expect("Failed to create CompletionManager")
+    }
+}
 
-                // Write back
-                fs::write(&config_path, content)
-                    .map_err(|e| format!("Failed to write PowerShell profile: {}", e))?;
+/// Finds a command by its path segments.
+/// Lookup tables the dynamic value completion glue is generated from.
+///
+/// Command paths are `:`-joined; a target is `"<command path> <param>"`,
+/// i.e. the arguments passed to `nest --complete-values`.
+#[derive(Debug, Default)]
+struct CompletionTargets {
+    /// Every command path
+    commands: Vec<String>,
+    /// `"<path> --long"` / `"<path> -s"` of options taking a value, with their
+    /// target or `-` when the value is not completed dynamically
+    options: Vec<(String, String)>,
+    /// `"<path> <index>"` of positionals that have a completion source
+    positionals: Vec<(String, String)>,
+}
 
-                Ok(true)
-            }
-            Shell::Elvish => {
-                let config_path = Self::get_shell_config_path(shell)
-                    .ok_or_else(|| "Could not determine elvish config path".to_string())?;
+fn collect_targets(commands: &[Command], prefix: &str, targets: &mut CompletionTargets) {
+    for command in commands.iter().filter(|c| !c.is_private()) {
+        let path = if prefix.is_empty() {
+            command.name.clone()
+        } else {
+            format!("{}:{}", prefix, command.name)
+        };
+        targets.commands.push(path.clone());
 
-                // Ensure parent directory exists
-                if let Some(parent) = config_path.parent() {
-                    fs::create_dir_all(parent)
-                        .map_err(|e| format!("Failed to create elvish config directory: {}", e))?;
-                }
+        // A group with a default subcommand accepts that subcommand's arguments
+        let (owner, owner_path) = match command
+            .children
+            .iter()
+            .find(|c| c.name == DEFAULT_SUBCOMMAND)
+        {
+            Some(default_cmd) => (default_cmd, format!("{}:{}", path, DEFAULT_SUBCOMMAND)),
+            None => (command, path.clone()),
+        };
 
-                // Read existing content
-                let mut content = if config_path.exists() {
-                    fs::read_to_string(&config_path)
-                        .map_err(|e| format!("Failed to read elvish config: {}", e))?
-                } else {
-                    String::new()
-                };
+        let target = |param: &Parameter| {
+            DirectiveResolver::get_complete_directive(&owner.directives, &param.name)
+                .map(|_| format!("{} {}", owner_path, param.name))
+        };
 
-                // Add completion setup
-                if !content.ends_with('\n') && !content.is_empty() {
-                    content.push('\n');
+        let mut index = 0;
+        for param in &owner.parameters {
+            if param.is_named {
+                if param.param_type == "bool" {
+                    continue;
                 }
-                content.push_str("\n# Nest CLI completion (auto-generated)\n");
-                content.push_str(&format!("eval (slurp < {})\n", script_path.display()));
+                let value = target(param).unwrap_or_else(|| "-".to_string());
+                targets
+                    .options
+                    .push((format!("{} --{}", path, param.name), value.clone()));
+                if let Some(alias) = &param.alias {
+                    targets
+                        .options
+                        .push((format!("{} -{}", path, alias), value));
+                }
+            } else if matches!(param.kind, ParamKind::Normal) {
+                index += 1;
+                if let Some(value) = target(param) {
+                    targets
+                        .positionals
+                        .push((format!("{} {}", path, index), value));
+                }
+            } else {
+                // Positions after a wildcard are not known in advance
+                break;
+            }
+        }
 
-                // Write back
-                fs::write(&config_path, content)
-                    .map_err(|e| format!("Failed to write elvish config: {}", e))?;
+        collect_targets(&command.children, &path, targets);
+    }
+}
 
-                Ok(true)
-            }
+impl CompletionTargets {
+    /// Render the glue for a shell, or `None` when there is nothing to
+    /// complete dynamically or the shell is not supported (PowerShell, elvish).
+    fn render(&self, shell: Shell) -> Option<String> {
+        let has_sources =
+            !self.positionals.is_empty() || self.options.iter().any(|(_, target)| target != "-");
+        if !has_sources {
+            return None;
+        }
+
+        match shell {
+            Shell::Bash | Shell::Zsh => Some(self.render_posix(shell)),
+            Shell::Fish => Some(self.render_fish()),
+            Shell::PowerShell | Shell::Elvish => None,
         }
     }
 
-    /// Automatically install completion for current shell
-    pub fn auto_install_completion(&self, nestfile_path: &Path) -> Result<Option<Shell>, String> {
-        let shell = match Self::detect_shell() {
-            Some(s) => s,
-            None => return Ok(None), // Shell not detected, skip installation
-        };
+    fn render_posix(&self, shell: Shell) -> String {
+        let mut out = String::from("\n# Dynamic value completion (complete.PARAM)\n");
 
-        let script_path = self.get_completion_script_path(shell, nestfile_path);
+        out.push_str("__nest_is_command() {\n    case \"$1\" in\n");
+        for path in &self.commands {
+            out.push_str(&format!("        '{}') return 0 ;;\n", path));
+        }
+        out.push_str("    esac\n    return 1\n}\n\n");
+
+        for (name, entries) in [
+            ("__nest_option", &self.options),
+            ("__nest_positional", &self.positionals),
+        ] {
+            out.push_str(&format!("{}() {{\n    case \"$1\" in\n", name));
+            for (key, target) in entries {
+                out.push_str(&format!("        '{}') echo '{}' ;;\n", key, target));
+            }
+            out.push_str("        *) return 1 ;;\n    esac\n}\n\n");
+        }
+
+        out.push_str(
+            r#"__nest_target() {
+    local cmd="" opt="" count=0 word next
+    for word in "$@"; do
+        if [ -n "$opt" ]; then
+            opt=""
+            continue
+        fi
+        case "$word" in
+            --*=*) ;;
+            -*) opt=$(__nest_option "$cmd $word") ;;
+            *)
+                next=""
+                if [ "$count" -eq 0 ]; then
+                    next="${cmd:+$cmd:}$word"
+                fi
+                if [ -n "$next" ] && __nest_is_command "$next"; then
+                    cmd="$next"
+                else
+                    count=$((count + 1))
+                fi
+                ;;
+        esac
+    done
+    if [ -n "$opt" ]; then
+        [ "$opt" != "-" ] && echo "$opt"
+        return
+    fi
+    __nest_positional "$cmd $((count + 1))"
+}
+
+"#,
+        );
+
+        match shell {
+            Shell::Zsh => out.push_str(
+                r#"_nest_dynamic() {
+    local target
+    local -a values
+    if [[ "${words[CURRENT]}" != -* ]] && target=$(__nest_target "${(@)words[2,CURRENT-1]}"); then
+        values=(${(f)"$(nest --complete-values ${=target} 2>/dev/null)"})
+        compadd -a values
+        return
+    fi
+    _nest "$@"
+}
 
-        // Check if script exists (should be generated first)
-        if !script_path.exists() {
-            return Ok(None); // Script not generated yet, skip
+compdef _nest_dynamic nest
+if [ "$funcstack[1]" = "_nest" ]; then
+    _nest_dynamic "$@"
+fi
+"#,
+            ),
+            _ => out.push_str(
+                r#"_nest_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" target
+    if [[ "$cur" != -* ]] && target=$(__nest_target "${COMP_WORDS[@]:1:COMP_CWORD-1}"); then
+        COMPREPLY=( $(compgen -W "$(nest --complete-values $target 2>/dev/null)" -- "$cur") )
+        return 0
+    fi
+    _nest "$@"
+}
+
+complete -F _nest_dynamic -o bashdefault -o default nest
+"#,
+            ),
         }
 
-        match Self::install_completion(shell, &script_path) {
-            Ok(true) => Ok(Some(shell)),  // Installed
-            Ok(false) => Ok(Some(shell)), // Already installed
-            Err(e) => Err(e),
+        out
+    }
+
+    fn render_fish(&self) -> String {
+        let mut out = String::from("\n# Dynamic value completion (complete.PARAM)\n");
+
+        out.push_str("function __nest_is_command\n    switch $argv[1]\n");
+        for path in &self.commands {
+            out.push_str(&format!("        case '{}'\n            return 0\n", path));
+        }
+        out.push_str("    end\n    return 1\nend\n\n");
+
+        for (name, entries) in [
+            ("__nest_option", &self.options),
+            ("__nest_positional", &self.positionals),
+        ] {
+            out.push_str(&format!("function {}\n    switch $argv[1]\n", name));
+            for (key, target) in entries {
+                out.push_str(&format!(
+                    "        case '{}'\n            echo '{}'\n            return 0\n",
+                    key, target
+                ));
+            }
+            out.push_str("    end\n    return 1\nend\n\n");
         }
+
+        out.push_str(
+            r#"function __nest_target
+    set -l cmd ""
+    set -l opt ""
+    set -l count 0
+    for word in (commandline -opc)[2..-1]
+        if test -n "$opt"
+            set opt ""
+            continue
+        end
+        switch $word
+            case '--*=*'
+            case '-*'
+                set opt (__nest_option "$cmd $word")
+            case '*'
+                set -l next ""
+                if test $count -eq 0
+                    if test -n "$cmd"
+                        set next "$cmd:$word"
+                    else
+                        set next $word
+                    end
+                end
+                if test -n "$next"; and __nest_is_command $next
+                    set cmd $next
+                else
+                    set count (math $count + 1)
+                end
+        end
+    end
+    if test -n "$opt"
+        test "$opt" != "-"; and echo $opt
+        return
+    end
+    __nest_positional "$cmd "(math $count + 1)
+end
+
+complete -c nest -f -n 'not string match -q -- "-*" (commandline -ct); and __nest_target >/dev/null' -a '(nest --complete-values (string split " " -- (__nest_target)) 2>/dev/null)'
+"#,
+        );
+
+        out
     }
 }
 
-impl Default for CompletionManager {
-    fn default() -> Self {
-        Self::new().expect("Failed to create CompletionManager")
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nestparse::parser::Parser;
+
+    #[test]
+    fn test_dynamic_completion_targets() {
+        let content = r#"deploy(target: str, !ns|n: str, !force: bool = false, !tag: str = "x"):
+    complete.ns: $(echo a b)
+    complete.target: namespaces
+    script: echo hi
+grp:
+    default(!x: str = "a"):
+        complete.x: $(echo x)
+        script: echo d
+"#;
+        let mut parser = Parser::new(content);
+        let commands = parser.parse().unwrap().commands;
+        let mut targets = CompletionTargets::default();
+        collect_targets(&commands, "", &mut targets);
+
+        assert_eq!(targets.commands, ["deploy", "grp", "grp:default"]);
+        assert!(targets
+            .options
+            .contains(&("deploy -n".to_string(), "deploy ns".to_string())));
+        assert!(targets
+            .options
+            .contains(&("deploy --tag".to_string(), "-".to_string())));
+        assert!(!targets
+            .options
+            .iter()
+            .any(|(key, _)| key == "deploy --force"));
+        assert!(targets
+            .options
+            .contains(&("grp --x".to_string(), "grp:default x".to_string())));
+        assert_eq!(
+            targets.positionals,
+            [("deploy 1".to_string(), "deploy target".to_string())]
+        );
+
+        assert!(targets.render(Shell::Bash).is_some());
+        assert!(targets.render(Shell::Elvish).is_none());
+        assert!(CompletionTargets::default().render(Shell::Zsh).is_none());
+    }
+
+    #[test]
+    fn test_marker_blocks() {
+        let rc = "alias x=y\n";
+        let installed = replace_marker_block(rc, Some("source a"));
+        assert_eq!(
+            installed,
+            format!("alias x=y\n\n{}\nsource a\n{}\n", MARKER_START, MARKER_END)
+        );
+        assert_eq!(
+            replace_marker_block(&installed, Some("source b")),
+            installed.replace("source a", "source b")
+        );
+        assert_eq!(replace_marker_block(&installed, None), rc);
+
+        let legacy = format!(
+            "export A=1\n\n{}\nif [ -f x ]; then\n    source x\nfi\nexport B=2\n\n{}\n. x.ps1\n",
+            LEGACY_MARKER, LEGACY_MARKER
+        );
+        assert_eq!(remove_legacy_block(&legacy), "export A=1\nexport B=2\n");
+        assert_eq!(remove_legacy_block(rc), rc);
     }
 }
@@ -0,0 +1,168 @@
+//! Updating git-sourced imports.
+//!
+//! `nest deps update` bumps the ref of every git import in a configuration
+//! file to the newest release tag of its repository. Imports pinned to a
+//! branch or a commit are left as they are.
+
+use super::git;
+use super::source::Source;
+
+/// A ref change applied to a git import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefBump {
+    /// Repository URL
+    pub repo: String,
+    /// Previous ref
+    pub from: String,
+    /// New ref
+    pub to: String,
+}
+
+/// Rewrites git imports in `content` to the newest release tag.
+///
+/// # Returns
+///
+/// Returns the updated content and the list of applied bumps.
+pub fn bump_git_refs(content: &str) -> Result<(String, Vec<RefBump>), String> {
+    let mut bumps = Vec::new();
+    let mut lines = Vec::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if !(trimmed.starts_with("import ") || trimmed.starts_with("@include ")) {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let mut new_line = line.to_string();
+        for token in line.split_whitespace() {
+            let spec = token.trim_matches('"').trim_matches('\'');
+            let Some(Source::Git {
+                repo, reference, ..
+            }) = Source::parse(spec)?
+            else {
+                continue;
+            };
+
+            if let Some(latest) = latest_tag(&repo, &reference)? {
+                let new_token = replace_ref(token, &reference, &latest);
+                new_line = new_line.replacen(token, &new_token, 1);
+                bumps.push(RefBump {
+                    repo,
+                    from: reference,
+                    to: latest,
+                });
+            }
+        }
+        lines.push(new_line);
+    }
+
+    let mut updated = lines.join("\n");
+    if content.ends_with('\n') {
+        updated.push('\n');
+    }
+    Ok((updated, bumps))
+}
+
+/// Returns the newest release tag of `repo` if it is newer than `current`.
+///
+/// Returns `None` if `current` is not a version tag.
+pub fn latest_tag(repo: &str, current: &str) -> Result<Option<String>, String> {
+    let Some(current_version) = parse_version(current) else {
+        return Ok(None);
+    };
+
+    let latest = git::list_remote_tags(repo)?
+        .into_iter()
+        .filter_map(|tag| parse_version(&tag).map(|v| (v, tag)))
+        .max_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(latest
+        .filter(|(version, _)| *version > current_version)
+        .map(|(_, tag)| tag))
+}
+
+/// Parses a release tag like `v1.4.0` or `2.1` into its numeric components.
+///
+/// Pre-release and build tags (`v2.0.0-rc.1`) are not considered releases.
+fn parse_version(tag: &str) -> Option<[u64; 3]> {
+    let version = tag.strip_prefix(['v', 'V']).unwrap_or(tag);
+    let mut parts = [0u64; 3];
+    for (i, part) in version.split('.').enumerate() {
+        *parts.get_mut(i)? = part.parse().ok()?;
+    }
+    Some(parts)
+}
+
+/// Replaces the `@ref` part of a git import token.
+fn replace_ref(token: &str, from: &str, to: &str) -> String {
+    let needle = format!("@{}", from);
+    match token.rfind(&needle) {
+        Some(pos) => format!("{}@{}{}", &token[..pos], to, &token[pos + needle.len()..]),
+        None => token.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=nest", "-c", "user.email=nest@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_bump_git_refs_against_bare_repo() {
+        let dir = std::env::temp_dir().join(format!("nest-deps-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let work = dir.join("work");
+        std::fs::create_dir_all(&work).unwrap();
+
+        git(&work, &["init", "-q"]);
+        std::fs::write(work.join("docker.nest"), "build:\n    script: echo build\n").unwrap();
+        git(&work, &["add", "."]);
+        git(&work, &["commit", "-q", "-m", "init"]);
+        for tag in ["v1.0.0", "v1.4.0", "v2.0.0-rc.1", "latest"] {
+            git(&work, &["tag", tag]);
+        }
+        let bare = dir.join("tasks.git");
+        git(
+            &dir,
+            &["clone", "-q", "--bare", "work", bare.to_str().unwrap()],
+        );
+
+        let repo = format!("file://{}", bare.display());
+        let content = format!(
+            "import * from git:{}@v1.0.0#docker.nest into docker\n\nbuild:\n    script: echo\n",
+            repo
+        );
+        let (updated, bumps) = bump_git_refs(&content).unwrap();
+
+        assert_eq!(
+            bumps,
+            vec![RefBump {
+                repo: repo.clone(),
+                from: "v1.0.0".to_string(),
+                to: "v1.4.0".to_string(),
+            }]
+        );
+        assert!(updated.starts_with(&format!(
+            "import * from git:{}@v1.4.0#docker.nest into docker\n",
+            repo
+        )));
+        assert!(updated.ends_with("script: echo\n"));
+
+        // Already on the newest release: nothing to do
+        let (_, bumps) = bump_git_refs(&updated).unwrap();
+        assert!(bumps.is_empty());
+    }
+}
@@ -3,7 +3,7 @@
 //! This module handles the resolution of directives from the AST,
 //! including OS compatibility checks and value extraction.
 
-use super::ast::Directive;
+use super::ast::{Directive, Value};
 
 /// Resolver for directives and their values.
 pub struct DirectiveResolver;
@@ -117,6 +117,22 @@ impl DirectiveResolver {
             .unwrap_or(false)
     }
 
+    /// Returns the help category of a command, if any.
+    pub fn get_category(directives: &[Directive]) -> Option<String> {
+        directives.iter().rev().find_map(|d| match d {
+            Directive::Category(category) => Some(category.clone()),
+            _ => None,
+        })
+    }
+
+    /// Returns the `help.order` of a command, if set.
+    pub fn get_help_order(directives: &[Directive]) -> Option<i64> {
+        directives.iter().rev().find_map(|d| match d {
+            Directive::HelpOrder(order) => Some(*order),
+            _ => None,
+        })
+    }
+
     pub fn get_require_confirm_directive(directives: &[Directive]) -> Option<String> {
         directives.iter().find_map(|d| match d {
             Directive::RequireConfirm(message) => Some(message.clone()),
@@ -140,4 +156,46 @@ impl DirectiveResolver {
             })
             .collect()
     }
+
+    /// Returns the presets of a command in definition order.
+    ///
+    /// A later preset with the same name replaces an earlier one.
+    pub fn get_presets(directives: &[Directive]) -> Vec<(String, Vec<(String, Value)>)> {
+        let mut presets: Vec<(String, Vec<(String, Value)>)> = Vec::new();
+        for directive in directives {
+            if let Directive::Preset(name, values) = directive {
+                presets.retain(|(existing, _)| existing != name);
+                presets.push((name.clone(), values.clone()));
+            }
+        }
+        presets
+    }
+
+    /// Returns the argument values of the preset called `name`, if defined.
+    pub fn get_preset(directives: &[Directive], name: &str) -> Option<Vec<(String, Value)>> {
+        Self::get_presets(directives)
+            .into_iter()
+            .find(|(preset, _)| preset == name)
+            .map(|(_, values)| values)
+    }
+
+    /// Returns all aliases of a command, in definition order.
+    pub fn get_aliases(directives: &[Directive]) -> Vec<String> {
+        directives
+            .iter()
+            .filter_map(|d| match d {
+                Directive::Alias(aliases) => Some(aliases.clone()),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Returns the completion source (`complete.PARAM`) of a parameter, if any.
+    pub fn get_complete_directive(directives: &[Directive], param: &str) -> Option<String> {
+        directives.iter().rev().find_map(|d| match d {
+            Directive::Complete(target, source) if target == param => Some(source.clone()),
+            _ => None,
+        })
+    }
 }
@@ -8,7 +8,8 @@ use super::ast::{Command, Directive};
 /// Prints a command and its children in a tree format.
 ///
 /// This function recursively prints the command structure with indentation,
-/// showing directives and child commands in a visual tree.
+/// showing directives and child commands in a visual tree. Private children
+/// are skipped.
 ///
 /// # Arguments
 ///
@@ -36,6 +37,15 @@ pub fn print_command(command: &Command, indent: usize) {
             Directive::Desc(s) => {
                 println!("{}    > desc: {}", indent_str, s);
             }
+            Directive::Alias(aliases) => {
+                println!("{}    > alias: {}", indent_str, aliases.join(", "));
+            }
+            Directive::Category(category) => {
+                println!("{}    > category: {}", indent_str, category);
+            }
+            Directive::HelpOrder(order) => {
+                println!("{}    > help.order: {}", indent_str, order);
+            }
             Directive::Cwd(s) => {
                 println!("{}    > cwd: {}", indent_str, s);
             }
@@ -150,9 +160,22 @@ pub fn print_command(command: &Command, indent: usize) {
             Directive::Validate(target, rule) => {
                 println!("{}    > validate.{}: {}", indent_str, target, rule);
             }
+            Directive::Complete(target, source) => {
+                println!("{}    > complete.{}: {}", indent_str, target, source);
+            }
+            Directive::Preset(name, values) => {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|(param, value)| format!("{}={}", param, value))
+                    .collect();
+                println!("{}    > preset {}: {}", indent_str, name, values.join(", "));
+            }
             Directive::Privileged(value) => {
                 println!("{}    > privileged: {}", indent_str, value);
             }
+            Directive::Private(value) => {
+                println!("{}    > private: {}", indent_str, value);
+            }
             Directive::Script(s, os, hide) => {
                 let mut name = String::from("script");
                 if let Some(os_name) = os {
@@ -191,7 +214,7 @@ pub fn print_command(command: &Command, indent: usize) {
     }
 
     // Print children
-    for child in &command.children {
+    for child in command.children.iter().filter(|c| !c.is_private()) {
         print_command(child, indent + 1);
     }
 }
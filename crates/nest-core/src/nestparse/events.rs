@@ -0,0 +1,174 @@
+//! Machine-readable event stream (`nest --events jsonl`).
+//!
+//! [`JsonlEvents`] is a [`RuntimeObserver`] writing one JSON object per line,
+//! so tools can follow a run without reading the human output:
+//!
+//! ```text
+//! {"event":"command_started","command":["build"]}
+//! {"event":"phase_started","command":["build"],"phase":"script"}
+//! {"event":"output","command":["build"],"stream":"stdout","line":"compiling"}
+//! {"event":"command_finished","command":["build"],"status":"succeeded","exit_code":null,"duration_ms":412}
+//! ```
+
+use super::executor::OutputStream;
+use super::observer::{Phase, RuntimeObserver};
+use crate::error::NestError;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// One line of the stream.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    CommandStarted {
+        command: &'a [String],
+    },
+    PhaseStarted {
+        command: &'a [String],
+        phase: &'static str,
+    },
+    Output {
+        command: &'a [String],
+        stream: &'static str,
+        line: &'a str,
+    },
+    DependencySkipped {
+        command: &'a [String],
+        required_by: &'a [String],
+    },
+    CommandFinished {
+        command: &'a [String],
+        /// `succeeded`, `failed` or `cancelled` (confirmation declined)
+        status: &'static str,
+        exit_code: Option<i32>,
+        duration_ms: u64,
+        /// Why it failed, as plain text
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+}
+
+/// Writes the events of a run as JSON lines to `W`.
+///
+/// Each line is flushed as soon as it is written. Failures to write are
+/// ignored: the run goes on when the reader goes away.
+pub struct JsonlEvents<W: Write + Send> {
+    writer: Mutex<W>,
+    /// When each running command started
+    started: Mutex<HashMap<Vec<String>, Instant>>,
+}
+
+impl<W: Write + Send> JsonlEvents<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+            started: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn emit(&self, event: &Event) {
+        let Ok(line) = serde_json::to_string(event) else {
+            return;
+        };
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writeln!(writer, "{}", line);
+            let _ = writer.flush();
+        }
+    }
+}
+
+impl<W: Write + Send> RuntimeObserver for JsonlEvents<W> {
+    fn command_started(&self, command_path: &[String]) {
+        if let Ok(mut started) = self.started.lock() {
+            started.insert(command_path.to_vec(), Instant::now());
+        }
+        self.emit(&Event::CommandStarted {
+            command: command_path,
+        });
+    }
+
+    fn command_finished(&self, command_path: &[String], result: &Result<(), NestError>) {
+        let started = self
+            .started
+            .lock()
+            .ok()
+            .and_then(|mut started| started.remove(command_path));
+        let status = match result {
+            Ok(()) => "succeeded",
+            Err(NestError::ConfirmationDeclined { .. }) => "cancelled",
+            Err(_) => "failed",
+        };
+        self.emit(&Event::CommandFinished {
+            command: command_path,
+            status,
+            exit_code: result.as_ref().err().and_then(NestError::exit_code),
+            duration_ms: started.map_or(0, |started| started.elapsed().as_millis() as u64),
+            message: result.as_ref().err().map(ToString::to_string),
+        });
+    }
+
+    fn dependency_skipped(&self, dependency: &[String], required_by: &[String]) {
+        self.emit(&Event::DependencySkipped {
+            command: dependency,
+            required_by,
+        });
+    }
+
+    fn phase_started(&self, command_path: &[String], phase: Phase) {
+        self.emit(&Event::PhaseStarted {
+            command: command_path,
+            phase: phase.name(),
+        });
+    }
+
+    fn output_line(&self, command_path: &[String], stream: OutputStream, line: &str) {
+        let stream = match stream {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        };
+        self.emit(&Event::Output {
+            command: command_path,
+            stream,
+            line,
+        });
+    }
+
+    fn receives_output(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_are_json_lines() {
+        let events = JsonlEvents::new(Vec::new());
+        let path = vec!["db".to_string(), "migrate".to_string()];
+        events.command_started(&path);
+        events.output_line(&path, OutputStream::Stderr, "say \"hi\"");
+        events.command_finished(
+            &path,
+            &Err(NestError::ConfirmationDeclined {
+                command_path: path.clone(),
+            }),
+        );
+
+        let written = String::from_utf8(events.writer.into_inner().unwrap()).unwrap();
+        let lines: Vec<serde_json::Value> = written
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["event"], "command_started");
+        assert_eq!(lines[0]["command"], serde_json::json!(["db", "migrate"]));
+        assert_eq!(lines[1]["stream"], "stderr");
+        assert_eq!(lines[1]["line"], "say \"hi\"");
+        assert_eq!(lines[2]["status"], "cancelled");
+        assert!(lines[2]["exit_code"].is_null());
+    }
+}
@@ -5,9 +5,22 @@
 //! and detailed error reporting with beautiful formatting.
 
 use super::ast::Command;
+use super::output::OutputFormatter;
+use crate::error::{NestError, ScriptFailure};
 use std::collections::HashMap;
 use std::env;
-use std::process::{Command as ProcessCommand, Stdio};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command as ProcessCommand, ExitStatus, Stdio};
+
+/// The stream a line of script output was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Receives script output line by line in place of the terminal.
+pub type OutputSink<'a> = dyn Fn(OutputStream, &str) + Send + Sync + 'a;
 
 /// Context for script execution containing all necessary parameters.
 pub struct ExecutionContext<'a> {
@@ -21,6 +34,7 @@ pub struct ExecutionContext<'a> {
     pub privileged: bool,
     pub pid_callback: Option<&'a dyn Fn(u32)>,
     pub hide_output: bool,
+    pub output_sink: Option<&'a OutputSink<'a>>,
 }
 
 /// Executes shell scripts for commands.
@@ -81,29 +95,22 @@ impl CommandExecutor {
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` if execution succeeded (or dry-run completed),
-    /// `Err(message)` with a formatted error message if execution failed.
+    /// Returns `Ok(())` if execution succeeded (or dry-run completed).
     ///
     /// # Errors
     ///
     /// Returns an error if:
+    /// - The command is privileged and the process isn't ([`NestError::Privilege`])
     /// - Script execution fails to start
-    /// - Script exits with non-zero status code
-    ///
-    /// The error message includes:
-    /// - Command path and arguments
-    /// - Working directory
-    /// - Script preview
-    /// - Exit code
-    /// - Helpful suggestions (e.g., missing commands)
+    /// - Script exits with non-zero status code ([`NestError::ScriptExit`], whose
+    ///   [`render`](NestError::render) is the detailed report `nest` prints)
     #[allow(dead_code)]
-    pub fn execute(script: &str, context: &ExecutionContext) -> Result<(), String> {
+    pub fn execute(script: &str, context: &ExecutionContext) -> Result<(), NestError> {
+        let command_path = context.command_path.unwrap_or(&[]).to_vec();
+
         // Check privileged access BEFORE execution
         if context.privileged && !context.dry_run && !Self::check_privileged_access() {
-            return Err(Self::format_privileged_error(
-                context.command,
-                context.command_path,
-            ));
+            return Err(NestError::Privilege { command_path });
         }
 
         // Show dry-run preview
@@ -140,11 +147,22 @@ impl CommandExecutor {
             cmd.env(key, value);
         }
 
-        // Capture output - hide if requested
+        // Capture output - hide if requested, stream it to the sink if there is one
+        let sink = context.output_sink.filter(|_| !context.hide_output);
         if context.hide_output {
             cmd.stdin(Stdio::null());
             cmd.stdout(Stdio::null());
             cmd.stderr(Stdio::null());
+        } else if sink.is_some() {
+            cmd.stdin(Stdio::null());
+            cmd.stdout(Stdio::piped());
+            cmd.stderr(Stdio::piped());
+            // Own process group, so cancelling the reported PID stops the whole script
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::CommandExt;
+                cmd.process_group(0);
+            }
         } else {
             cmd.stdin(Stdio::inherit());
             cmd.stdout(Stdio::inherit());
@@ -153,38 +171,58 @@ impl CommandExecutor {
 
         let mut child = cmd
             .spawn()
-            .map_err(|e| format!("Failed to start script execution: {}", e))?;
+            .map_err(|e| NestError::Other(format!("Failed to start script execution: {}", e)))?;
 
         if let Some(callback) = context.pid_callback {
             callback(child.id());
         }
 
         // Wait for command to finish
-        let status = child
-            .wait()
-            .map_err(|e| format!("Failed to wait for script execution: {}", e))?;
+        let status = match sink {
+            Some(sink) => Self::forward_output(&mut child, sink),
+            None => child.wait(),
+        }
+        .map_err(|e| NestError::Other(format!("Failed to wait for script execution: {}", e)))?;
 
         if !status.success() {
-            let exit_code = status.code().unwrap_or(-1);
-
-            // Build beautiful formatted error message
-            // Note: we don't have the stderr output since it was inherited directly to terminal
-            let error_msg = format_error_message(
-                context.command,
-                context.command_path,
-                context.args,
-                context.cwd,
-                script,
-                exit_code,
-                "(See output above)",
-            );
-
-            return Err(error_msg);
+            // The report is built by `NestError::render`; the output itself
+            // already went to the terminal or the sink
+            return Err(NestError::ScriptExit(Box::new(ScriptFailure {
+                command_path,
+                code: status.code(),
+                script: script.to_string(),
+                cwd: context.cwd.map(str::to_string),
+                args: context.args.clone(),
+            })));
         }
 
         Ok(())
     }
 
+    /// Passes the child's stdout and stderr to `sink` line by line until it exits.
+    fn forward_output(child: &mut Child, sink: &OutputSink) -> std::io::Result<ExitStatus> {
+        fn forward(stream: Option<impl Read>, kind: OutputStream, sink: &OutputSink) {
+            let Some(stream) = stream else {
+                return;
+            };
+            let mut reader = BufReader::new(stream);
+            let mut line = Vec::new();
+            while matches!(reader.read_until(b'\n', &mut line), Ok(read) if read > 0) {
+                let text = String::from_utf8_lossy(&line);
+                sink(kind, text.trim_end_matches(['\n', '\r']));
+                line.clear();
+            }
+        }
+
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        std::thread::scope(|scope| {
+            scope.spawn(|| forward(stderr, OutputStream::Stderr, sink));
+            forward(stdout, OutputStream::Stdout, sink);
+        });
+        child.wait()
+    }
+
     /// Executes a command and captures its stdout as a string.
     /// This is used for dynamic value evaluation $(command).
     pub fn capture_output(script: &str, context: &ExecutionContext) -> Result<String, String> {
@@ -282,18 +320,14 @@ impl CommandExecutor {
         }
     }
 
-    pub fn format_privileged_error(command: &Command, command_path: Option<&[String]>) -> String {
+    pub fn format_privileged_error(command_path: &[String]) -> String {
         use super::output::colors;
         use std::env::consts::OS;
         use std::fmt::Write;
 
         let mut output = String::new();
 
-        let command_display = if let Some(path) = command_path {
-            format!("nest {}", path.join(" "))
-        } else {
-            command.name.clone()
-        };
+        let command_display = format!("nest {}", command_path.join(" "));
 
         let sudo_command = if OS == "windows" {
             "Run PowerShell/CMD as Administrator, then: nest <command>"
@@ -551,29 +585,37 @@ impl CommandExecutor {
         )
         .expect("Failed to format dry run info message");
 
-        eprint!("{}", output);
+        OutputFormatter::eprint(&output);
     }
 
     pub fn show_verbose_info(script: &str, context: &ExecutionContext) {
         use super::output::colors;
+        use std::fmt::Write;
 
-        eprintln!(
+        let mut output = String::new();
+        writeln!(
+            output,
             "\n{}╔═══════════════════════════════════════════════════════════════╗{}",
             colors::BRIGHT_BLUE,
             colors::RESET
-        );
-        eprintln!(
+        )
+        .expect("Failed to format verbose info");
+        writeln!(
+            output,
             "{}║{}  {}ℹ Verbose Mode{}",
             colors::BRIGHT_BLUE,
             colors::RESET,
             colors::BRIGHT_CYAN,
             colors::RESET
-        );
-        eprintln!(
+        )
+        .expect("Failed to format verbose info");
+        writeln!(
+            output,
             "{}╚═══════════════════════════════════════════════════════════════╝{}\n",
             colors::BRIGHT_BLUE,
             colors::RESET
-        );
+        )
+        .expect("Failed to format verbose info");
 
         let command_display = if let Some(path) = context.command_path {
             format!("nest {}", path.join(" "))
@@ -581,12 +623,14 @@ impl CommandExecutor {
             context.command.name.clone()
         };
 
-        eprintln!(
+        writeln!(
+            output,
             "{}📋 Command:{} {}",
             colors::CYAN,
             colors::RESET,
             command_display
-        );
+        )
+        .expect("Failed to format verbose info");
 
         if !context.args.is_empty() {
             let args_str: Vec<String> = context
@@ -604,21 +648,25 @@ impl CommandExecutor {
                     )
                 })
                 .collect();
-            eprintln!(
+            writeln!(
+                output,
                 "{}🔧 Arguments:{} {}",
                 colors::CYAN,
                 colors::RESET,
                 args_str.join(", ")
-            );
+            )
+            .expect("Failed to format verbose info");
         }
 
         if let Some(cwd_path) = context.cwd {
-            eprintln!(
+            writeln!(
+                output,
                 "{}📁 Working directory:{} {}",
                 colors::CYAN,
                 colors::RESET,
                 cwd_path
-            );
+            )
+            .expect("Failed to format verbose info");
         }
 
         if context.privileged {
@@ -628,24 +676,29 @@ impl CommandExecutor {
             } else {
                 "sudo"
             };
-            eprintln!(
+            writeln!(
+                output,
                 "{}🔐 Privileged access:{} {}Required ({}){}",
                 colors::YELLOW,
                 colors::RESET,
                 colors::BRIGHT_YELLOW,
                 sudo_command,
                 colors::RESET
-            );
+            )
+            .expect("Failed to format verbose info");
         }
 
         if !context.env_vars.is_empty() {
-            eprintln!(
+            writeln!(
+                output,
                 "\n{}🌍 Environment variables:{}",
                 colors::CYAN,
                 colors::RESET
-            );
+            )
+            .expect("Failed to format verbose info");
             for (key, value) in context.env_vars {
-                eprintln!(
+                writeln!(
+                    output,
                     "  {}{}{}={}{}{}",
                     colors::YELLOW,
                     key,
@@ -653,19 +706,24 @@ impl CommandExecutor {
                     colors::CYAN,
                     value,
                     colors::RESET
-                );
+                )
+                .expect("Failed to format verbose info");
             }
         }
 
-        eprintln!("\n{}📜 Script:{}", colors::CYAN, colors::RESET);
-        eprintln!(
+        writeln!(output, "\n{}📜 Script:{}", colors::CYAN, colors::RESET)
+            .expect("Failed to format verbose info");
+        writeln!(
+            output,
             "{}┌─────────────────────────────────────────────────────────┐{}",
             colors::GRAY,
             colors::RESET
-        );
+        )
+        .expect("Failed to format verbose info");
         for (i, line) in script.lines().enumerate() {
             let line_num = format!("{:2}", i + 1);
-            eprintln!(
+            writeln!(
+                output,
                 "{}│{} {} {}{}│{}",
                 colors::GRAY,
                 colors::RESET,
@@ -673,13 +731,18 @@ impl CommandExecutor {
                 line,
                 colors::RESET,
                 colors::GRAY
-            );
+            )
+            .expect("Failed to format verbose info");
         }
-        eprintln!(
+        writeln!(
+            output,
             "{}└─────────────────────────────────────────────────────────┘{}\n",
             colors::GRAY,
             colors::RESET
-        );
+        )
+        .expect("Failed to format verbose info");
+
+        OutputFormatter::eprint(&output);
     }
 
     /// Parses a command call from a string.
@@ -689,6 +752,7 @@ impl CommandExecutor {
     /// - `group:command` - nested command
     /// - `command(arg="value")` - command with arguments
     /// - `group:command(arg="value")` - nested command with arguments
+    /// - `group.function(arg="value")` - function of an imported namespace
     ///
     /// Returns (command_path, args) if it's a command call, None otherwise.
     pub fn parse_command_call(line: &str) -> Option<(String, HashMap<String, String>)> {
@@ -776,7 +840,8 @@ impl CommandExecutor {
             command_path = trimmed.to_string();
         }
 
-        // Validate command path (should contain only alphanumeric, underscore, colon, hyphen)
+        // Validate command path (should contain only alphanumeric, underscore, colon, hyphen,
+        // or dot for namespaced functions like `docker.build_image`)
         if command_path.is_empty() {
             return None;
         }
@@ -784,9 +849,9 @@ impl CommandExecutor {
         // Check if it looks like a valid command path
         let is_valid = command_path
             .chars()
-            .all(|c| c.is_alphanumeric() || c == ':' || c == '_' || c == '-')
-            && !command_path.starts_with(':')
-            && !command_path.ends_with(':');
+            .all(|c| c.is_alphanumeric() || c == ':' || c == '_' || c == '-' || c == '.')
+            && !command_path.starts_with([':', '.'])
+            && !command_path.ends_with([':', '.']);
 
         if !is_valid {
             return None;
@@ -878,9 +943,8 @@ impl CommandExecutor {
 }
 
 #[allow(dead_code)]
-fn format_error_message(
-    command: &Command,
-    command_path: Option<&[String]>,
+pub(crate) fn format_error_message(
+    command_path: &[String],
     args: &HashMap<String, String>,
     cwd: Option<&str>,
     script: &str,
@@ -918,11 +982,7 @@ fn format_error_message(
     .expect("Failed to format error message footer");
 
     // Command information
-    let command_display = if let Some(path) = command_path {
-        format!("nest {}", path.join(" "))
-    } else {
-        command.name.clone()
-    };
+    let command_display = format!("nest {}", command_path.join(" "));
 
     writeln!(
         output,
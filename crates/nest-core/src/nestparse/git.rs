@@ -0,0 +1,146 @@
+//! Git-backed include sources.
+//!
+//! Repositories are cloned (bare) into a cache directory once and fetched
+//! again only when a requested ref is missing or a refresh is forced.
+//! Files are read directly from the object database with `git show`, so no
+//! working tree is ever checked out.
+
+use super::lock::hash_content;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Reads a file from a git repository at the given ref.
+///
+/// Returns the file content and the commit the ref resolved to.
+///
+/// # Arguments
+///
+/// * `repo` - Repository URL or path, as understood by `git clone`
+/// * `reference` - Branch, tag or commit
+/// * `path` - Path of the file inside the repository
+/// * `cache_dir` - Directory holding the bare clones
+/// * `refresh` - Fetch from the remote even if the ref is already known
+pub fn read_file(
+    repo: &str,
+    reference: &str,
+    path: &str,
+    cache_dir: &Path,
+    refresh: bool,
+) -> Result<(String, String), String> {
+    let clone_dir = ensure_clone(repo, cache_dir)?;
+
+    if refresh || !has_ref(&clone_dir, reference) {
+        fetch(&clone_dir, repo)?;
+    }
+
+    let commit = resolve_commit(&clone_dir, reference)
+        .map_err(|e| format!("Unknown ref '{}' in {}: {}", reference, repo, e))?;
+    let content = run_git(&clone_dir, &["show", &format!("{}:{}", commit, path)]).map_err(|e| {
+        format!(
+            "Cannot read '{}' at '{}' from {}: {}",
+            path, reference, repo, e
+        )
+    })?;
+    Ok((content, commit))
+}
+
+/// Returns the bare clone of `repo`, cloning it if needed.
+pub fn ensure_clone(repo: &str, cache_dir: &Path) -> Result<PathBuf, String> {
+    let clone_dir = clone_dir(repo, cache_dir);
+    if clone_dir.exists() {
+        return Ok(clone_dir);
+    }
+
+    std::fs::create_dir_all(cache_dir)
+        .map_err(|e| format!("Failed to create git cache directory: {}", e))?;
+
+    let status = Command::new("git")
+        .args(["clone", "--bare", "--quiet", "--", repo])
+        .arg(&clone_dir)
+        .status()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if !status.success() {
+        let _ = std::fs::remove_dir_all(&clone_dir);
+        return Err(format!("Failed to clone {}", repo));
+    }
+    Ok(clone_dir)
+}
+
+/// Fetches all branches and tags of the clone from `repo`.
+pub fn fetch(clone_dir: &Path, repo: &str) -> Result<(), String> {
+    run_git(
+        clone_dir,
+        &[
+            "fetch",
+            "--quiet",
+            "--force",
+            "--tags",
+            "--",
+            repo,
+            "+refs/heads/*:refs/heads/*",
+        ],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("Failed to fetch {}: {}", repo, e))
+}
+
+/// Resolves a ref to a commit id.
+pub fn resolve_commit(clone_dir: &Path, reference: &str) -> Result<String, String> {
+    run_git(
+        clone_dir,
+        &[
+            "rev-parse",
+            "--verify",
+            &format!("{}^{{commit}}", reference),
+        ],
+    )
+    .map(|s| s.trim().to_string())
+}
+
+fn has_ref(clone_dir: &Path, reference: &str) -> bool {
+    resolve_commit(clone_dir, reference).is_ok()
+}
+
+fn clone_dir(repo: &str, cache_dir: &Path) -> PathBuf {
+    cache_dir.join(&hash_content(repo)[..16])
+}
+
+/// Runs a git command in `dir` and returns its stdout.
+pub(crate) fn run_git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Lists the tag names of a remote repository.
+pub fn list_remote_tags(repo: &str) -> Result<Vec<String>, String> {
+    let output = Command::new("git")
+        .args(["ls-remote", "--tags", "--refs", "--", repo])
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list tags of {}: {}",
+            repo,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1))
+        .filter_map(|r| r.strip_prefix("refs/tags/"))
+        .map(|tag| tag.to_string())
+        .collect())
+}
@@ -18,13 +18,13 @@ pub fn handle_version() {
         "glibc"
     };
 
-    println!(
-        "{}nest{} {} ({})",
+    OutputFormatter::print(&format!(
+        "{}nest{} {} ({})\n",
         colors::BRIGHT_BLUE,
         colors::RESET,
         OutputFormatter::value(env!("CARGO_PKG_VERSION")),
         libc_info
-    );
+    ));
     std::process::exit(0);
 }
 
@@ -56,14 +56,14 @@ pub fn handle_json(commands: &[Command]) -> Result<(), Box<dyn std::error::Error
 pub fn handle_show_ast(commands: &[Command]) {
     use super::display::print_command;
     use super::output::colors;
-    println!(
-        "{}🌳{} {}AST Structure:{}\n",
+    OutputFormatter::print(&format!(
+        "{}🌳{} {}AST Structure:{}\n\n",
         colors::BRIGHT_GREEN,
         colors::RESET,
         colors::BRIGHT_CYAN,
         colors::RESET
-    );
-    for command in commands {
+    ));
+    for command in commands.iter().filter(|c| !c.is_private()) {
         print_command(command, 0);
         println!();
     }
@@ -363,17 +363,16 @@ fn download_examples_from_release(
 
                 use super::output::colors;
                 OutputFormatter::success("Examples folder downloaded successfully!");
-                println!(
-                    "  {}Location:{} {}",
+                OutputFormatter::print(&format!(
+                    "  {} {}\n",
                     OutputFormatter::help_label("Location:"),
-                    colors::RESET,
                     OutputFormatter::path(&examples_dir.display().to_string())
-                );
-                println!(
-                    "\n{}Changing to examples directory...{}",
+                ));
+                OutputFormatter::print(&format!(
+                    "\n{}Changing to examples directory...{}\n",
                     colors::BRIGHT_CYAN,
                     colors::RESET
-                );
+                ));
                 println!("Run: cd examples");
                 true
             } else {
@@ -470,19 +469,18 @@ fn download_examples_from_repo(current_dir: &std::path::Path, examples_dir: &std
 
                         use super::output::colors;
                         OutputFormatter::success("Examples folder downloaded successfully!");
-                        println!(
-                            "  {}Location:{} {}",
+                        OutputFormatter::print(&format!(
+                            "  {} {}\n",
                             OutputFormatter::help_label("Location:"),
-                            colors::RESET,
                             OutputFormatter::path(&examples_dir.display().to_string())
-                        );
+                        ));
 
                         // Change directory to examples
-                        println!(
-                            "\n{}Changing to examples directory...{}",
+                        OutputFormatter::print(&format!(
+                            "\n{}Changing to examples directory...{}\n",
                             colors::BRIGHT_CYAN,
                             colors::RESET
-                        );
+                        ));
                         println!("Run: cd examples");
                     }
                     Err(e) => {
@@ -606,17 +604,16 @@ fn download_examples_archive(current_dir: &std::path::Path, examples_dir: &std::
 
                         use super::output::colors;
                         OutputFormatter::success("Examples folder downloaded successfully!");
-                        println!(
-                            "  {}Location:{} {}",
+                        OutputFormatter::print(&format!(
+                            "  {} {}\n",
                             OutputFormatter::help_label("Location:"),
-                            colors::RESET,
                             OutputFormatter::path(&examples_dir.display().to_string())
-                        );
-                        println!(
-                            "\n{}Changing to examples directory...{}",
+                        ));
+                        OutputFormatter::print(&format!(
+                            "\n{}Changing to examples directory...{}\n",
                             colors::BRIGHT_CYAN,
                             colors::RESET
-                        );
+                        ));
                         println!("Run: cd examples");
                     }
                     Err(e) => {
@@ -1056,10 +1053,10 @@ pub fn handle_update(recreate: bool) {
     if let Ok(output) = ProcessCommand::new(&binary_path).arg("--version").output() {
         if output.status.success() {
             let version_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            println!(
-                "  Current version: {}",
+            OutputFormatter::print(&format!(
+                "  Current version: {}\n",
                 OutputFormatter::value(&version_str)
-            );
+            ));
         }
     }
 }
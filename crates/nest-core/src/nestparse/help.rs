@@ -1,55 +1,273 @@
-//! Help message formatting for command groups.
+//! Help message formatting for commands and command groups.
 //!
-//! This module provides utilities for displaying help messages when
-//! a group command is called without a subcommand and has no default.
+//! This module renders the command tables used by `nest --help`, group help
+//! and `--list`: commands are sectioned by `category:`, ordered by
+//! `help.order:` and aligned, with descriptions wrapped to the terminal width.
 
 use super::ast::{Command, Directive};
-use super::output::OutputFormatter;
+use super::directives::DirectiveResolver;
+use super::output::{colors, OutputFormatter};
+use crate::constants::DEFAULT_SUBCOMMAND;
+
+/// Heading of the section holding commands without a `category:`.
+const UNCATEGORIZED_HEADING: &str = "Commands";
+
+/// Widest the signature column gets before descriptions move to their own line.
+const MAX_SIGNATURE_WIDTH: usize = 40;
+
+/// Width used when the terminal size can't be determined (e.g. output is piped).
+const FALLBACK_TERMINAL_WIDTH: usize = 100;
 
 /// Formats and prints help messages for command groups.
 ///
 /// This is a utility struct with static methods for help formatting.
 pub struct HelpFormatter;
 
+/// A single line of a command table before alignment.
+struct Row {
+    depth: usize,
+    signature: String,
+    description: String,
+    markers: Vec<String>,
+}
+
 impl HelpFormatter {
     /// Prints a help message for a group command.
     ///
     /// The help message includes:
     /// - Usage information
     /// - Command description (if available)
-    /// - List of available subcommands with their descriptions
+    /// - The subcommands as a sectioned table
     ///
     /// # Arguments
     ///
     /// * `command` - The group command to show help for
     /// * `command_path` - The full path to the command (e.g., ["dev"])
     pub fn print_group_help(command: &Command, command_path: &[String]) {
-        println!(
-            "{} nest {} [COMMAND]",
+        let mut output = format!(
+            "{} nest {} [COMMAND]\n\n",
             OutputFormatter::help_label("Usage:"),
             OutputFormatter::help_command(&command_path.join(" "))
         );
-        println!();
 
         if let Some(desc) = Self::extract_description(&command.directives) {
-            println!("{}", OutputFormatter::help_description(desc));
-            println!();
+            output.push_str(&OutputFormatter::help_description(desc));
+            output.push_str("\n\n");
         }
 
-        println!("{}", OutputFormatter::help_label("Available commands:"));
-        for child in &command.children {
-            let child_desc = Self::extract_description(&child.directives);
-            let command_with_params = child.to_string();
-            if let Some(desc) = child_desc {
-                println!(
-                    "  {}  {}",
-                    OutputFormatter::help_command(&command_with_params),
-                    OutputFormatter::help_description(desc)
-                );
-            } else {
-                println!("  {}", OutputFormatter::help_command(&command_with_params));
+        output.push_str(&Self::command_table(&command.children, false));
+        OutputFormatter::print(&output);
+    }
+
+    /// Renders commands as aligned tables, one section per category.
+    ///
+    /// Uncategorized commands come first under "Commands:", followed by each
+    /// category in order of first appearance. Within a section, commands with a
+    /// `help.order` come first in ascending order, the rest in definition order.
+    /// Private commands and `default` subcommands are left out.
+    ///
+    /// Each row shows the command signature (parameters and defaults), its
+    /// description and markers for aliases, `privileged`, `require_confirm`
+    /// and `watch`. With `nested`, subcommands are listed under their group.
+    pub fn command_table(commands: &[Command], nested: bool) -> String {
+        let mut sections: Vec<(String, Vec<&Command>)> = Vec::new();
+        for command in Self::visible(commands) {
+            let heading = DirectiveResolver::get_category(&command.directives)
+                .unwrap_or_else(|| UNCATEGORIZED_HEADING.to_string());
+            match sections
+                .iter_mut()
+                .find(|(existing, _)| *existing == heading)
+            {
+                Some((_, members)) => members.push(command),
+                None => sections.push((heading, vec![command])),
+            }
+        }
+        if let Some(index) = sections
+            .iter()
+            .position(|(heading, _)| heading == UNCATEGORIZED_HEADING)
+        {
+            let uncategorized = sections.remove(index);
+            sections.insert(0, uncategorized);
+        }
+
+        let sections: Vec<(String, Vec<Row>)> = sections
+            .into_iter()
+            .map(|(heading, members)| {
+                let mut rows = Vec::new();
+                for command in members {
+                    Self::collect_rows(command, 0, nested, &mut rows);
+                }
+                (heading, rows)
+            })
+            .collect();
+
+        let signature_width = sections
+            .iter()
+            .flat_map(|(_, rows)| rows)
+            .map(|row| row.depth * 2 + row.signature.chars().count())
+            .filter(|width| *width <= MAX_SIGNATURE_WIDTH)
+            .max()
+            .unwrap_or(0);
+        let description_width = Self::terminal_width()
+            .saturating_sub(signature_width + 4)
+            .max(20);
+
+        let mut out = String::new();
+        for (index, (heading, rows)) in sections.iter().enumerate() {
+            if index > 0 {
+                out.push('\n');
+            }
+            out.push_str(&format!(
+                "{}\n",
+                OutputFormatter::help_label(&format!("{}:", heading))
+            ));
+            for row in rows {
+                let indent = "  ".repeat(row.depth);
+                let width = row.depth * 2 + row.signature.chars().count();
+                let signature = OutputFormatter::help_command(&row.signature);
+                let lines = Self::wrap(&row.description, &row.markers, description_width);
+                let continuation = " ".repeat(signature_width + 4);
+
+                if lines.is_empty() {
+                    out.push_str(&format!("  {}{}\n", indent, signature));
+                    continue;
+                }
+                let mut lines = lines.iter();
+                if width > signature_width {
+                    // Too long to align: the description starts on the next line
+                    out.push_str(&format!("  {}{}\n", indent, signature));
+                } else if let Some(first) = lines.next() {
+                    out.push_str(&format!(
+                        "  {}{}{}  {}\n",
+                        indent,
+                        signature,
+                        " ".repeat(signature_width - width),
+                        first
+                    ));
+                }
+                for line in lines {
+                    out.push_str(&format!("{}{}\n", continuation, line));
+                }
             }
         }
+        out
+    }
+
+    fn visible(commands: &[Command]) -> Vec<&Command> {
+        let mut visible: Vec<&Command> = commands
+            .iter()
+            .filter(|c| !c.is_private() && c.name != DEFAULT_SUBCOMMAND)
+            .collect();
+        visible.sort_by_key(|c| {
+            DirectiveResolver::get_help_order(&c.directives).map_or((1, 0), |order| (0, order))
+        });
+        visible
+    }
+
+    fn collect_rows(command: &Command, depth: usize, nested: bool, rows: &mut Vec<Row>) {
+        rows.push(Row {
+            depth,
+            signature: command.to_string(),
+            description: Self::extract_description(&command.directives)
+                .unwrap_or_default()
+                .to_string(),
+            markers: Self::markers(command),
+        });
+        if nested {
+            for child in Self::visible(&command.children) {
+                Self::collect_rows(child, depth + 1, nested, rows);
+            }
+        }
+    }
+
+    /// Returns the markers shown after a command's description.
+    fn markers(command: &Command) -> Vec<String> {
+        let mut markers = Vec::new();
+        let aliases = DirectiveResolver::get_aliases(&command.directives);
+        if !aliases.is_empty() {
+            markers.push(format!("[aliases: {}]", aliases.join(", ")));
+        }
+        if DirectiveResolver::get_privileged_directive(&command.directives) {
+            markers.push("[privileged]".to_string());
+        }
+        if DirectiveResolver::get_require_confirm_directive(&command.directives).is_some() {
+            markers.push("[confirm]".to_string());
+        }
+        if command
+            .directives
+            .iter()
+            .any(|d| matches!(d, Directive::Watch(_)))
+        {
+            markers.push("[watch]".to_string());
+        }
+        markers
+    }
+
+    /// Wraps a description and its markers into lines of at most `width`
+    /// characters, breaking at spaces. Markers are dimmed.
+    fn wrap(description: &str, markers: &[String], width: usize) -> Vec<String> {
+        let words = description
+            .split_whitespace()
+            .map(|word| (word, false))
+            .chain(
+                markers
+                    .iter()
+                    .flat_map(|marker| marker.split_whitespace())
+                    .map(|word| (word, true)),
+            );
+
+        let mut lines: Vec<Vec<(&str, bool)>> = Vec::new();
+        let mut line_width = 0;
+        for (word, is_marker) in words {
+            let word_width = word.chars().count();
+            match lines.last_mut() {
+                Some(line) if line_width + 1 + word_width <= width => {
+                    line.push((word, is_marker));
+                    line_width += 1 + word_width;
+                }
+                _ => {
+                    lines.push(vec![(word, is_marker)]);
+                    line_width = word_width;
+                }
+            }
+        }
+
+        lines
+            .into_iter()
+            .map(|line| {
+                let text = |marker: bool| {
+                    line.iter()
+                        .filter(|(_, is_marker)| *is_marker == marker)
+                        .map(|(word, _)| *word)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+                let (plain, dimmed) = (text(false), text(true));
+                match (plain.is_empty(), dimmed.is_empty()) {
+                    (_, true) => OutputFormatter::help_description(&plain),
+                    (true, false) => format!("{}{}{}", colors::GRAY, dimmed, colors::RESET),
+                    (false, false) => format!(
+                        "{} {}{}{}",
+                        OutputFormatter::help_description(&plain),
+                        colors::GRAY,
+                        dimmed,
+                        colors::RESET
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns `$COLUMNS` if set, otherwise the width of the attached terminal.
+    fn terminal_width() -> usize {
+        std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .or_else(|| {
+                terminal_size::terminal_size()
+                    .map(|(terminal_size::Width(width), _)| width as usize)
+            })
+            .unwrap_or(FALLBACK_TERMINAL_WIDTH)
     }
 
     fn extract_description(directives: &[Directive]) -> Option<&str> {
@@ -62,3 +280,38 @@ impl HelpFormatter {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nestparse::parser::Parser;
+
+    #[test]
+    fn test_command_table_sections() {
+        let content = r#"migrate:
+    category: Database
+    privileged
+    script: echo m
+build(!target: str = "x86"):
+    script: echo b
+seed:
+    category: Database
+    help.order: -1
+    watch: "db/seeds/*.sql"
+    script: echo s
+_setup:
+    script: echo s
+"#;
+        let mut parser = Parser::new(content);
+        let commands = parser.parse().unwrap().commands;
+        let table = HelpFormatter::command_table(&commands, false);
+
+        let position = |needle: &str| table.find(needle).unwrap();
+        assert!(position("Commands:") < position("Database:"));
+        assert!(position("build(!target: str = \"x86\")") < position("Database:"));
+        assert!(position("seed") < position("migrate"));
+        assert!(table.contains("[privileged]"));
+        assert!(table.contains("[watch]"));
+        assert!(!table.contains("_setup"));
+    }
+}
@@ -5,10 +5,11 @@
 
 use super::codegen;
 use super::file::read_file_unchecked;
+use super::lock::{RemoteMode, RemoteStore};
 use super::parser::Parser;
 use super::path::is_config_file;
+use super::source::Source;
 use std::fs;
-use std::io::Read;
 use std::path::{Path, PathBuf};
 
 /// Errors that can occur during include processing.
@@ -35,6 +36,8 @@ impl std::fmt::Display for IncludeError {
     }
 }
 
+impl std::error::Error for IncludeError {}
+
 /// Processes include directives in the content and returns merged content.
 ///
 /// This function:
@@ -53,27 +56,63 @@ impl std::fmt::Display for IncludeError {
 ///
 /// - `Ok(merged_content)` - Content with all includes processed
 /// - `Err(error)` - Error if include processing fails
+///
+/// Remote includes are checked against the `Nestfile.lock` next to
+/// `base_path`, but the lockfile is never written. Use
+/// [`process_includes_with_remote`] and [`RemoteStore::save`] to pin new ones.
 pub fn process_includes(
     content: &str,
     base_path: &Path,
     visited: &mut std::collections::HashSet<PathBuf>,
+) -> Result<String, IncludeError> {
+    let mut remote =
+        RemoteStore::for_config(base_path, RemoteMode::Default).map_err(IncludeError::IoError)?;
+    process_includes_with_remote(content, base_path, visited, &mut remote)
+}
+
+/// Processes include directives, resolving remote includes through `remote`.
+///
+/// Same as [`process_includes`], but the caller controls how remote URLs are
+/// resolved (default, `--offline` or `nest lock update`) and decides whether
+/// to save the lockfile afterwards.
+pub fn process_includes_with_remote(
+    content: &str,
+    base_path: &Path,
+    visited: &mut std::collections::HashSet<PathBuf>,
+    remote: &mut RemoteStore,
 ) -> Result<String, IncludeError> {
     // Normalize base_path for comparison and path resolution
     let normalized_base = base_path
         .canonicalize()
         .map_err(|e| IncludeError::IoError(format!("Cannot canonicalize base path: {}", e)))?;
 
-    if visited.contains(&normalized_base) {
-        return Err(IncludeError::CircularInclude(
-            base_path.display().to_string(),
-        ));
-    }
-    visited.insert(normalized_base.clone());
+    process_source_includes(content, &Source::Local(normalized_base), visited, remote)
+}
 
-    // Get base directory from canonicalized path
-    let base_dir = normalized_base
-        .parent()
-        .ok_or_else(|| IncludeError::InvalidPath("Base path has no parent".to_string()))?;
+/// Processes include directives in content that was loaded from `base`.
+///
+/// Relative includes are resolved against the filesystem for local sources
+/// and against the URL or repository path for remote sources.
+fn process_source_includes(
+    content: &str,
+    base: &Source,
+    visited: &mut std::collections::HashSet<PathBuf>,
+    remote: &mut RemoteStore,
+) -> Result<String, IncludeError> {
+    let visit_key = base.visit_key();
+    if visited.contains(&visit_key) {
+        return Err(IncludeError::CircularInclude(base.to_string()));
+    }
+    visited.insert(visit_key.clone());
+
+    // Get base directory for local sources
+    let base_dir = match base {
+        Source::Local(path) => Some(
+            path.parent()
+                .ok_or_else(|| IncludeError::InvalidPath("Base path has no parent".to_string()))?,
+        ),
+        _ => None,
+    };
 
     let mut result = String::new();
     let lines = content.lines();
@@ -169,17 +208,31 @@ pub fn process_includes(
 
         // Resolve the include path (remove quotes if present)
         let include_path_str_clean = include_path_str.trim_matches('"').trim_matches('\'');
-        let include_path = base_dir.join(include_path_str_clean);
+        let include_source = base
+            .resolve(include_path_str_clean)
+            .map_err(IncludeError::InvalidPath)?;
 
         // Process the include
-        let included_content =
-            match resolve_and_load_include(&include_path, base_dir, visited, filter_slice)? {
-                Some(content) => content,
-                None => {
-                    // Include path didn't match any files, skip this include
-                    continue;
-                }
-            };
+        let loaded = match (&include_source, base_dir) {
+            (Some(source), _) => load_remote_source(source, visited, filter_slice, remote)?,
+            (None, Some(base_dir)) => {
+                let include_path = base_dir.join(include_path_str_clean);
+                resolve_and_load_include(&include_path, base_dir, visited, filter_slice, remote)?
+            }
+            (None, None) => {
+                return Err(IncludeError::InvalidPath(format!(
+                    "Cannot resolve '{}' inside {}",
+                    include_path_str_clean, base
+                )))
+            }
+        };
+        let included_content = match loaded {
+            Some(content) => content,
+            None => {
+                // Include path didn't match any files, skip this include
+                continue;
+            }
+        };
 
         // Add the included content
         if let Some(group_name) = into_group {
@@ -209,19 +262,16 @@ pub fn process_includes(
                 result.push_str(content_line);
                 result.push('\n');
             }
+            result.push_str(&format!("# @source: {}\n", base));
         } else {
-            // Process includes in the included content
-            let processed_inc = process_includes(&included_content, &include_path, visited)?;
-
-            // Add marker for start of include
-            result.push_str(&format!("# @source: {}\n", include_path.display()));
-            result.push_str(&processed_inc);
+            // Loaded content is already processed and starts with its own source marker
+            result.push_str(&included_content);
             // Add marker for restoring current context
-            result.push_str(&format!("# @source: {}\n", normalized_base.display()));
+            result.push_str(&format!("# @source: {}\n", base));
         }
     }
 
-    visited.remove(&normalized_base);
+    visited.remove(&visit_key);
     Ok(result)
 }
 
@@ -248,18 +298,14 @@ fn resolve_and_load_include(
     base_dir: &Path,
     visited: &mut std::collections::HashSet<PathBuf>,
     filter: Option<&[&str]>,
+    remote: &mut RemoteStore,
 ) -> Result<Option<String>, IncludeError> {
     // Check if it's a wildcard pattern
     let path_str = include_path.to_string_lossy();
 
     if path_str.contains('*') {
         // Pattern matching: app2/*.nest
-        return load_pattern_files(include_path, base_dir, visited, filter);
-    }
-
-    // Check for remote URL
-    if path_str.starts_with("http://") || path_str.starts_with("https://") {
-        return load_remote_file(&path_str, visited, filter);
+        return load_pattern_files(include_path, base_dir, visited, filter, remote);
     }
 
     // Check if it's a directory (ends with /)
@@ -278,7 +324,7 @@ fn resolve_and_load_include(
             dir_path
         };
 
-        return load_directory_files(&dir_path, visited, filter);
+        return load_directory_files(&dir_path, visited, filter, remote);
     }
 
     // Specific file: app1/nestfile
@@ -290,12 +336,12 @@ fn resolve_and_load_include(
 
     // Check if path exists and is a file
     if file_path.exists() && file_path.is_file() {
-        return load_single_file(&file_path, visited, filter);
+        return load_single_file(&file_path, visited, filter, remote);
     }
 
     // If path doesn't exist or is a directory, try to resolve it
     if file_path.is_dir() {
-        return load_directory_files(&file_path, visited, filter);
+        return load_directory_files(&file_path, visited, filter, remote);
     }
 
     // If path doesn't have extension, try to find a config file
@@ -305,7 +351,7 @@ fn resolve_and_load_include(
             if let Some(name_str) = file_name.to_str() {
                 if is_config_file(name_str) {
                     // It's already a config file name
-                    return load_single_file(&file_path, visited, filter);
+                    return load_single_file(&file_path, visited, filter, remote);
                 }
             }
         }
@@ -314,13 +360,13 @@ fn resolve_and_load_include(
         for config_name in ["nestfile", "Nestfile", "nest", "Nest"] {
             let config_path = file_path.join(config_name);
             if config_path.exists() && config_path.is_file() {
-                return load_single_file(&config_path, visited, filter);
+                return load_single_file(&config_path, visited, filter, remote);
             }
         }
 
         // If the path itself doesn't exist, try it as a directory
         if !file_path.exists() {
-            return load_directory_files(&file_path, visited, filter);
+            return load_directory_files(&file_path, visited, filter, remote);
         }
     }
 
@@ -331,80 +377,28 @@ fn resolve_and_load_include(
     )))
 }
 
-/// Loads content from a remote URL.
-fn load_remote_file(
-    url: &str,
+/// Loads content from a remote source (URL or git repository).
+///
+/// Includes inside the remote file are resolved relative to its location.
+fn load_remote_source(
+    source: &Source,
     visited: &mut std::collections::HashSet<PathBuf>,
     filter: Option<&[&str]>,
+    remote: &mut RemoteStore,
 ) -> Result<Option<String>, IncludeError> {
-    // Use the URL as the "canonical path" for cycle detection
-    // We treat the URL string as a dummy PathBuf
-    let url_path = PathBuf::from(url);
-
-    if visited.contains(&url_path) {
-        return Err(IncludeError::CircularInclude(url.to_string()));
-    }
-
-    // Fetch the content
-    let content = match ureq::get(url).call() {
-        Ok(response) => {
-            if response.status() != 200 {
-                return Err(IncludeError::IoError(format!(
-                    "Failed to fetch remote include {}: status {}",
-                    url,
-                    response.status()
-                )));
-            }
-            let mut body = String::new();
-            response
-                .into_body()
-                .as_reader()
-                .read_to_string(&mut body)
-                .map_err(|e| {
-                    IncludeError::IoError(format!("Failed to read remote content: {}", e))
-                })?;
-            body
-        }
-        Err(e) => {
-            return Err(IncludeError::IoError(format!(
-                "Failed to fetch remote include {}: {}",
-                url, e
-            )))
-        }
-    };
+    if visited.contains(&source.visit_key()) {
+        return Err(IncludeError::CircularInclude(source.to_string()));
+    }
 
-    // Recursively process includes in the included file
-    // Note: Relative includes in a remote file will fail because we can't resolve them easily
-    // unless we track the base URL. For now, we assume remote files only have absolute includes or no includes.
-    // If we want to support relative includes in remote files, we need to pass a "Base URI" instead of PathBuf.
-    // Given the current architecture uses PathBuf, let's pass a dummy path but maybe warn about relative includes?
-    // Actually, process_includes takes a Path. If we pass the URL as Path, resolving relative paths will likely fail
-    // or produce weird paths.
-    // Ideally, we shouldn't allow relative includes in remote files unless we properly resolve URLs.
-    // For simplicity: treat remote url as a "file" in current dir? No, that breaks relative paths.
-    // Let's just process includes but use a dummy base path that indicates it's remote.
-
-    // We can't really support relative includes inside remote files without a significant refactor
-    // to separate Filesystem vs URL resolution.
-    // For now, let's just process it with the current directory as base, which means relative includes
-    // will look in the local file system. This is probably "safe" but maybe not what user expects.
-    // Let's rely on absolute includes or remote includes inside remote files.
-
-    // Actually, let's just reuse the current logic, but mark visited.
-    // We use a dummy path for "base_path" so process_includes doesn't crash?
-    // process_includes uses canonicalize() on base_path. Attempting to canonicalize a URL or non-existent path will fail.
-
-    // HACK: To avoid refactoring everything, let's skip recursive include processing for remote files for now,
-    // OR we can create a temporary file? No, that's messy.
-    // Let's check process_includes implementation again.
-    // It canonicalizes base_path.
-
-    // If we simply return the content without recursive processing, then `@include` inside remote file won't work.
-    // This is acceptable for a first version.
+    // Fetch the content (verified against Nestfile.lock, served from cache when pinned)
+    let content = remote.fetch(source).map_err(IncludeError::IoError)?;
+
+    // Recursively process includes in the remote file, relative to its location
+    let processed_content = process_source_includes(&content, source, visited, remote)?;
 
     // If a filter is provided, parse and filter the commands
     let final_content = if let Some(filter_paths) = filter {
-        let mut parser = Parser::new(&content);
+        let mut parser = Parser::new(&processed_content);
         let parse_result = parser.parse().map_err(|e| {
             IncludeError::InvalidPath(format!(
                 "Error parsing remote included file for filtering: {:?}",
@@ -422,11 +416,11 @@ fn load_remote_file(
         }
         filtered_content
     } else {
-        content
+        processed_content
     };
 
     let mut result = String::new();
-    result.push_str(&format!("# @source: {}\n", url));
+    result.push_str(&format!("# @source: {}\n", source));
     result.push_str(&final_content);
 
     Ok(Some(result))
@@ -437,6 +431,7 @@ fn load_single_file(
     file_path: &Path,
     visited: &mut std::collections::HashSet<PathBuf>,
     filter: Option<&[&str]>,
+    remote: &mut RemoteStore,
 ) -> Result<Option<String>, IncludeError> {
     let canonical_path = file_path
         .canonicalize()
@@ -454,7 +449,8 @@ fn load_single_file(
 
     // Recursively process includes in the included file
     // Note: We normally don't pass the filter recursively because the filter applies to the *result* of the file.
-    let processed_content = process_includes(&content, &canonical_path, visited)?;
+    let processed_content =
+        process_includes_with_remote(&content, &canonical_path, visited, remote)?;
 
     // If a filter is provided, parse and filter the commands
     let final_content = if let Some(filter_paths) = filter {
@@ -494,6 +490,7 @@ fn load_pattern_files(
     base_dir: &Path,
     visited: &mut std::collections::HashSet<PathBuf>,
     filter: Option<&[&str]>,
+    remote: &mut RemoteStore,
 ) -> Result<Option<String>, IncludeError> {
     let pattern_str = pattern_path.to_string_lossy();
 
@@ -542,7 +539,7 @@ fn load_pattern_files(
             if let Some(name_str) = file_name.to_str() {
                 // Simple pattern matching (supports * wildcard)
                 if matches_pattern(name_str, pattern) {
-                    if let Some(content) = load_single_file(&file_path, visited, filter)? {
+                    if let Some(content) = load_single_file(&file_path, visited, filter, remote)? {
                         merged_content.push_str(&content);
                         merged_content.push('\n');
                         found_any = true;
@@ -564,6 +561,7 @@ fn load_directory_files(
     dir_path: &Path,
     visited: &mut std::collections::HashSet<PathBuf>,
     filter: Option<&[&str]>,
+    remote: &mut RemoteStore,
 ) -> Result<Option<String>, IncludeError> {
     if !dir_path.exists() {
         return Ok(None); // Directory doesn't exist, not an error
@@ -599,7 +597,7 @@ fn load_directory_files(
         if let Some(file_name) = file_path.file_name() {
             if let Some(name_str) = file_name.to_str() {
                 if is_config_file(name_str) {
-                    if let Some(content) = load_single_file(&file_path, visited, filter)? {
+                    if let Some(content) = load_single_file(&file_path, visited, filter, remote)? {
                         merged_content.push_str(&content);
                         merged_content.push('\n');
                         found_any = true;
@@ -792,6 +790,8 @@ mod tests {
             local_variables: vec![],
             local_constants: vec![],
             source_file: None,
+            merge_mode: Default::default(),
+            extends: None,
         }
     }
 
@@ -801,6 +801,112 @@ mod tests {
         cmd
     }
 
+    /// Serves `files` over HTTP on a random local port and returns the base URL.
+    fn serve(files: Vec<(&'static str, &'static str)>) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line.split_whitespace().nth(1).unwrap_or("");
+                let response = match files.iter().find(|(p, _)| *p == path) {
+                    Some((_, body)) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    ),
+                    None => {
+                        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_remote_relative_includes() {
+        let base_url = serve(vec![
+            ("/lib/main.nest", "import build from ./tasks/build.nest\n"),
+            ("/lib/tasks/build.nest", "build:\n    script: echo build\n"),
+        ]);
+
+        let dir = std::env::temp_dir().join(format!("nest-include-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let root = dir.join("nestfile");
+        let content = format!("import * from {}/lib/main.nest into lib\n", base_url);
+        fs::write(&root, &content).unwrap();
+
+        let mut remote =
+            RemoteStore::new(dir.join("Nestfile.lock"), None, RemoteMode::Default).unwrap();
+        let mut visited = std::collections::HashSet::new();
+        let result =
+            process_includes_with_remote(&content, &root, &mut visited, &mut remote).unwrap();
+
+        let commands = Parser::new(&result).parse().unwrap().commands;
+        assert_eq!(commands[0].name, "lib");
+        assert_eq!(commands[0].children[0].name, "build");
+        assert_eq!(remote.resolved_count(), 2);
+    }
+
+    #[test]
+    fn test_import_into_scopes_symbols() {
+        let dir = std::env::temp_dir().join(format!("nest-namespace-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("docker.nest"),
+            "var IMAGE = \"app\"\n\nfunction build_image():\n    echo {{IMAGE}}\n\nbuild:\n    script: build_image()\n",
+        )
+        .unwrap();
+        let root = dir.join("nestfile");
+        let content = "var IMAGE = \"root\"\nimport * from docker.nest into docker\n";
+        fs::write(&root, content).unwrap();
+
+        let mut visited = std::collections::HashSet::new();
+        let result = process_includes(content, &root, &mut visited).unwrap();
+        let parsed = Parser::new(&result).parse().unwrap();
+
+        assert_eq!(parsed.variables.len(), 1);
+        assert_eq!(parsed.commands[0].name, "docker");
+        assert_eq!(parsed.commands[0].local_variables[0].name, "IMAGE");
+        assert_eq!(parsed.functions[0].name, "docker.build_image");
+        assert_eq!(parsed.commands[0].children[0].name, "build");
+    }
+
+    #[test]
+    fn test_source_resolve() {
+        let url = Source::Url("https://example.com/lib/main.nest?x=1".to_string());
+        assert_eq!(
+            url.resolve("../shared/a.nest").unwrap(),
+            Some(Source::Url("https://example.com/shared/a.nest".to_string()))
+        );
+        assert_eq!(
+            url.resolve("/root.nest").unwrap(),
+            Some(Source::Url("https://example.com/root.nest".to_string()))
+        );
+        assert!(url.resolve("tasks/*.nest").is_err());
+
+        let git = Source::parse("git+https://host/org/tasks.git@v1.2#docker/main.nest")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            git.resolve("./build.nest").unwrap().unwrap().to_string(),
+            "git+https://host/org/tasks.git@v1.2#docker/build.nest"
+        );
+
+        let local = Source::Local(PathBuf::from("/tmp/nestfile"));
+        assert_eq!(local.resolve("tasks.nest").unwrap(), None);
+        assert!(local.resolve("git+--upload-pack=touch /tmp/x").is_err());
+        assert!(local
+            .resolve("git+https://host/org/tasks.git@--output=/tmp/x")
+            .is_err());
+    }
+
     #[test]
     fn test_filter_commands_deep() {
         // defined: group1 -> sub1, sub2
@@ -2,7 +2,12 @@
 //!
 //! This module handles interactive user prompts and confirmations.
 
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+
+/// Returns `true` when stdin is attached to a terminal, so prompts can be answered.
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal()
+}
 
 /// Prompts user for confirmation before executing a command.
 ///
@@ -73,3 +78,128 @@ pub fn prompt_confirmation(
         }
     }
 }
+
+/// Prompts user for a value until it passes `check`.
+///
+/// # Arguments
+///
+/// * `label` - Text shown before the input, e.g. the parameter name and type
+/// * `secret` - Read the value without echoing it (Unix terminals only)
+/// * `check` - Validation applied to every answer; its error is shown and the question repeated
+///
+/// # Returns
+///
+/// Returns `Ok(value)` with the first accepted answer,
+/// or `Err(message)` if there was an error reading input.
+pub fn prompt_value(
+    label: &str,
+    secret: bool,
+    check: &dyn Fn(&str) -> Result<(), String>,
+) -> Result<String, String> {
+    loop {
+        print!("{}: ", label);
+        io::stdout()
+            .flush()
+            .map_err(|e| format!("Failed to flush stdout: {}", e))?;
+
+        let input = if secret {
+            read_line_masked()?
+        } else {
+            read_line()?
+        };
+        let value = input.trim();
+
+        if value.is_empty() {
+            eprintln!("A value is required.");
+            continue;
+        }
+
+        match check(value) {
+            Ok(()) => return Ok(value.to_string()),
+            Err(message) => eprintln!("{}", message),
+        }
+    }
+}
+
+/// Prompts user to pick one of `options`, by number or by value.
+///
+/// # Returns
+///
+/// Returns `Ok(option)` with the selected option,
+/// or `Err(message)` if there was an error reading input.
+pub fn prompt_select(label: &str, options: &[String]) -> Result<String, String> {
+    println!("{}:", label);
+    for (i, option) in options.iter().enumerate() {
+        println!("  {}) {}", i + 1, option);
+    }
+
+    prompt_value(&format!("Select [1-{}]", options.len()), false, &|answer| {
+        if select_option(answer, options).is_some() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Please enter a number from 1 to {} or one of the listed values.",
+                options.len()
+            ))
+        }
+    })
+    .map(|answer| select_option(&answer, options).unwrap_or(answer))
+}
+
+fn select_option(answer: &str, options: &[String]) -> Option<String> {
+    if let Ok(n) = answer.parse::<usize>() {
+        if (1..=options.len()).contains(&n) {
+            return Some(options[n - 1].clone());
+        }
+    }
+    options.iter().find(|o| o.as_str() == answer).cloned()
+}
+
+fn read_line() -> Result<String, String> {
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| format!("Failed to read input: {}", e))?;
+    Ok(input)
+}
+
+/// Reads a line with terminal echo turned off via `stty`.
+#[cfg(unix)]
+fn read_line_masked() -> Result<String, String> {
+    use std::process::{Command, Stdio};
+
+    let stty = |arg: &str| {
+        Command::new("stty")
+            .arg(arg)
+            .stdin(Stdio::inherit())
+            .status()
+            .is_ok_and(|status| status.success())
+    };
+
+    let masked = stty("-echo");
+    let input = read_line();
+    if masked {
+        stty("echo");
+        println!();
+    }
+    input
+}
+
+#[cfg(not(unix))]
+fn read_line_masked() -> Result<String, String> {
+    read_line()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_option_by_number_or_value() {
+        let options = vec!["dev".to_string(), "prod".to_string()];
+        assert_eq!(select_option("2", &options), Some("prod".to_string()));
+        assert_eq!(select_option("dev", &options), Some("dev".to_string()));
+        assert_eq!(select_option("3", &options), None);
+        assert_eq!(select_option("staging", &options), None);
+    }
+}
@@ -32,6 +32,15 @@ pub struct JsonDependency {
     pub args: HashMap<String, String>,
 }
 
+/// JSON representation of one argument value of a preset.
+#[derive(Serialize, Deserialize)]
+pub struct JsonPresetValue {
+    /// The parameter name
+    pub param: String,
+    /// The value the preset gives it
+    pub value: JsonValue,
+}
+
 /// JSON representation of a Parameter.
 #[derive(Serialize, Deserialize)]
 pub struct JsonParameter {
@@ -54,6 +63,15 @@ pub struct JsonParameter {
     /// Optional fixed size for wildcard (`*[N]` / `*name[N]`).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub wildcard_count: Option<usize>,
+    /// Whether a missing value is prompted for.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub prompt: bool,
+    /// Whether the value is read without echo when prompted.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub secret: bool,
+    /// Environment variable the value falls back to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
 }
 
 /// JSON representation of a Directive.
@@ -63,6 +81,15 @@ pub enum JsonDirective {
     /// Description directive
     #[serde(rename = "desc")]
     Desc(String),
+    /// Command aliases directive
+    #[serde(rename = "alias")]
+    Alias(Vec<String>),
+    /// Help category directive
+    #[serde(rename = "category")]
+    Category(String),
+    /// Help order directive
+    #[serde(rename = "help_order")]
+    HelpOrder(i64),
     /// Working directory directive
     #[serde(rename = "cwd")]
     Cwd(String),
@@ -117,6 +144,15 @@ pub enum JsonDirective {
     /// Validation directive
     #[serde(rename = "validate")]
     Validate { target: String, rule: String },
+    /// Completion source directive
+    #[serde(rename = "complete")]
+    Complete { target: String, source: String },
+    /// Preset directive
+    #[serde(rename = "preset")]
+    Preset {
+        name: String,
+        values: Vec<JsonPresetValue>,
+    },
     /// Script directive
     #[serde(rename = "script")]
     Script {
@@ -127,6 +163,9 @@ pub enum JsonDirective {
     /// Privileged access directive
     #[serde(rename = "privileged")]
     Privileged(bool),
+    /// Private command marker
+    #[serde(rename = "private")]
+    Private(bool),
     /// Logs directive
     #[serde(rename = "logs")]
     Logs { path: String, format: String },
@@ -184,6 +223,9 @@ impl From<&Parameter> for JsonParameter {
             is_wildcard,
             wildcard_name,
             wildcard_count,
+            prompt: param.prompt,
+            secret: param.secret,
+            env: param.env.clone(),
         }
     }
 }
@@ -192,6 +234,9 @@ impl From<&Directive> for JsonDirective {
     fn from(directive: &Directive) -> Self {
         match directive {
             Directive::Desc(s) => JsonDirective::Desc(s.clone()),
+            Directive::Alias(aliases) => JsonDirective::Alias(aliases.clone()),
+            Directive::Category(category) => JsonDirective::Category(category.clone()),
+            Directive::HelpOrder(order) => JsonDirective::HelpOrder(*order),
             Directive::Cwd(s) => JsonDirective::Cwd(s.clone()),
             Directive::Env(name, value, hide) => JsonDirective::Env {
                 name: name.clone(),
@@ -239,12 +284,27 @@ impl From<&Directive> for JsonDirective {
                 target: target.clone(),
                 rule: rule.clone(),
             },
+            Directive::Complete(target, source) => JsonDirective::Complete {
+                target: target.clone(),
+                source: source.clone(),
+            },
+            Directive::Preset(name, values) => JsonDirective::Preset {
+                name: name.clone(),
+                values: values
+                    .iter()
+                    .map(|(param, value)| JsonPresetValue {
+                        param: param.clone(),
+                        value: value.into(),
+                    })
+                    .collect(),
+            },
             Directive::Script(s, os, hide) => JsonDirective::Script {
                 content: s.clone(),
                 os: os.clone(),
                 hide: *hide,
             },
             Directive::Privileged(value) => JsonDirective::Privileged(*value),
+            Directive::Private(value) => JsonDirective::Private(*value),
             Directive::Logs(path, format) => JsonDirective::Logs {
                 path: path.clone(),
                 format: format.clone(),
@@ -262,7 +322,12 @@ impl From<&Command> for JsonCommand {
             name: command.name.clone(),
             parameters: command.parameters.iter().map(|p| p.into()).collect(),
             directives: command.directives.iter().map(|d| d.into()).collect(),
-            children: command.children.iter().map(|c| c.into()).collect(),
+            children: command
+                .children
+                .iter()
+                .filter(|c| !c.is_private())
+                .map(|c| c.into())
+                .collect(),
             has_wildcard: command.has_wildcard,
             // Note: source_file is not included in JSON output as it's internal metadata
         }
@@ -271,6 +336,8 @@ impl From<&Command> for JsonCommand {
 
 /// Converts a list of commands to a pretty-printed JSON string.
 ///
+/// Private commands are left out.
+///
 /// # Arguments
 ///
 /// * `commands` - The list of commands to serialize
@@ -280,6 +347,10 @@ impl From<&Command> for JsonCommand {
 /// Returns `Ok(json_string)` with the JSON representation,
 /// or `Err(error)` if serialization fails.
 pub fn to_json(commands: &[Command]) -> Result<String, serde_json::Error> {
-    let json_commands: Vec<JsonCommand> = commands.iter().map(|c| c.into()).collect();
+    let json_commands: Vec<JsonCommand> = commands
+        .iter()
+        .filter(|c| !c.is_private())
+        .map(|c| c.into())
+        .collect();
     serde_json::to_string_pretty(&json_commands)
 }
@@ -0,0 +1,400 @@
+//! Lockfile and content cache for remote includes.
+//!
+//! Remote `import` sources (URLs and git references) are pinned in a
+//! `Nestfile.lock` next to the root configuration file. Each entry records
+//! the SHA-256 of the fetched content, which is verified every time the
+//! include is loaded. Fetched content is cached under `~/.cache/nest/remote`,
+//! keyed by its hash, so that pinned includes can be resolved without network
+//! access (`--offline`). Git repositories are cloned under `~/.cache/nest/git`.
+
+use super::git;
+use super::source::Source;
+use crate::constants::LOCK_FILE_NAME;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Current version of the lockfile format.
+const LOCK_VERSION: u32 = 1;
+
+/// How remote includes are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteMode {
+    /// Use the cache for pinned URLs, fetch and pin new URLs.
+    Default,
+    /// Never touch the network; only pinned, cached content is used.
+    Offline,
+    /// Re-fetch every URL and rewrite the lockfile (`nest lock update`).
+    Update,
+}
+
+/// A pinned remote include.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedRemote {
+    /// Hex-encoded SHA-256 of the content
+    pub sha256: String,
+    /// Commit the ref resolved to (git sources only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+/// On-disk representation of `Nestfile.lock`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Format version
+    pub version: u32,
+    /// Pinned remote includes, keyed by URL or git reference
+    #[serde(default)]
+    pub remote: BTreeMap<String, LockedRemote>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            version: LOCK_VERSION,
+            remote: BTreeMap::new(),
+        }
+    }
+}
+
+impl Lockfile {
+    /// Reads a lockfile from disk. A missing file yields an empty lockfile.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let lock: Lockfile = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid lockfile {}: {}", path.display(), e))?;
+        if lock.version > LOCK_VERSION {
+            return Err(format!(
+                "Lockfile {} has unsupported version {} (expected {})",
+                path.display(),
+                lock.version,
+                LOCK_VERSION
+            ));
+        }
+        Ok(lock)
+    }
+
+    /// Writes the lockfile to disk.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize lockfile: {}", e))?;
+        content.push('\n');
+        fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// Resolves remote includes through the lockfile and content cache.
+///
+/// A single store is shared by all includes of one configuration load, so
+/// that the lockfile is read once and written once at the end.
+pub struct RemoteStore {
+    lock_path: PathBuf,
+    cache_root: Option<PathBuf>,
+    mode: RemoteMode,
+    locked: Lockfile,
+    resolved: BTreeMap<String, LockedRemote>,
+}
+
+impl RemoteStore {
+    /// Creates a store for the given root configuration file.
+    ///
+    /// The lockfile is expected next to the configuration file.
+    pub fn for_config(config_path: &Path, mode: RemoteMode) -> Result<Self, String> {
+        let dir = config_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        Self::new(dir.join(LOCK_FILE_NAME), default_cache_root(), mode)
+    }
+
+    /// Creates a store with an explicit lockfile path and cache root.
+    ///
+    /// Without a cache root nothing is cached and git sources are unavailable.
+    pub fn new(
+        lock_path: PathBuf,
+        cache_root: Option<PathBuf>,
+        mode: RemoteMode,
+    ) -> Result<Self, String> {
+        let locked = Lockfile::load(&lock_path)?;
+        Ok(Self {
+            lock_path,
+            cache_root,
+            mode,
+            locked,
+            resolved: BTreeMap::new(),
+        })
+    }
+
+    /// Returns the resolution mode of this store.
+    pub fn mode(&self) -> RemoteMode {
+        self.mode
+    }
+
+    /// Returns the path of the lockfile.
+    pub fn lock_path(&self) -> &Path {
+        &self.lock_path
+    }
+
+    /// Returns the number of remote includes resolved so far.
+    pub fn resolved_count(&self) -> usize {
+        self.resolved.len()
+    }
+
+    /// Returns the content of a remote include, verified against the lockfile.
+    pub fn fetch(&mut self, source: &Source) -> Result<String, String> {
+        let key = source.to_string();
+        let pinned = match self.mode {
+            RemoteMode::Update => None,
+            _ => self.locked.remote.get(&key).cloned(),
+        };
+
+        let (content, commit) = match (&pinned, self.mode) {
+            (Some(entry), _) => match self.read_cache(&entry.sha256) {
+                Some(content) => (content, entry.commit.clone()),
+                None if self.mode == RemoteMode::Offline => {
+                    return Err(format!(
+                        "Remote include {} is not cached and --offline was given",
+                        key
+                    ));
+                }
+                None => self.load(source, entry.commit.as_deref())?,
+            },
+            (None, RemoteMode::Offline) => {
+                return Err(format!(
+                    "Remote include {} is not pinned in {}; cannot resolve it with --offline",
+                    key, LOCK_FILE_NAME
+                ));
+            }
+            (None, _) => self.load(source, None)?,
+        };
+
+        let sha256 = hash_content(&content);
+        if let Some(entry) = &pinned {
+            if entry.sha256 != sha256 {
+                return Err(format!(
+                    "Integrity check failed for {}: expected sha256 {}, got {}. Run 'nest lock update' if the change is expected.",
+                    key, entry.sha256, sha256
+                ));
+            }
+        }
+
+        self.write_cache(&sha256, &content);
+        self.resolved.insert(key, LockedRemote { sha256, commit });
+        Ok(content)
+    }
+
+    /// Writes the lockfile if it changed.
+    ///
+    /// In default mode newly pinned URLs are added and existing entries are kept.
+    /// In update mode the lockfile is rewritten from the URLs seen in this load.
+    /// Offline mode never writes.
+    ///
+    /// Returns `Ok(true)` if the lockfile was written.
+    pub fn save(&self) -> Result<bool, String> {
+        let updated = match self.mode {
+            RemoteMode::Offline => return Ok(false),
+            RemoteMode::Default => {
+                let mut lock = self.locked.clone();
+                lock.remote
+                    .extend(self.resolved.iter().map(|(k, v)| (k.clone(), v.clone())));
+                lock
+            }
+            RemoteMode::Update => Lockfile {
+                version: LOCK_VERSION,
+                remote: self.resolved.clone(),
+            },
+        };
+
+        if updated == self.locked && (self.lock_path.exists() || updated.remote.is_empty()) {
+            return Ok(false);
+        }
+
+        updated.save(&self.lock_path)?;
+        Ok(true)
+    }
+
+    /// Loads the content of a source from its origin.
+    ///
+    /// Git sources are read at `pinned_commit` when the lockfile has one,
+    /// so a moved tag or branch does not change what is loaded.
+    /// Returns the content and, for git sources, the commit it was read from.
+    fn load(
+        &self,
+        source: &Source,
+        pinned_commit: Option<&str>,
+    ) -> Result<(String, Option<String>), String> {
+        match source {
+            Source::Url(url) => Ok((download(url)?, None)),
+            Source::Git {
+                repo,
+                reference,
+                path,
+            } => {
+                let cache_dir = self
+                    .cache_root
+                    .as_ref()
+                    .map(|root| root.join("git"))
+                    .ok_or_else(|| {
+                        "HOME or USERPROFILE environment variable not set".to_string()
+                    })?;
+                let (content, commit) = git::read_file(
+                    repo,
+                    pinned_commit.unwrap_or(reference),
+                    path,
+                    &cache_dir,
+                    self.mode == RemoteMode::Update,
+                )?;
+                Ok((content, Some(commit)))
+            }
+            Source::Local(path) => std::fs::read_to_string(path)
+                .map(|content| (content, None))
+                .map_err(|e| format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    fn read_cache(&self, sha256: &str) -> Option<String> {
+        let path = self.cache_root.as_ref()?.join("remote").join(sha256);
+        let content = fs::read_to_string(path).ok()?;
+        // A corrupted cache entry is treated as a miss
+        (hash_content(&content) == sha256).then_some(content)
+    }
+
+    fn write_cache(&self, sha256: &str, content: &str) {
+        // The cache is an optimization; failing to write it is not fatal
+        if let Some(root) = &self.cache_root {
+            let dir = root.join("remote");
+            let path = dir.join(sha256);
+            if !path.exists() && fs::create_dir_all(&dir).is_ok() {
+                let _ = fs::write(path, content);
+            }
+        }
+    }
+}
+
+/// Returns the SHA-256 of the content as a lowercase hex string.
+pub fn hash_content(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Returns `~/.cache/nest`, if the home directory is known.
+fn default_cache_root() -> Option<PathBuf> {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()?;
+
+    let mut cache_dir = PathBuf::from(home);
+    cache_dir.push(".cache");
+    cache_dir.push("nest");
+    Some(cache_dir)
+}
+
+/// Fetches the content of a remote URL.
+fn download(url: &str) -> Result<String, String> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|e| format!("Failed to fetch remote include {}: {}", url, e))?;
+
+    if response.status() != 200 {
+        return Err(format!(
+            "Failed to fetch remote include {}: status {}",
+            url,
+            response.status()
+        ));
+    }
+
+    let mut body = String::new();
+    response
+        .into_body()
+        .as_reader()
+        .read_to_string(&mut body)
+        .map_err(|e| format!("Failed to read remote content: {}", e))?;
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://example.com/tasks.nest";
+
+    fn url() -> Source {
+        Source::Url(URL.to_string())
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nest-lock-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn pinned_store(dir: &Path, content: &str, cached: &str, mode: RemoteMode) -> RemoteStore {
+        let cache = dir.join("cache");
+        fs::create_dir_all(cache.join("remote")).unwrap();
+        let sha256 = hash_content(content);
+        fs::write(cache.join("remote").join(&sha256), cached).unwrap();
+
+        let mut lock = Lockfile::default();
+        lock.remote.insert(
+            URL.to_string(),
+            LockedRemote {
+                sha256,
+                commit: None,
+            },
+        );
+        let lock_path = dir.join(LOCK_FILE_NAME);
+        lock.save(&lock_path).unwrap();
+
+        RemoteStore::new(lock_path, Some(cache), mode).unwrap()
+    }
+
+    #[test]
+    fn test_offline_uses_pinned_cache() {
+        let dir = temp_dir("offline");
+        let mut store = pinned_store(&dir, "build:\n", "build:\n", RemoteMode::Offline);
+
+        assert_eq!(store.fetch(&url()).unwrap(), "build:\n");
+        assert!(store
+            .fetch(&Source::Url("https://example.com/other.nest".to_string()))
+            .is_err());
+        assert!(!store.save().unwrap());
+    }
+
+    #[test]
+    fn test_offline_rejects_tampered_cache() {
+        let dir = temp_dir("tampered");
+        let mut store = pinned_store(&dir, "build:\n", "evil:\n", RemoteMode::Offline);
+
+        assert!(store.fetch(&url()).is_err());
+    }
+
+    #[test]
+    fn test_lockfile_roundtrip() {
+        let dir = temp_dir("roundtrip");
+        let path = dir.join(LOCK_FILE_NAME);
+        let mut lock = Lockfile::default();
+        lock.remote.insert(
+            URL.to_string(),
+            LockedRemote {
+                sha256: hash_content("x"),
+                commit: Some("0123abcd".to_string()),
+            },
+        );
+        lock.save(&path).unwrap();
+
+        assert_eq!(Lockfile::load(&path).unwrap(), lock);
+        assert_eq!(
+            Lockfile::load(&dir.join("missing.lock")).unwrap(),
+            Lockfile::default()
+        );
+    }
+}
@@ -3,6 +3,7 @@
 //! This module handles writing command execution logs to files in various formats (txt, json).
 
 use super::template::{TemplateContext, TemplateProcessor};
+use crate::error::NestError;
 use chrono::Utc;
 use serde_json::json;
 use std::collections::HashMap;
@@ -15,8 +16,8 @@ pub fn write_log_entry(
     log_format: &str,
     command_path: Option<&[String]>,
     args: &HashMap<String, String>,
-    result: &Result<(), String>,
-) -> Result<(), String> {
+    result: &Result<(), NestError>,
+) -> Result<(), NestError> {
     // Process template in log path
     // Log path doesn't need parent args (it's just a path)
     let empty_parent_args: HashMap<String, String> = HashMap::new();
@@ -31,14 +32,14 @@ pub fn write_log_entry(
     // Create parent directories if needed
     if let Some(parent) = std::path::Path::new(&processed_path).parent() {
         std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create log directory: {}", e))?;
+            .map_err(|e| NestError::Io(format!("Failed to create log directory: {}", e)))?;
     }
 
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
         .open(&processed_path)
-        .map_err(|e| format!("Failed to open log file: {}", e))?;
+        .map_err(|e| NestError::Io(format!("Failed to open log file: {}", e)))?;
 
     let command_name = command_path
         .map(|p| p.join(" "))
@@ -58,33 +59,37 @@ pub fn write_log_entry(
                 "error": error_msg,
             });
             writeln!(file, "{}", serde_json::to_string(&log_entry).unwrap())
-                .map_err(|e| format!("Failed to write log: {}", e))?;
+                .map_err(write_failed)?;
         }
         "txt" => {
-            writeln!(file, "[{}] Command: {}", timestamp, command_name)
-                .map_err(|e| format!("Failed to write log: {}", e))?;
+            writeln!(file, "[{}] Command: {}", timestamp, command_name).map_err(write_failed)?;
             if !args.is_empty() {
                 let args_str: Vec<String> =
                     args.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
-                writeln!(file, "  Args: {}", args_str.join(", "))
-                    .map_err(|e| format!("Failed to write log: {}", e))?;
+                writeln!(file, "  Args: {}", args_str.join(", ")).map_err(write_failed)?;
             }
             writeln!(
                 file,
                 "  Status: {}",
                 if success { "SUCCESS" } else { "FAILED" }
             )
-            .map_err(|e| format!("Failed to write log: {}", e))?;
+            .map_err(write_failed)?;
             if let Some(err) = error_msg {
-                writeln!(file, "  Error: {}", err)
-                    .map_err(|e| format!("Failed to write log: {}", e))?;
+                writeln!(file, "  Error: {}", err).map_err(write_failed)?;
             }
-            writeln!(file).map_err(|e| format!("Failed to write log: {}", e))?;
+            writeln!(file).map_err(write_failed)?;
         }
         _ => {
-            return Err(format!("Unknown log format: {}", log_format));
+            return Err(NestError::Other(format!(
+                "Unknown log format: {}",
+                log_format
+            )));
         }
     }
 
     Ok(())
 }
+
+fn write_failed(e: std::io::Error) -> NestError {
+    NestError::Io(format!("Failed to write log: {}", e))
+}
@@ -1,9 +1,43 @@
-use crate::nestparse::ast::{Command, Directive};
+use crate::nestparse::ast::{find_command, Command, Directive, MergeMode};
 use indexmap::IndexMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// An implicit redefinition of a command (without `override` or `append`).
+///
+/// The later definition still wins, but `--check` reports it since it is
+/// usually an accident (e.g. two imported files defining the same command).
+#[derive(Debug, Clone)]
+pub struct MergeConflict {
+    /// Path of the redefined command
+    pub command_path: Vec<String>,
+    /// Source file of the earlier definition
+    pub first: Option<PathBuf>,
+    /// Source file of the later definition
+    pub second: Option<PathBuf>,
+}
 
 /// Merges a list of commands, applying "Last Wins" strategy for duplicates.
-pub fn merge_commands(mut commands: Vec<Command>) -> Vec<Command> {
+pub fn merge_commands(commands: Vec<Command>) -> Vec<Command> {
+    merge_commands_with_conflicts(commands).0
+}
+
+/// Merges a list of commands and reports implicit redefinitions.
+///
+/// Duplicates are combined according to their [`MergeMode`]:
+/// - `Implicit` - "Last Wins" (scalars replaced, env merged), reported as a conflict
+/// - `Override` - the later definition replaces the earlier one entirely
+/// - `Append` - `depends`, `env` and `script` are added to the earlier definition
+pub fn merge_commands_with_conflicts(commands: Vec<Command>) -> (Vec<Command>, Vec<MergeConflict>) {
+    let mut conflicts = Vec::new();
+    let merged = merge_level(commands, &[], &mut conflicts);
+    (merged, conflicts)
+}
+
+fn merge_level(
+    mut commands: Vec<Command>,
+    parent_path: &[String],
+    conflicts: &mut Vec<MergeConflict>,
+) -> Vec<Command> {
     // Phase 0: Resolve relative paths in all commands based on their source file
     resolve_relative_paths(&mut commands);
 
@@ -11,17 +45,41 @@ pub fn merge_commands(mut commands: Vec<Command>) -> Vec<Command> {
     let mut merged_map: IndexMap<String, Command> = IndexMap::new();
 
     for cmd in commands {
-        if let Some(existing) = merged_map.get_mut(&cmd.name) {
-            merge_single_command(existing, cmd);
-        } else {
+        let Some(existing) = merged_map.get_mut(&cmd.name) else {
             merged_map.insert(cmd.name.clone(), cmd);
+            continue;
+        };
+
+        let mut path = parent_path.to_vec();
+        path.push(cmd.name.clone());
+
+        match cmd.merge_mode {
+            MergeMode::Override => *existing = cmd,
+            MergeMode::Append => merge_single_command(existing, cmd, true, &path, conflicts),
+            MergeMode::Implicit => {
+                // Groups only combine their children; redefining a leaf replaces it
+                if existing.children.is_empty() && cmd.children.is_empty() {
+                    conflicts.push(MergeConflict {
+                        command_path: path.clone(),
+                        first: existing.source_file.clone(),
+                        second: cmd.source_file.clone(),
+                    });
+                }
+                merge_single_command(existing, cmd, false, &path, conflicts);
+            }
         }
     }
 
     merged_map.into_values().collect()
 }
 
-fn merge_single_command(base: &mut Command, override_cmd: Command) {
+fn merge_single_command(
+    base: &mut Command,
+    override_cmd: Command,
+    append: bool,
+    path: &[String],
+    conflicts: &mut Vec<MergeConflict>,
+) {
     // 1. Merge Directives
     // Strategy: Split directives into unique kinds.
     // Some directives are "scalar" (script, desc) -> Replace
@@ -37,6 +95,33 @@ fn merge_single_command(base: &mut Command, override_cmd: Command) {
     // Let's iterate over override directives and apply them to base.
     for dir in override_cmd.directives {
         match dir {
+            // Append: extend the script for the same OS
+            Directive::Script(content, os, hide) if append => {
+                let existing = base.directives.iter_mut().find_map(|d| match d {
+                    Directive::Script(existing, existing_os, _) if *existing_os == os => {
+                        Some(existing)
+                    }
+                    _ => None,
+                });
+                match existing {
+                    Some(existing) => {
+                        existing.push('\n');
+                        existing.push_str(&content);
+                    }
+                    None => base.directives.push(Directive::Script(content, os, hide)),
+                }
+            }
+            // Append: extend the dependency list
+            Directive::Depends(deps, parallel) if append => {
+                let existing = base.directives.iter_mut().find_map(|d| match d {
+                    Directive::Depends(existing, _) => Some(existing),
+                    _ => None,
+                });
+                match existing {
+                    Some(existing) => existing.extend(deps),
+                    None => base.directives.push(Directive::Depends(deps, parallel)),
+                }
+            }
             // Scalars: Replace existing
             Directive::Script(..) => {
                 // Remove all script directives from base
@@ -86,11 +171,13 @@ fn merge_single_command(base: &mut Command, override_cmd: Command) {
     }
 
     // 2. Merge Children (Recursive)
-    let merged_children = merge_commands(
+    let merged_children = merge_level(
         base.children
             .drain(..)
             .chain(override_cmd.children)
             .collect(),
+        path,
+        conflicts,
     );
     base.children = merged_children;
 
@@ -99,6 +186,100 @@ fn merge_single_command(base: &mut Command, override_cmd: Command) {
         base.parameters.retain(|p| p.name != param.name);
         base.parameters.push(param);
     }
+
+    // 4. Merge Local Variables and Constants - Replace by name
+    // (two imports into the same group share its namespace)
+    for var in override_cmd.local_variables {
+        base.local_variables.retain(|v| v.name != var.name);
+        base.local_variables.push(var);
+    }
+    for constant in override_cmd.local_constants {
+        base.local_constants.retain(|c| c.name != constant.name);
+        base.local_constants.push(constant);
+    }
+}
+
+/// Applies `extends` inheritance to every command in the tree.
+///
+/// A command that extends another starts from a copy of the base command
+/// (parameters, directives, variables and children) and merges its own
+/// definition on top. The base is looked up among the siblings first, then
+/// at the top level; `group:name` refers to an absolute path. Unknown bases
+/// and cycles are left untouched and reported by the validator.
+pub fn resolve_extends(commands: &mut [Command]) {
+    let snapshot = commands.to_vec();
+    resolve_extends_recursive(commands, &[], &snapshot);
+}
+
+fn resolve_extends_recursive(commands: &mut [Command], parent_path: &[String], root: &[Command]) {
+    for cmd in commands.iter_mut() {
+        let mut path = parent_path.to_vec();
+        path.push(cmd.name.clone());
+
+        if cmd.extends.is_some() {
+            if let Some(resolved) = resolved_command(&path, root, &mut Vec::new()) {
+                *cmd = resolved;
+            }
+        }
+
+        resolve_extends_recursive(&mut cmd.children, &path, root);
+    }
+}
+
+/// Returns the command at `path` with its `extends` chain applied.
+fn resolved_command(
+    path: &[String],
+    root: &[Command],
+    visiting: &mut Vec<Vec<String>>,
+) -> Option<Command> {
+    if visiting.iter().any(|p| p == path) {
+        // Cycle - reported by the validator
+        return None;
+    }
+    let cmd = find_command(root, path)?;
+    let Some(reference) = &cmd.extends else {
+        return Some(cmd.clone());
+    };
+
+    visiting.push(path.to_vec());
+    let base_path = extends_target(reference, &path[..path.len() - 1], root)?;
+    let mut resolved = resolved_command(&base_path, root, visiting)?;
+    visiting.pop();
+
+    resolved.name = cmd.name.clone();
+    merge_single_command(
+        &mut resolved,
+        cmd.clone(),
+        cmd.merge_mode == MergeMode::Append,
+        path,
+        &mut Vec::new(),
+    );
+    resolved.source_file = cmd.source_file.clone();
+    resolved.merge_mode = cmd.merge_mode;
+    resolved.extends = cmd.extends.clone();
+    Some(resolved)
+}
+
+/// Resolves the command path an `extends` reference points to.
+///
+/// Returns `None` if no such command exists.
+pub fn extends_target(
+    reference: &str,
+    parent_path: &[String],
+    root: &[Command],
+) -> Option<Vec<String>> {
+    if reference.contains(':') {
+        let path: Vec<String> = reference.split(':').map(|s| s.trim().to_string()).collect();
+        return find_command(root, &path).map(|_| path);
+    }
+
+    let mut sibling = parent_path.to_vec();
+    sibling.push(reference.to_string());
+    if find_command(root, &sibling).is_some() {
+        return Some(sibling);
+    }
+    let top_level = vec![reference.to_string()];
+    find_command(root, &top_level).map(|_| top_level)
 }
 
 /// Resolves relative paths in directives to absolute paths based on source file.
@@ -134,3 +315,72 @@ fn resolve_path_string(path: &mut String, base_dir: &Path) {
         *path = abs_path.to_string_lossy().to_string();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::nestparse::ast::Directive;
+    use crate::nestparse::parser::Parser;
+
+    fn script(directives: &[Directive]) -> Option<&str> {
+        directives.iter().find_map(|d| match d {
+            Directive::Script(content, _, _) => Some(content.as_str()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_merge_modes_and_extends() {
+        let content = "\
+base(target: str = \"x86\"):
+    env: MODE=release
+    script: echo base
+
+build extends base:
+    desc: Build
+
+deploy:
+    depends: base
+    script: echo one
+
+append deploy:
+    depends: build
+    script: echo two
+
+test:
+    script: echo t1
+
+override test:
+    script: echo t2
+
+lint:
+    script: echo l1
+
+lint:
+    script: echo l2
+";
+        let result = Parser::new(content).parse().unwrap();
+        let find = |name: &str| result.commands.iter().find(|c| c.name == name).unwrap();
+
+        let build = find("build");
+        assert_eq!(script(&build.directives), Some("echo base"));
+        assert_eq!(build.parameters[0].name, "target");
+        assert!(build
+            .directives
+            .iter()
+            .any(|d| matches!(d, Directive::Env(k, _, _) if k == "MODE")));
+
+        let deploy = find("deploy");
+        assert_eq!(script(&deploy.directives), Some("echo one\necho two"));
+        let deps = deploy.directives.iter().find_map(|d| match d {
+            Directive::Depends(deps, _) => Some(deps.len()),
+            _ => None,
+        });
+        assert_eq!(deps, Some(2));
+
+        assert_eq!(script(&find("test").directives), Some("echo t2"));
+
+        // Only the implicit redefinition is reported
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].command_path, vec!["lint".to_string()]);
+    }
+}
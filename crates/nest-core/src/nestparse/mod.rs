@@ -12,6 +12,7 @@ pub mod cli;
 pub mod codegen;
 pub mod command_handler;
 pub mod completion;
+pub mod deps;
 pub mod directives;
 pub mod runtime_validator;
 
@@ -19,19 +20,24 @@ pub mod standard_commands;
 
 pub mod display;
 pub mod env;
+pub mod events;
 pub mod executor;
 pub mod file;
+pub mod git;
 pub mod handlers;
 pub mod help;
 pub mod include;
 pub mod input;
 pub mod json;
+pub mod lock;
 pub mod logging;
 pub mod merge;
+pub mod observer;
 pub mod output;
 pub mod parser;
 pub mod path;
 pub mod runtime;
+pub mod source;
 pub mod template;
 pub mod type_validator;
 pub mod validator;
@@ -0,0 +1,85 @@
+//! Lifecycle events of command execution.
+//!
+//! A [`RuntimeObserver`] registered with
+//! [`Runtime::with_observer`](super::runtime::Runtime::with_observer) is told
+//! when each command of a run starts and finishes, which dependencies it
+//! schedules, when each of its scripts runs, which processes it spawns and
+//! what they print. Front ends, log writers and exporters can all follow a
+//! run through the same stream.
+
+use super::executor::OutputStream;
+use crate::error::NestError;
+
+/// A script of a command, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Before,
+    Script,
+    /// Runs only when the script succeeded
+    After,
+    /// Runs only when the script failed
+    Fallback,
+    /// Runs whatever happened
+    Finally,
+}
+
+impl Phase {
+    /// The directive the phase's script comes from.
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Before => "before",
+            Phase::Script => "script",
+            Phase::After => "after",
+            Phase::Fallback => "fallback",
+            Phase::Finally => "finally",
+        }
+    }
+}
+
+/// Receives the events of every command a run executes.
+///
+/// Command paths are canonical. Every method does nothing by default, so an
+/// observer implements only what it needs. Dependencies declared `parallel`
+/// run on their own threads, so events of different commands may interleave.
+pub trait RuntimeObserver: Send + Sync {
+    /// The command starts; its dependencies run first.
+    fn command_started(&self, _command_path: &[String]) {}
+
+    /// The command, its dependencies and its scripts are done.
+    fn command_finished(&self, _command_path: &[String], _result: &Result<(), NestError>) {}
+
+    /// `required_by` is about to run `dependency`.
+    fn dependency_scheduled(&self, _dependency: &[String], _required_by: &[String]) {}
+
+    /// `dependency` of `required_by` won't run, since a dependency listed
+    /// before it failed.
+    fn dependency_skipped(&self, _dependency: &[String], _required_by: &[String]) {}
+
+    fn phase_started(&self, _command_path: &[String], _phase: Phase) {}
+
+    fn phase_finished(
+        &self,
+        _command_path: &[String],
+        _phase: Phase,
+        _result: &Result<(), NestError>,
+    ) {
+    }
+
+    /// A script of the command was started as process `pid`.
+    fn process_spawned(&self, _command_path: &[String], _pid: u32) {}
+
+    /// A line the command's scripts printed.
+    ///
+    /// Only sent when an observer [receives output](Self::receives_output);
+    /// otherwise scripts write straight to the terminal.
+    fn output_line(&self, _command_path: &[String], _stream: OutputStream, _line: &str) {}
+
+    /// Whether scripts should stream their output to
+    /// [`output_line`](Self::output_line) instead of the terminal.
+    ///
+    /// Scripts then run with stdin closed and in their own process group, so
+    /// the PID reported for a script identifies the group to cancel.
+    fn receives_output(&self) -> bool {
+        false
+    }
+}
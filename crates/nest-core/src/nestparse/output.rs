@@ -5,8 +5,129 @@
 //! - Help messages
 //! - System/info messages
 //! - Success messages
+//!
+//! Messages are built with the ANSI codes of [`colors`]; whether they reach
+//! the terminal is decided once, when printing, by [`OutputFormatter::adapt`]
+//! (`--color`, `NO_COLOR`, `CLICOLOR_FORCE` and whether the stream is a terminal).
 
+use super::executor::OutputStream;
+use std::borrow::Cow;
 use std::fmt::Write;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// When output is colored (`--color`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color terminals, unless `NO_COLOR` is set; `CLICOLOR_FORCE` colors anything
+    Auto,
+    Always,
+    Never,
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            other => Err(format!(
+                "Invalid color choice '{}'. Expected auto, always or never",
+                other
+            )),
+        }
+    }
+}
+
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(0);
+
+/// Sets how the process colors its output, from `--color`.
+pub fn set_color_choice(choice: ColorChoice) {
+    let value = match choice {
+        ColorChoice::Auto => 0,
+        ColorChoice::Always => 1,
+        ColorChoice::Never => 2,
+    };
+    COLOR_CHOICE.store(value, Ordering::Relaxed);
+}
+
+/// Returns the color choice set by [`set_color_choice`] (`auto` by default).
+pub fn color_choice() -> ColorChoice {
+    match COLOR_CHOICE.load(Ordering::Relaxed) {
+        1 => ColorChoice::Always,
+        2 => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// Whether `stream` is attached to a terminal.
+pub fn is_terminal(stream: OutputStream) -> bool {
+    match stream {
+        OutputStream::Stdout => std::io::stdout().is_terminal(),
+        OutputStream::Stderr => std::io::stderr().is_terminal(),
+    }
+}
+
+/// Whether text written to `stream` may contain ANSI colors.
+pub fn colors_enabled(stream: OutputStream) -> bool {
+    let env_set = |name: &str| std::env::var_os(name).is_some_and(|v| !v.is_empty());
+    match color_choice() {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto if env_set("NO_COLOR") => false,
+        ColorChoice::Auto if env_set("CLICOLOR_FORCE") => {
+            std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| v != "0")
+        }
+        ColorChoice::Auto => is_terminal(stream),
+    }
+}
+
+/// Removes ANSI escape sequences from `text`: CSI sequences such as colors
+/// and OSC sequences such as window titles and hyperlinks.
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters up to a final byte in '@'..='~'
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: terminated by BEL or ESC '\'
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Replaces box-drawing characters with `+`, `-` and `|`.
+fn ascii_boxes(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '╔' | '╗' | '╚' | '╝' | '┌' | '┐' | '└' | '┘' => '+',
+            '═' | '─' => '-',
+            '║' | '│' => '|',
+            other => other,
+        })
+        .collect()
+}
 
 /// ANSI color codes
 #[allow(dead_code)]
@@ -33,6 +154,29 @@ pub struct OutputFormatter;
 
 #[allow(dead_code)]
 impl OutputFormatter {
+    /// Fits `text` to `stream`: without colors when they are off for it, and
+    /// with ASCII boxes when it isn't a terminal.
+    pub fn adapt(text: &str, stream: OutputStream) -> Cow<'_, str> {
+        let mut text = Cow::Borrowed(text);
+        if !colors_enabled(stream) {
+            text = Cow::Owned(strip_ansi(&text));
+        }
+        if !is_terminal(stream) {
+            text = Cow::Owned(ascii_boxes(&text));
+        }
+        text
+    }
+
+    /// Prints `text` to stdout, fitted to it.
+    pub fn print(text: &str) {
+        print!("{}", Self::adapt(text, OutputStream::Stdout));
+    }
+
+    /// Prints `text` to stderr, fitted to it.
+    pub fn eprint(text: &str) {
+        eprint!("{}", Self::adapt(text, OutputStream::Stderr));
+    }
+
     /// Prints an error message with consistent formatting
     pub fn error(message: &str) {
         Self::error_box("Error", message);
@@ -45,21 +189,41 @@ impl OutputFormatter {
 
     /// Prints a success message
     pub fn success(message: &str) {
-        eprintln!("{}✓{} {}", colors::BRIGHT_GREEN, colors::RESET, message);
+        Self::eprint(&format!(
+            "{}✓{} {}\n",
+            colors::BRIGHT_GREEN,
+            colors::RESET,
+            message
+        ));
     }
 
     /// Prints an info message
     pub fn info(message: &str) {
-        eprintln!("{}ℹ{} {}", colors::BRIGHT_CYAN, colors::RESET, message);
+        Self::eprint(&format!(
+            "{}ℹ{} {}\n",
+            colors::BRIGHT_CYAN,
+            colors::RESET,
+            message
+        ));
     }
 
     /// Prints a warning message
     pub fn warning(message: &str) {
-        eprintln!("{}⚠{} {}", colors::BRIGHT_YELLOW, colors::RESET, message);
+        Self::eprint(&format!(
+            "{}⚠{} {}\n",
+            colors::BRIGHT_YELLOW,
+            colors::RESET,
+            message
+        ));
     }
 
     /// Prints a formatted error box (like execution errors)
     pub fn error_box(title: &str, content: &str) {
+        Self::eprint(&Self::format_error_box(title, content));
+    }
+
+    /// Formats an error box without printing it
+    pub fn format_error_box(title: &str, content: &str) -> String {
         let mut output = String::new();
         writeln!(
             output,
@@ -86,7 +250,7 @@ impl OutputFormatter {
         )
         .expect("Failed to format error box footer");
         writeln!(output, "\n{}", content).expect("Failed to format error box content");
-        eprint!("{}", output);
+        output
     }
 
     /// Formats a help section header
@@ -135,22 +299,64 @@ impl OutputFormatter {
         let lines: Vec<&str> = content.lines().collect();
         let max_width = lines.iter().map(|l| l.len()).max().unwrap_or(0).min(60);
 
-        eprintln!("{}┌{}┐{}", color, "─".repeat(max_width), colors::RESET);
+        let mut output = String::new();
+        writeln!(
+            output,
+            "{}┌{}┐{}",
+            color,
+            "─".repeat(max_width),
+            colors::RESET
+        )
+        .expect("Failed to format box top");
         for line in &lines {
-            eprintln!(
+            writeln!(
+                output,
                 "{}│{} {}{}│{}",
                 color,
                 colors::RESET,
                 line,
                 " ".repeat(max_width.saturating_sub(line.len())),
                 color
-            );
+            )
+            .expect("Failed to format box line");
         }
-        eprintln!("{}└{}┘{}", color, "─".repeat(max_width), colors::RESET);
+        writeln!(
+            output,
+            "{}└{}┘{}",
+            color,
+            "─".repeat(max_width),
+            colors::RESET
+        )
+        .expect("Failed to format box bottom");
+        Self::eprint(&output);
     }
 
     /// Prints a section divider
     pub fn divider() {
-        eprintln!("{}{}{}", colors::GRAY, "─".repeat(60), colors::RESET);
+        Self::eprint(&format!(
+            "{}{}{}\n",
+            colors::GRAY,
+            "─".repeat(60),
+            colors::RESET
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi_and_ascii_boxes() {
+        let boxed = OutputFormatter::format_error_box("Error", "bad");
+        let plain = ascii_boxes(&strip_ansi(&boxed));
+        assert!(!plain.contains('\x1b'));
+        assert!(plain.contains("+-----"));
+        assert!(plain.contains("|  Error"));
+        assert_eq!(strip_ansi("\x1b[1;31mred\x1b[0m text"), "red text");
+        assert_eq!(strip_ansi("\x1b]0;title\x07done"), "done");
+        assert_eq!(strip_ansi("\x1b]8;;https://x\x1b\\link"), "link");
+        assert_eq!("never".parse(), Ok(ColorChoice::Never));
+        assert!("sometimes".parse::<ColorChoice>().is_err());
     }
 }
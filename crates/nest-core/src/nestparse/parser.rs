@@ -3,7 +3,9 @@
 //! This module parses the Nestfile syntax into an Abstract Syntax Tree (AST).
 //! It handles nested commands, parameters, directives, and multiline constructs.
 
-use super::ast::{Constant, Dependency, Directive, Function, Parameter, Value, Variable};
+use super::ast::{
+    Constant, Dependency, Directive, Function, MergeMode, Parameter, Value, Variable,
+};
 use crate::constants::{BOOL_FALSE, BOOL_TRUE, INDENT_SIZE};
 use std::collections::HashMap;
 
@@ -18,6 +20,10 @@ pub struct Parser {
     current_index: usize,
     /// Current source file path (for tracking where commands come from)
     current_source_file: Option<std::path::PathBuf>,
+    /// Names of the commands enclosing the current line (outermost first)
+    command_path: Vec<String>,
+    /// Functions defined inside command bodies, named after their namespace
+    scoped_functions: Vec<Function>,
 }
 
 /// Errors that can occur during parsing.
@@ -44,6 +50,8 @@ pub struct ParseResult {
     pub constants: Vec<Constant>,
     /// List of parsed functions (reusable scripts)
     pub functions: Vec<Function>,
+    /// Commands redefined without `override` or `append`
+    pub conflicts: Vec<super::merge::MergeConflict>,
 }
 
 impl Parser {
@@ -62,6 +70,8 @@ impl Parser {
             lines,
             current_index: 0,
             current_source_file: None,
+            command_path: Vec::new(),
+            scoped_functions: Vec::new(),
         }
     }
 
@@ -174,14 +184,20 @@ impl Parser {
             }
         }
 
-        // Merge commands (Last Wins strategy for duplicate names)
-        let merged_commands = crate::nestparse::merge::merge_commands(commands);
+        // Functions defined inside groups (e.g. by `import ... into group`)
+        functions.append(&mut self.scoped_functions);
+
+        // Merge commands (Last Wins strategy for duplicate names), then apply `extends`
+        let (mut merged_commands, conflicts) =
+            crate::nestparse::merge::merge_commands_with_conflicts(commands);
+        crate::nestparse::merge::resolve_extends(&mut merged_commands);
 
         Ok(ParseResult {
             commands: merged_commands,
             variables,
             constants,
             functions,
+            conflicts,
         })
     }
 
@@ -197,10 +213,14 @@ impl Parser {
             return Err(ParseError::InvalidIndent(self.current_line_number()));
         }
 
+        // Parse `override`/`append` prefixes and the `extends base` suffix
+        let (merge_mode, extends) = self.parse_merge_header();
+
         // Parse function signature: name(params): (may be multiline)
         let (name, parameters) = self.parse_function_signature_multiline(indent)?;
 
         // current_index already incremented in parse_function_signature_multiline
+        self.command_path.push(name.clone());
 
         let mut directives = Vec::new();
         let mut children = Vec::new();
@@ -273,10 +293,24 @@ impl Parser {
                 let env_directive = self.parse_env_directive_keyword()?;
                 directives.push(env_directive);
                 continue;
+            } else if next_trimmed.starts_with("preset ") {
+                let preset = self.parse_preset()?;
+                directives.push(preset);
+                continue;
             } else if next_trimmed == "privileged" {
                 directives.push(Directive::Privileged(true));
                 self.current_index += 1;
                 continue;
+            } else if next_trimmed == "private" {
+                directives.push(Directive::Private(true));
+                self.current_index += 1;
+                continue;
+            } else if next_trimmed.starts_with("function ") {
+                // Functions inside a group live in its namespace: `group.name`
+                let mut func = self.parse_function()?;
+                func.name = format!("{}.{}", self.command_path.join("."), func.name);
+                self.scoped_functions.push(func);
+                continue;
             }
 
             // Check if it's a directive (property: value or property.mod: value)
@@ -303,6 +337,8 @@ impl Parser {
             }
         }
 
+        self.command_path.pop();
+
         let has_wildcard = parameters
             .iter()
             .any(|p| matches!(p.kind, super::ast::ParamKind::Wildcard { .. }));
@@ -316,9 +352,57 @@ impl Parser {
             local_variables,
             local_constants,
             source_file: self.current_source_file.clone(),
+            merge_mode,
+            extends,
         })
     }
 
+    /// Strips the merge keywords from the current command header.
+    ///
+    /// Supported forms:
+    /// - `override name(params):` - replaces an earlier definition
+    /// - `append name(params):` - adds to an earlier definition
+    /// - `name(params) extends base:` - inherits from another command
+    ///
+    /// The header line is rewritten without the keywords, so the signature
+    /// parser only sees `name(params):`.
+    fn parse_merge_header(&mut self) -> (MergeMode, Option<String>) {
+        let line = &self.lines[self.current_index];
+        let indent_len = line.len() - line.trim_start().len();
+        let mut header = line.trim().to_string();
+
+        let merge_mode = if let Some(rest) = header.strip_prefix("override ") {
+            header = rest.trim_start().to_string();
+            MergeMode::Override
+        } else if let Some(rest) = header.strip_prefix("append ") {
+            header = rest.trim_start().to_string();
+            MergeMode::Append
+        } else {
+            MergeMode::Implicit
+        };
+
+        // `extends` comes after the parameter list, if any
+        let search_from = header.rfind(')').unwrap_or(0);
+        let extends = match header[search_from..].find(" extends ") {
+            Some(pos) => {
+                let pos = search_from + pos;
+                let base = header[pos + " extends ".len()..]
+                    .trim()
+                    .trim_end_matches(':')
+                    .trim()
+                    .to_string();
+                header = format!("{}:", header[..pos].trim_end());
+                Some(base)
+            }
+            None => None,
+        };
+
+        if merge_mode != MergeMode::Implicit || extends.is_some() {
+            self.lines[self.current_index] = format!("{}{}", &line[..indent_len], header);
+        }
+        (merge_mode, extends)
+    }
+
     fn parse_function_signature_multiline(
         &mut self,
         base_indent: u8,
@@ -497,7 +581,7 @@ impl Parser {
         param_str: &str,
         line_number: usize,
     ) -> Result<Parameter, ParseError> {
-        // Format: [!]name|alias: type = default
+        // Format: [!]name|alias: type = default @attribute ...
         // ! prefix means named argument (uses --name)
         let parts: Vec<&str> = param_str.split(':').collect();
 
@@ -513,7 +597,7 @@ impl Parser {
 
         let name_part = parts[0].trim();
         let type_default_str: String = parts[1..].join(":");
-        let type_default = type_default_str.trim();
+        let (type_default, attributes) = Self::split_parameter_attributes(type_default_str.trim());
 
         // Check if it's a named argument (starts with !)
         let (is_named, name_part_clean) = if let Some(clean) = name_part.strip_prefix('!') {
@@ -532,8 +616,18 @@ impl Parser {
             (name_part_clean.to_string(), None)
         };
 
-        // Parse type and default
-        let (param_type, default) = if let Some(eq_pos) = type_default.find('=') {
+        // Parse type and default (`=` inside the type, e.g. `int(1..=10)`, is not a default)
+        let mut depth = 0i32;
+        let default_pos = type_default.char_indices().find_map(|(i, ch)| {
+            match ch {
+                '(' | '<' => depth += 1,
+                ')' | '>' => depth -= 1,
+                '=' if depth == 0 => return Some(i),
+                _ => {}
+            }
+            None
+        });
+        let (param_type, default) = if let Some(eq_pos) = default_pos {
             let param_type = type_default[..eq_pos].trim().to_string();
             let default_str = type_default[eq_pos + 1..].trim();
             let default_value = self.parse_value(default_str)?;
@@ -542,14 +636,92 @@ impl Parser {
             (type_default.to_string(), None)
         };
 
-        Ok(Parameter {
+        let mut parameter = Parameter {
             name,
             alias,
             param_type,
             default,
             is_named,
             kind: super::ast::ParamKind::Normal,
-        })
+            prompt: false,
+            secret: false,
+            env: None,
+        };
+
+        for attribute in attributes {
+            match attribute {
+                "prompt" => parameter.prompt = true,
+                "secret" => parameter.secret = true,
+                other if other.starts_with("env(") && other.ends_with(')') => {
+                    let var = other[4..other.len() - 1].trim();
+                    let valid = var
+                        .chars()
+                        .next()
+                        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                        && var.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                    if !valid {
+                        return Err(ParseError::InvalidSyntax(
+                            format!(
+                                "Invalid environment variable name '{}' in @env on parameter '{}'",
+                                var, parameter.name
+                            ),
+                            line_number,
+                        ));
+                    }
+                    parameter.env = Some(var.to_string());
+                }
+                other => {
+                    return Err(ParseError::InvalidSyntax(
+                        format!(
+                            "Unknown attribute '@{}' on parameter '{}'. Supported attributes: @prompt, @secret, @env(NAME)",
+                            other, parameter.name
+                        ),
+                        line_number,
+                    ));
+                }
+            }
+        }
+
+        Ok(parameter)
+    }
+
+    /// Splits trailing `@attribute` markers off a parameter's `type = default` part.
+    ///
+    /// An attribute starts at an `@` preceded by whitespace, outside quotes and brackets,
+    /// so `"user@host"` defaults are left alone.
+    fn split_parameter_attributes(type_default: &str) -> (&str, Vec<&str>) {
+        let mut depth = 0i32;
+        let mut quote: Option<char> = None;
+        let mut prev = ' ';
+        let mut start = None;
+        for (i, ch) in type_default.char_indices() {
+            match quote {
+                Some(q) if ch == q => quote = None,
+                Some(_) => {}
+                None => match ch {
+                    '"' | '\'' => quote = Some(ch),
+                    '(' | '<' | '[' => depth += 1,
+                    ')' | '>' | ']' => depth -= 1,
+                    '@' if depth == 0 && prev.is_whitespace() => {
+                        start = Some(i);
+                        break;
+                    }
+                    _ => {}
+                },
+            }
+            prev = ch;
+        }
+
+        match start {
+            Some(pos) => (
+                type_default[..pos].trim_end(),
+                type_default[pos..]
+                    .split_whitespace()
+                    .map(|attr| attr.trim_start_matches('@'))
+                    .collect(),
+            ),
+            None => (type_default, Vec::new()),
+        }
     }
 
     fn parse_wildcard_parameter(
@@ -655,6 +827,9 @@ impl Parser {
                 name: name_opt,
                 count: count_opt,
             },
+            prompt: false,
+            secret: false,
+            env: None,
         })
     }
 
@@ -1101,6 +1276,63 @@ impl Parser {
                 let value = self.parse_value(value_str)?.to_string();
                 Ok(Directive::Cwd(value))
             }
+            "category" => {
+                let value = self.parse_value(value_str)?.to_string();
+                Ok(Directive::Category(value))
+            }
+            "help" => {
+                // Syntax: help.order: 10
+                if modifiers != ["order"] {
+                    return Err(ParseError::InvalidSyntax(
+                        "Invalid help syntax. Use 'help.order: NUMBER'".to_string(),
+                        self.current_line_number(),
+                    ));
+                }
+                let order = value_str.trim().parse::<i64>().map_err(|_| {
+                    ParseError::InvalidSyntax(
+                        format!("Invalid help order '{}'. Expected an integer", value_str.trim()),
+                        self.current_line_number(),
+                    )
+                })?;
+                Ok(Directive::HelpOrder(order))
+            }
+            "watch" => {
+                // Syntax: watch: "src/**/*.rs", "Cargo.toml" (brackets are optional)
+                let list = value_str.trim();
+                let list = if list.starts_with('[') {
+                    list.to_string()
+                } else {
+                    format!("[{}]", list)
+                };
+                match self.parse_value(&list)? {
+                    Value::Array(patterns) if !patterns.is_empty() => {
+                        Ok(Directive::Watch(patterns))
+                    }
+                    _ => Err(ParseError::InvalidSyntax(
+                        "Invalid watch syntax. Use 'watch: \"src/**/*.rs\", \"Cargo.toml\"'"
+                            .to_string(),
+                        self.current_line_number(),
+                    )),
+                }
+            }
+            "alias" => {
+                // Syntax: alias: b, bld
+                let aliases: Vec<String> = value_str
+                    .split(',')
+                    .map(|alias| alias.trim().to_string())
+                    .collect();
+                if let Some(invalid) = aliases.iter().find(|alias| {
+                    alias.is_empty()
+                        || alias.starts_with('-')
+                        || !alias.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+                }) {
+                    return Err(ParseError::InvalidSyntax(
+                        format!("Invalid command alias '{}'. Use 'alias: name, other'", invalid),
+                        self.current_line_number(),
+                    ));
+                }
+                Ok(Directive::Alias(aliases))
+            }
             "script" => {
                 let script = if value_str == "|" {
                     self.parse_multiline_block(indent)?
@@ -1205,6 +1437,23 @@ impl Parser {
                     Ok(Directive::Validate(target, rule))
                 }
             }
+            "complete" => {
+                // Syntax: complete.PARAM: $(command) or complete.PARAM: function_name
+                let Some(target) = modifiers.first() else {
+                    return Err(ParseError::InvalidSyntax(
+                        "Invalid complete syntax. Use 'complete.PARAM: $(command)' or 'complete.PARAM: function_name'".to_string(),
+                        self.current_line_number(),
+                    ));
+                };
+                let source = value_str.trim();
+                if source.is_empty() {
+                    return Err(ParseError::InvalidSyntax(
+                        format!("Missing completion source for parameter '{}'", target),
+                        self.current_line_number(),
+                    ));
+                }
+                Ok(Directive::Complete(target.to_string(), source.to_string()))
+            }
             "logs" => {
                 // Syntax: logs.json: path/to/file or logs: path/to/file
                 let format = modifiers.iter()
@@ -1358,16 +1607,69 @@ impl Parser {
         }
     }
 
+    /// Parses a preset definition.
+    /// Format: `preset NAME: name="value", name2=true, name3=[1, 2]`
+    fn parse_preset(&mut self) -> Result<Directive, ParseError> {
+        let trimmed = self.lines[self.current_index].trim();
+        let definition = trimmed.strip_prefix("preset ").unwrap_or("").trim();
+
+        let (name, values_str) = definition.split_once(':').ok_or_else(|| {
+            ParseError::InvalidSyntax(
+                format!(
+                    "Invalid preset syntax. Expected 'preset NAME: param=value, ...', got: {}",
+                    trimmed
+                ),
+                self.current_line_number(),
+            )
+        })?;
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(ParseError::InvalidSyntax(
+                format!(
+                    "Invalid preset name '{}'. Use letters, digits, '_' and '-'",
+                    name
+                ),
+                self.current_line_number(),
+            ));
+        }
+
+        let mut values = Vec::new();
+        for (param, value_str) in self.split_named_args(values_str)? {
+            values.push((param, self.parse_value(value_str)?));
+        }
+
+        self.current_index += 1;
+        Ok(Directive::Preset(name.to_string(), values))
+    }
+
     /// Parses arguments from a dependency argument string.
     /// Format: `name="value", name2=true, name3=123`
     fn parse_dependency_args(&self, args_str: &str) -> Result<HashMap<String, String>, ParseError> {
         let mut args = HashMap::new();
+        for (name, value_str) in self.split_named_args(args_str)? {
+            // Parse value (string, bool, or number)
+            let value = self.parse_dependency_value(value_str)?;
+            args.insert(name, value);
+        }
+        Ok(args)
+    }
+
+    /// Splits `name=value, name2=value2` into names and raw value strings.
+    fn split_named_args<'a>(
+        &self,
+        args_str: &'a str,
+    ) -> Result<Vec<(String, &'a str)>, ParseError> {
+        let mut args = Vec::new();
 
         if args_str.trim().is_empty() {
             return Ok(args);
         }
 
-        // Split by comma, but respect quotes
+        // Split by comma, but respect quotes and brackets
         let mut current = args_str.trim();
         while !current.is_empty() {
             let (arg_str, remainder) = self.split_next_arg(current)?;
@@ -1386,11 +1688,7 @@ impl Parser {
 
             let name = arg_str[..equals_pos].trim().to_string();
             let value_str = arg_str[equals_pos + 1..].trim();
-
-            // Parse value (string, bool, or number)
-            let value = self.parse_dependency_value(value_str)?;
-
-            args.insert(name, value);
+            args.push((name, value_str));
 
             current = remainder.trim();
         }
@@ -1398,10 +1696,11 @@ impl Parser {
         Ok(args)
     }
 
-    /// Splits the next argument from the string, handling quotes.
+    /// Splits the next argument from the string, handling quotes and brackets.
     fn split_next_arg<'a>(&self, s: &'a str) -> Result<(&'a str, &'a str), ParseError> {
         let mut in_quotes = false;
         let mut quote_char = '\0';
+        let mut depth = 0usize;
 
         for (i, ch) in s.char_indices() {
             match ch {
@@ -1412,7 +1711,9 @@ impl Parser {
                 ch if ch == quote_char && in_quotes => {
                     in_quotes = false;
                 }
-                ',' if !in_quotes => {
+                '[' | '(' if !in_quotes => depth += 1,
+                ']' | ')' if !in_quotes => depth = depth.saturating_sub(1),
+                ',' if !in_quotes && depth == 0 => {
                     return Ok((&s[..i], &s[i + 1..]));
                 }
                 _ => {}
@@ -1446,3 +1747,46 @@ impl Parser {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nestparse::ast::Command;
+    use crate::nestparse::codegen;
+
+    fn watch_patterns(command: &Command) -> Option<Vec<String>> {
+        command.directives.iter().find_map(|d| match d {
+            Directive::Watch(patterns) => Some(patterns.clone()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_watch_directive() {
+        let content = r#"dev:
+    watch: "src/**/*.rs", 'Cargo.toml'
+    script: cargo run
+test:
+    watch: [tests/*.rs]
+    script: cargo test
+"#;
+        let commands = Parser::new(content).parse().unwrap().commands;
+        assert_eq!(
+            watch_patterns(&commands[0]),
+            Some(vec!["src/**/*.rs".to_string(), "Cargo.toml".to_string()])
+        );
+        assert_eq!(
+            watch_patterns(&commands[1]),
+            Some(vec!["tests/*.rs".to_string()])
+        );
+
+        // The generated form parses back to the same patterns
+        let generated = codegen::to_nestfile_string(&commands[0], 0);
+        let reparsed = Parser::new(&generated).parse().unwrap().commands;
+        assert_eq!(watch_patterns(&reparsed[0]), watch_patterns(&commands[0]));
+
+        assert!(Parser::new("dev:\n    watch: []\n    script: x\n")
+            .parse()
+            .is_err());
+    }
+}
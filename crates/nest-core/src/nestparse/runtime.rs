@@ -3,12 +3,15 @@
 //! This module handles the execution phase of the CLI, separating it from
 //! the build/generation phase.
 
-use super::ast::{Command, Constant, Function, Variable};
+use super::ast::{find_command, Command, Constant, Function, Variable};
 use super::directives::DirectiveResolver;
 use super::env::EnvironmentManager;
+use super::executor::{OutputSink, OutputStream};
+use super::observer::{Phase, RuntimeObserver};
 use super::runtime_validator::RuntimeValidator;
 use super::template::{FunctionResolver, TemplateContext, TemplateProcessor};
 use crate::constants::{DEFAULT_SUBCOMMAND, ENV_NEST_CALL_STACK};
+use crate::error::NestError;
 
 use std::collections::HashMap;
 
@@ -24,6 +27,7 @@ pub struct ScriptExecutionContext<'a> {
     pub hide_output: bool,
     pub privileged: bool,
     pub pid_callback: Option<&'a (dyn Fn(u32) + Send + Sync)>,
+    pub output_sink: Option<&'a OutputSink<'a>>,
 }
 
 /// Context for command execution containing related parameters.
@@ -46,8 +50,28 @@ pub struct Runtime {
     constants: Vec<Constant>,
     /// The parsed functions (reusable scripts)
     functions: Vec<Function>,
+    /// Group variables and constants under their qualified names (`docker.IMAGE`)
+    namespace_variables: Vec<Variable>,
     /// Callback for reporting child process PIDs (for signal handling)
     pid_callback: Option<Box<dyn Fn(u32) + Send + Sync>>,
+    /// Answers `require_confirm` instead of a terminal prompt (see `with_confirm_handler`)
+    confirm_handler: Option<Box<ConfirmHandler>>,
+    /// Told about every step of a run (see `with_observer`)
+    observers: Vec<Box<dyn RuntimeObserver>>,
+}
+
+/// Decides whether a command with `require_confirm` may run, given the
+/// directive's message (possibly empty) and the command path.
+pub type ConfirmHandler = dyn Fn(&str, &[String]) -> bool + Send + Sync;
+
+/// A command and the commands its `depends` directive names, recursively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyNode {
+    /// Canonical command path
+    pub path: Vec<String>,
+    /// Whether the dependencies run in parallel
+    pub parallel: bool,
+    pub children: Vec<DependencyNode>,
 }
 
 /// Internal helper for resolving function calls during template processing.
@@ -62,8 +86,9 @@ impl<'a> FunctionResolver for RuntimeFunctionResolver<'a> {
         func_name: &str,
         args: &HashMap<String, String>,
     ) -> Result<Option<String>, String> {
-        // Resolve function from the runtime
-        if let Some(func) = self.runtime.find_function(func_name) {
+        // Resolve function from the runtime (namespace of the current command first)
+        let scope = self.context.command_path.unwrap_or(&[]);
+        if let Some(func) = self.runtime.find_function(func_name, scope) {
             // Functions called from templates use the current context's environment
             // but their own arguments.
             let mut merged_env = self.context.env_vars.clone();
@@ -78,7 +103,9 @@ impl<'a> FunctionResolver for RuntimeFunctionResolver<'a> {
                 ..*self.context
             };
 
-            self.runtime.execute_function(func, &func_context)
+            self.runtime
+                .execute_function(func, &func_context)
+                .map_err(|e| e.to_string())
         } else {
             // Function not found - returning Ok(None) allows TemplateProcessor
             // to keep the original {{ func() }} tag if it wasn't a valid function call.
@@ -95,15 +122,59 @@ impl Runtime {
         functions: Vec<Function>,
         pid_callback: Option<Box<dyn Fn(u32) + Send + Sync>>,
     ) -> Self {
+        let mut namespace_variables = Vec::new();
+        collect_namespace_variables(&commands, "", &mut namespace_variables);
         Self {
             commands,
             variables,
             constants,
             functions,
+            namespace_variables,
             pid_callback,
+            confirm_handler: None,
+            observers: Vec::new(),
+        }
+    }
+
+    /// Asks `handler` instead of the terminal whether a `require_confirm`
+    /// command may run.
+    pub fn with_confirm_handler(mut self, handler: Box<ConfirmHandler>) -> Self {
+        self.confirm_handler = Some(handler);
+        self
+    }
+
+    /// Adds an observer told about every step of a run. Observers are told
+    /// in the order they were added.
+    pub fn with_observer(mut self, observer: Box<dyn RuntimeObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    fn notify(&self, event: impl Fn(&dyn RuntimeObserver)) {
+        for observer in &self.observers {
+            event(observer.as_ref());
         }
     }
 
+    /// Whether scripts stream their output to the observers instead of the terminal.
+    fn streams_output(&self) -> bool {
+        self.observers.iter().any(|o| o.receives_output())
+    }
+
+    /// Runs the script of one phase of a command, telling the observers.
+    fn execute_phase(
+        &self,
+        phase: Phase,
+        script: &str,
+        context: &ScriptExecutionContext,
+    ) -> Result<(), NestError> {
+        let command_path = context.command_path.unwrap_or(&[]);
+        self.notify(|o| o.phase_started(command_path, phase));
+        let result = self.execute_script(script, context);
+        self.notify(|o| o.phase_finished(command_path, phase, &result));
+        result
+    }
+
     // / Checks if directives are valid
     // removed directive getters in favor of DirectiveResolver
 
@@ -127,6 +198,7 @@ impl Runtime {
             privileged: context.privileged,
             pid_callback: None, // No PID callback for value evaluation
             hide_output: true,  // Hide output since we capture it
+            output_sink: None,
         };
 
         CommandExecutor::capture_output(script, &exec_context)
@@ -142,7 +214,11 @@ impl Runtime {
     /// Command calls use the format: `command` or `group:command` or `command(arg="value")`.
     ///
     /// This is a helper function for executing before, after, and fallback scripts.
-    fn execute_script(&self, script: &str, context: &ScriptExecutionContext) -> Result<(), String> {
+    fn execute_script(
+        &self,
+        script: &str,
+        context: &ScriptExecutionContext,
+    ) -> Result<(), NestError> {
         if context.dry_run {
             use super::output::OutputFormatter;
             OutputFormatter::info(&format!("[DRY RUN] Would execute: {}", script));
@@ -163,6 +239,7 @@ impl Runtime {
             local_constants: &[],
             parent_variables: &parent_vars,
             parent_constants: &parent_consts,
+            namespace_variables: &self.namespace_variables,
         };
 
         let script = TemplateProcessor::process(
@@ -212,6 +289,7 @@ impl Runtime {
                         local_constants: &[],
                         parent_variables: &[],
                         parent_constants: &[],
+                        namespace_variables: &self.namespace_variables,
                     };
                     let processed_command = TemplateProcessor::process(
                         external_command,
@@ -238,7 +316,8 @@ impl Runtime {
 
                 // Check if it's a function call (single name, no colons)
                 if !call_name.contains(':') {
-                    if let Some(func) = self.find_function(&call_name) {
+                    let scope = context.command_path.unwrap_or(&[]);
+                    if let Some(func) = self.find_function(&call_name, scope) {
                         // Merge global env_vars with system env
                         let mut merged_env = context.env_vars.clone();
                         use std::env;
@@ -281,6 +360,7 @@ impl Runtime {
                         vec![cmd_name]
                     }
                 };
+                let resolved_path = self.canonical_command_path(&resolved_path);
 
                 // Check if resolved path matches current command (recursive call)
                 if let Some(current_path) = context.command_path {
@@ -336,6 +416,7 @@ impl Runtime {
                             local_constants: &[],
                             parent_variables: &[],
                             parent_constants: &[],
+                            namespace_variables: &self.namespace_variables,
                         };
                         let processed_line = TemplateProcessor::process(
                             &shell_cmd,
@@ -386,7 +467,10 @@ impl Runtime {
     }
 
     /// Executes a shell script (helper function).
-    fn execute_shell_script(script: &str, context: &ScriptExecutionContext) -> Result<(), String> {
+    fn execute_shell_script(
+        script: &str,
+        context: &ScriptExecutionContext,
+    ) -> Result<(), NestError> {
         use super::ast::Command;
         use super::executor::{CommandExecutor, ExecutionContext};
 
@@ -401,6 +485,7 @@ impl Runtime {
             privileged: context.privileged,
             pid_callback: context.pid_callback.map(|cb| cb as &dyn Fn(u32)),
             hide_output: context.hide_output,
+            output_sink: context.output_sink,
         };
 
         CommandExecutor::execute(script, &exec_context)
@@ -476,19 +561,87 @@ impl Runtime {
 
     /// Finds a command by its path.
     pub fn find_command(&self, path: &[String]) -> Option<&Command> {
+        find_command(&self.commands, path)
+    }
+
+    /// Resolves aliases in a command path to the canonical command names.
+    ///
+    /// Returns the path unchanged if it doesn't name a command.
+    pub fn canonical_command_path(&self, path: &[String]) -> Vec<String> {
         let mut current = &self.commands;
-        let mut found: Option<&Command> = None;
+        let mut canonical = Vec::with_capacity(path.len());
 
         for name in path {
-            found = current.iter().find(|c| &c.name == name);
-            if let Some(cmd) = found {
-                current = &cmd.children;
-            } else {
-                return None;
+            let Some(cmd) = current.iter().find(|c| c.matches_name(name)) else {
+                return path.to_vec();
+            };
+            canonical.push(cmd.name.clone());
+            current = &cmd.children;
+        }
+
+        canonical
+    }
+
+    /// Builds the tree of commands that running `path` executes through `depends`.
+    ///
+    /// Dependencies are resolved the same way as at run time. A dependency that
+    /// closes a cycle is left out; a missing one is kept as a leaf.
+    pub fn dependency_tree(&self, path: &[String]) -> DependencyNode {
+        let mut ancestors = Vec::new();
+        self.build_dependency_node(self.canonical_command_path(path), &mut ancestors)
+    }
+
+    fn build_dependency_node(
+        &self,
+        path: Vec<String>,
+        ancestors: &mut Vec<Vec<String>>,
+    ) -> DependencyNode {
+        let Some(command) = self.find_command(&path) else {
+            return DependencyNode {
+                path,
+                parallel: false,
+                children: Vec::new(),
+            };
+        };
+
+        let (depends, parallel) = DirectiveResolver::get_depends_directive(&command.directives);
+        ancestors.push(path.clone());
+        let mut children = Vec::new();
+        for dep in &depends {
+            let dep_path = self.resolve_dependency_path(dep, &path);
+            if !ancestors.contains(&dep_path) {
+                children.push(self.build_dependency_node(dep_path, ancestors));
             }
         }
+        ancestors.pop();
 
-        found
+        DependencyNode {
+            path,
+            parallel,
+            children,
+        }
+    }
+
+    /// Resolves a `depends` entry to a canonical command path.
+    ///
+    /// `parent:command` is absolute from the root; a bare name is a sibling of
+    /// the command that depends on it.
+    fn resolve_dependency_path(
+        &self,
+        dep: &super::ast::Dependency,
+        current_path: &[String],
+    ) -> Vec<String> {
+        let dep_path: Vec<String> = if dep.command_path.contains(':') {
+            dep.command_path
+                .split(':')
+                .map(|s| s.trim().to_string())
+                .collect()
+        } else {
+            let mut resolved = current_path[..current_path.len().saturating_sub(1)].to_vec();
+            resolved.push(dep.command_path.trim().to_string());
+            resolved
+        };
+        self.canonical_command_path(&dep_path)
     }
 
     /// and constants from each parent command. The order is from root to leaf,
@@ -497,28 +650,37 @@ impl Runtime {
         &self,
         command_path: &[String],
     ) -> (Vec<super::ast::Variable>, Vec<super::ast::Constant>) {
-        let mut parent_variables = Vec::new();
-        let mut parent_constants = Vec::new();
-
         // If path is empty or has only one element, no parents
         if command_path.len() <= 1 {
-            return (parent_variables, parent_constants);
+            return (Vec::new(), Vec::new());
         }
 
-        // Traverse path from root to parent (excluding the last element which is the current command)
+        // Excluding the last element which is the current command
+        self.collect_scope_variables(&command_path[..command_path.len() - 1])
+    }
+
+    /// Collects local variables and constants of every command in the path,
+    /// from root to leaf.
+    fn collect_scope_variables(
+        &self,
+        path: &[String],
+    ) -> (Vec<super::ast::Variable>, Vec<super::ast::Constant>) {
+        let mut variables = Vec::new();
+        let mut constants = Vec::new();
+
         let mut current = &self.commands;
-        for name in command_path.iter().take(command_path.len() - 1) {
+        for name in path {
             if let Some(cmd) = current.iter().find(|c| &c.name == name) {
-                // Add variables and constants from this parent command
-                parent_variables.extend(cmd.local_variables.iter().cloned());
-                parent_constants.extend(cmd.local_constants.iter().cloned());
+                // Add variables and constants from this command
+                variables.extend(cmd.local_variables.iter().cloned());
+                constants.extend(cmd.local_constants.iter().cloned());
                 current = &cmd.children;
             } else {
                 break;
             }
         }
 
-        (parent_variables, parent_constants)
+        (variables, constants)
     }
 
     /// Collects directives (CWD, AFTER, BEFORE, FALLBACK) from all parent commands in the path.
@@ -602,8 +764,16 @@ impl Runtime {
     }
 
     /// Finds a function by its name.
-    fn find_function(&self, name: &str) -> Option<&Function> {
-        self.functions.iter().find(|f| f.name == name)
+    ///
+    /// Functions defined inside a group are named `group.name`. They are
+    /// looked up in the namespaces of `scope` first, innermost to outermost,
+    /// so commands of an imported group can call them without the prefix.
+    fn find_function(&self, name: &str, scope: &[String]) -> Option<&Function> {
+        (1..=scope.len())
+            .rev()
+            .map(|depth| format!("{}.{}", scope[..depth].join("."), name))
+            .find_map(|qualified| self.functions.iter().find(|f| f.name == qualified))
+            .or_else(|| self.functions.iter().find(|f| f.name == name))
     }
 
     /// Checks if a command has a default subcommand.
@@ -620,7 +790,7 @@ impl Runtime {
         &self,
         function: &Function,
         context: &ScriptExecutionContext,
-    ) -> Result<Option<String>, String> {
+    ) -> Result<Option<String>, NestError> {
         if context.verbose {
             use super::output::OutputFormatter;
             let args_str = if context.args.is_empty() {
@@ -639,9 +809,22 @@ impl Runtime {
             ));
         }
 
-        // Build variable map: function local > global
+        // Functions named `group.name` belong to the namespace of `group`
+        let namespace: Vec<String> = function
+            .name
+            .rsplit_once('.')
+            .map(|(prefix, _)| prefix.split('.').map(str::to_string).collect())
+            .unwrap_or_default();
+        let (namespace_vars, namespace_consts) = self.collect_scope_variables(&namespace);
+
+        // Build variable map: function local > namespace > global > qualified
         let mut var_map: HashMap<String, String> = HashMap::new();
 
+        // Add qualified namespace symbols ({{group.VAR}})
+        for variable in &self.namespace_variables {
+            var_map.insert(variable.name.clone(), variable.value.to_string_unquoted());
+        }
+
         // Add global constants
         for constant in &self.constants {
             var_map.insert(constant.name.clone(), constant.value.to_string_unquoted());
@@ -652,6 +835,14 @@ impl Runtime {
             var_map.insert(variable.name.clone(), variable.value.to_string_unquoted());
         }
 
+        // Add constants and variables of the function's namespace
+        for constant in &namespace_consts {
+            var_map.insert(constant.name.clone(), constant.value.to_string_unquoted());
+        }
+        for variable in &namespace_vars {
+            var_map.insert(variable.name.clone(), variable.value.to_string_unquoted());
+        }
+
         // Add function local variables (override global)
         for variable in &function.local_variables {
             var_map.insert(variable.name.clone(), variable.value.to_string_unquoted());
@@ -706,15 +897,14 @@ impl Runtime {
                     current_shell_block.clear();
                 }
 
-                // Extract return value
-                let return_value = if let Some(return_expr) = trimmed_line.strip_prefix("return ") {
-                    Self::process_template_in_return_value(
+                // Extract return value (a bare `@return` returns an empty string)
+                let return_value = match trimmed_line.strip_prefix("@return ") {
+                    Some(return_expr) => Self::process_template_in_return_value(
                         return_expr.trim_start(),
                         context.args,
                         &var_map,
-                    )
-                } else {
-                    String::new()
+                    ),
+                    None => String::new(),
                 };
 
                 return Ok(Some(return_value));
@@ -748,6 +938,7 @@ impl Runtime {
                         local_constants: &[],
                         parent_variables: &[],
                         parent_constants: &[],
+                        namespace_variables: &self.namespace_variables,
                     };
                     let processed_command = TemplateProcessor::process(
                         external_command,
@@ -774,7 +965,7 @@ impl Runtime {
 
                 // Check if it's a function call (single name, no colons)
                 if !call_name.contains(':') {
-                    if let Some(func) = self.find_function(&call_name) {
+                    if let Some(func) = self.find_function(&call_name, &namespace) {
                         // Merge global env_vars with system env
                         let mut merged_env = context.env_vars.clone();
                         use std::env;
@@ -808,6 +999,7 @@ impl Runtime {
                         vec![cmd_name]
                     }
                 };
+                let resolved_path = self.canonical_command_path(&resolved_path);
 
                 // Execute command (recursive call detection handled in execute_command)
                 if let Some(cmd) = self.find_command(&resolved_path) {
@@ -844,7 +1036,7 @@ impl Runtime {
         depends: &[super::ast::Dependency],
         context: &mut CommandExecutionContext<'_>,
         parallel: bool,
-    ) -> Result<(), String> {
+    ) -> Result<(), NestError> {
         let current_path = context.command_path.unwrap_or(&[]);
         let dry_run = context.dry_run;
         let verbose = context.verbose;
@@ -853,37 +1045,14 @@ impl Runtime {
         // Resolve all dependency paths first
         let mut tasks = Vec::new();
         for dep in depends {
-            // Parse dependency path
-            // Format: "command" (relative to current command's parent)
-            //         "parent:command" (absolute path from root)
-            let dep_path: Vec<String> = if dep.command_path.contains(':') {
-                // Absolute path from root (e.g., "dev:build")
-                dep.command_path
-                    .split(':')
-                    .map(|s| s.trim().to_string())
-                    .collect()
-            } else {
-                // Relative dependency - resolve from current command's parent
-                let dep_name = dep.command_path.trim().to_string();
-                if current_path.is_empty() {
-                    // Top-level command - dependency is also top-level
-                    vec![dep_name]
-                } else {
-                    // Nested command - dependency is relative to parent
-                    let mut resolved =
-                        current_path[..current_path.len().saturating_sub(1)].to_vec();
-                    resolved.push(dep_name);
-                    resolved
-                }
-            };
+            let dep_path = self.resolve_dependency_path(dep, current_path);
 
             // Check for cycles
             if visited.contains(&dep_path) {
-                return Err(format!(
-                    "Circular dependency detected: {} -> {}",
+                return Err(NestError::Cycle(vec![
                     current_path.join(" "),
-                    dep_path.join(" ")
-                ));
+                    dep_path.join(" "),
+                ]));
             }
 
             tasks.push((dep, dep_path));
@@ -903,6 +1072,7 @@ impl Runtime {
 
                     s.spawn(move || {
                         if let Some(dep_command) = self.find_command(&dep_path) {
+                            self.notify(|o| o.dependency_scheduled(&dep_path, current_path));
                             let mut dep_context = CommandExecutionContext {
                                 command: dep_command,
                                 args: &dep.args,
@@ -914,31 +1084,35 @@ impl Runtime {
                             };
                             if let Err(e) = self.execute_command_with_deps(&mut dep_context) {
                                 let mut errs = errors_clone.lock().unwrap();
-                                errs.push(format!(
-                                    "Dependency '{}' failed: {}",
-                                    dep.command_path, e
-                                ));
+                                errs.push(NestError::Dependency {
+                                    dependency: dep.command_path.clone(),
+                                    source: Box::new(e),
+                                });
                             }
                         } else {
                             let mut errs = errors_clone.lock().unwrap();
-                            errs.push(format!(
-                                "Dependency not found: {} (required by {})",
-                                dep_path.join(" "),
-                                current_path.join(" ")
-                            ));
+                            errs.push(NestError::DependencyNotFound {
+                                dependency: dep_path.clone(),
+                                required_by: current_path.to_vec(),
+                            });
                         }
                     });
                 }
             });
 
-            let errors = errors.lock().unwrap();
+            let mut errors = std::mem::take(&mut *errors.lock().unwrap());
+            if errors.len() == 1 {
+                return Err(errors.remove(0));
+            }
             if !errors.is_empty() {
-                return Err(errors.join("\n"));
+                return Err(NestError::Multiple(errors));
             }
         } else {
             // Serial execution
-            for (dep, dep_path) in tasks {
-                if let Some(dep_command) = self.find_command(&dep_path) {
+            let mut tasks = tasks.into_iter();
+            while let Some((dep, dep_path)) = tasks.next() {
+                let result = if let Some(dep_command) = self.find_command(&dep_path) {
+                    self.notify(|o| o.dependency_scheduled(&dep_path, current_path));
                     let mut dep_context = CommandExecutionContext {
                         command: dep_command,
                         args: &dep.args,
@@ -948,13 +1122,18 @@ impl Runtime {
                         visited,
                         parent_args,
                     };
-                    self.execute_command_with_deps(&mut dep_context)?;
+                    self.execute_command_with_deps(&mut dep_context)
                 } else {
-                    return Err(format!(
-                        "Dependency not found: {} (required by {})",
-                        dep_path.join(" "),
-                        current_path.join(" ")
-                    ));
+                    Err(NestError::DependencyNotFound {
+                        dependency: dep_path,
+                        required_by: current_path.to_vec(),
+                    })
+                };
+                if result.is_err() {
+                    for (_, skipped) in tasks {
+                        self.notify(|o| o.dependency_skipped(&skipped, current_path));
+                    }
+                    return result;
                 }
             }
         }
@@ -966,7 +1145,19 @@ impl Runtime {
     fn execute_command_with_deps(
         &self,
         context: &mut CommandExecutionContext<'_>,
-    ) -> Result<(), String> {
+    ) -> Result<(), NestError> {
+        let command_path = context.command_path.unwrap_or(&[]);
+        self.notify(|o| o.command_started(command_path));
+        let result = self.execute_command_steps(context);
+        self.notify(|o| o.command_finished(command_path, &result));
+        result
+    }
+
+    /// Validation, dependencies, confirmation and scripts of one command.
+    fn execute_command_steps(
+        &self,
+        context: &mut CommandExecutionContext<'_>,
+    ) -> Result<(), NestError> {
         let command = context.command;
         let args = context.args;
         let command_path = context.command_path;
@@ -982,49 +1173,18 @@ impl Runtime {
             if let Ok(stack_str) = std::env::var(ENV_NEST_CALL_STACK) {
                 let stack: Vec<&str> = stack_str.split(',').collect();
                 if stack.contains(&command_id.as_str()) {
-                    return Err(format!(
-                         "Circular dependency detected: Command '{}' is already in the call stack.\nCall stack: {}", 
-                         command_id, stack_str
-                     ));
+                    let chain = stack
+                        .iter()
+                        .chain(std::iter::once(&command_id.as_str()))
+                        .map(|id| id.replace(':', " "))
+                        .collect();
+                    return Err(NestError::Cycle(chain));
                 }
             }
         }
 
         // Validate parameters
-        let validate_directives = DirectiveResolver::get_validate_directives(&command.directives);
-        if !validate_directives.is_empty() {
-            let (parent_vars, parent_consts) =
-                self.collect_parent_variables(command_path_unwrapped);
-
-            let mut all_env_directives = self.collect_parent_env_directives(command_path_unwrapped);
-            for directive in &command.directives {
-                match directive {
-                    super::ast::Directive::Env(..) | super::ast::Directive::EnvFile(..) => {
-                        all_env_directives.push(directive.clone());
-                    }
-                    _ => {}
-                }
-            }
-            let env_vars = EnvironmentManager::extract_env_vars(&all_env_directives);
-
-            let tpl_context = TemplateContext {
-                global_variables: &self.variables,
-                global_constants: &self.constants,
-                local_variables: &[],
-                local_constants: &[],
-                parent_variables: &parent_vars,
-                parent_constants: &parent_consts,
-            };
-
-            RuntimeValidator::validate(
-                &validate_directives,
-                args,
-                &env_vars,
-                &tpl_context,
-                command_path_unwrapped,
-                parent_args,
-            )?;
-        }
+        self.validate_arguments(command, args, command_path_unwrapped, parent_args)?;
 
         // Execute dependencies first
         let (depends, parallel) = DirectiveResolver::get_depends_directive(&command.directives);
@@ -1062,16 +1222,24 @@ impl Runtime {
             if let Some(confirm_message) =
                 DirectiveResolver::get_require_confirm_directive(&command.directives)
             {
-                match super::input::prompt_confirmation(Some(&confirm_message), command_path) {
+                let confirmed = match &self.confirm_handler {
+                    Some(handler) => Ok(handler(&confirm_message, command_path_unwrapped)),
+                    None => super::input::prompt_confirmation(Some(&confirm_message), command_path),
+                };
+                match confirmed {
                     Ok(true) => {
                         // User confirmed - continue execution
                     }
                     Ok(false) => {
-                        // User declined - return without error
-                        return Ok(());
+                        return Err(NestError::ConfirmationDeclined {
+                            command_path: command_path_unwrapped.to_vec(),
+                        });
                     }
                     Err(e) => {
-                        return Err(format!("Confirmation prompt failed: {}", e));
+                        return Err(NestError::Other(format!(
+                            "Confirmation prompt failed: {}",
+                            e
+                        )));
                     }
                 }
             }
@@ -1108,6 +1276,8 @@ impl Runtime {
                     .source_file
                     .as_ref()
                     .and_then(|p| p.parent())
+                    // Remote sources (URLs) have no local directory to run in
+                    .filter(|p| p.is_dir())
                     .map(|p| p.to_string_lossy().to_string())
             });
         let privileged = DirectiveResolver::get_privileged_directive(&command.directives);
@@ -1152,6 +1322,7 @@ impl Runtime {
             local_constants: &command.local_constants,
             parent_variables: &parent_variables,
             parent_constants: &parent_constants,
+            namespace_variables: &self.namespace_variables,
         };
 
         for value in processed_env_vars.values_mut() {
@@ -1164,6 +1335,7 @@ impl Runtime {
                 verbose,
                 privileged,
                 pid_callback: self.pid_callback.as_deref(),
+                output_sink: None,
                 parent_args: &merged_parent_args,
                 hide_output: true,
             };
@@ -1191,6 +1363,17 @@ impl Runtime {
 
         let env_vars = processed_env_vars;
 
+        // The command's scripts report their processes and output to the observers too
+        let on_spawn = |pid: u32| {
+            if let Some(callback) = &self.pid_callback {
+                callback(pid);
+            }
+            self.notify(|o| o.process_spawned(command_path_unwrapped, pid));
+        };
+        let on_output = |stream: OutputStream, line: &str| {
+            self.notify(|o| o.output_line(command_path_unwrapped, stream, line));
+        };
+
         let mut script_exec_context = ScriptExecutionContext {
             args,
             env_vars: &env_vars,
@@ -1199,7 +1382,8 @@ impl Runtime {
             dry_run,
             verbose,
             privileged,
-            pid_callback: self.pid_callback.as_deref(),
+            pid_callback: Some(&on_spawn),
+            output_sink: self.streams_output().then_some(&on_output as &OutputSink),
             parent_args: &merged_parent_args,
             hide_output: false,
         };
@@ -1222,14 +1406,19 @@ impl Runtime {
             }
 
             script_exec_context.hide_output = hide_before;
-            if let Err(e) = self.execute_script(&processed_before, &script_exec_context) {
-                return Err(format!("Before script failed: {}", e));
+            if let Err(e) =
+                self.execute_phase(Phase::Before, &processed_before, &script_exec_context)
+            {
+                return Err(NestError::Hook {
+                    hook: "before",
+                    source: Box::new(e),
+                });
             }
         }
 
         let (script, hide_script) =
             DirectiveResolver::get_directive_value_with_hide(&command.directives, "script")
-                .ok_or_else(|| "Command has no script directive".to_string())?;
+                .ok_or_else(|| NestError::Other("Command has no script directive".to_string()))?;
 
         let processed_script = TemplateProcessor::process(
             &script,
@@ -1242,10 +1431,9 @@ impl Runtime {
         if privileged && !dry_run {
             use super::executor::CommandExecutor;
             if !CommandExecutor::check_privileged_access() {
-                return Err(CommandExecutor::format_privileged_error(
-                    command,
-                    Some(command_path_unwrapped),
-                ));
+                return Err(NestError::Privilege {
+                    command_path: command_path_unwrapped.to_vec(),
+                });
             }
         }
 
@@ -1261,6 +1449,7 @@ impl Runtime {
                 verbose,
                 privileged,
                 pid_callback: None,
+                output_sink: None,
                 hide_output: false,
             };
             CommandExecutor::show_dry_run_preview(&processed_script, &dry_run_context);
@@ -1279,13 +1468,15 @@ impl Runtime {
                 verbose: true,
                 privileged,
                 pid_callback: None,
+                output_sink: None,
                 hide_output: hide_script,
             };
             CommandExecutor::show_verbose_info(&processed_script, &verbose_context);
         }
 
         script_exec_context.hide_output = hide_script;
-        let main_result = self.execute_script(&processed_script, &script_exec_context);
+        let main_result =
+            self.execute_phase(Phase::Script, &processed_script, &script_exec_context);
 
         let result = match main_result {
             Ok(()) => {
@@ -1307,22 +1498,28 @@ impl Runtime {
                     }
 
                     script_exec_context.hide_output = hide_after;
-                    if let Err(e) = self.execute_script(&processed_after, &script_exec_context) {
-                        return Err(format!("After script failed: {}", e));
+                    if let Err(e) =
+                        self.execute_phase(Phase::After, &processed_after, &script_exec_context)
+                    {
+                        return Err(NestError::Hook {
+                            hook: "after",
+                            source: Box::new(e),
+                        });
                     }
                 }
                 Ok(())
             }
-            Err(error_msg) => {
+            Err(error) => {
                 let fallback_info = DirectiveResolver::get_directive_value_with_hide(
                     &command.directives,
                     "fallback",
                 )
                 .or_else(|| parent_directives.get("fallback").cloned());
                 if let Some((fallback_script, hide_fallback)) = fallback_info {
+                    let error_msg = error.to_string();
                     let mut fallback_args = args.clone();
                     fallback_args.insert("SYSTEM_ERROR_MESSAGE".to_string(), error_msg.clone());
-                    fallback_args.insert("error".to_string(), error_msg.clone());
+                    fallback_args.insert("error".to_string(), error_msg);
 
                     let fallback_context = ScriptExecutionContext {
                         args: &fallback_args,
@@ -1337,12 +1534,17 @@ impl Runtime {
                         &merged_parent_args,
                         Some(&|cmd| self.evaluate_dynamic_value(cmd, &fallback_context)),
                     );
-                    if let Err(e) = self.execute_script(&processed_fallback, &fallback_context) {
-                        return Err(format!("Fallback script failed: {}", e));
+                    if let Err(e) =
+                        self.execute_phase(Phase::Fallback, &processed_fallback, &fallback_context)
+                    {
+                        return Err(NestError::Hook {
+                            hook: "fallback",
+                            source: Box::new(e),
+                        });
                     }
                     Ok(())
                 } else {
-                    Err(error_msg)
+                    Err(error)
                 }
             }
         };
@@ -1368,11 +1570,6 @@ impl Runtime {
             DirectiveResolver::get_directive_value_with_hide(&command.directives, "finally")
                 .or_else(|| parent_directives.get("finally").cloned());
         if let Some((finally_script, hide_finally)) = finally_info {
-            let original_result = match &result {
-                Ok(_) => Ok(()),
-                Err(e) => Err(e.clone()),
-            };
-
             let tpl_context = TemplateContext {
                 global_variables: &self.variables,
                 global_constants: &self.constants,
@@ -1380,6 +1577,7 @@ impl Runtime {
                 local_constants: &command.local_constants,
                 parent_variables: &parent_variables,
                 parent_constants: &parent_constants,
+                namespace_variables: &self.namespace_variables,
             };
             let processed_finally = TemplateProcessor::process(
                 &finally_script,
@@ -1396,14 +1594,16 @@ impl Runtime {
 
             script_exec_context.hide_output = hide_finally;
             script_exec_context.args = args;
-            if let Err(e) = self.execute_script(&processed_finally, &script_exec_context) {
+            if let Err(e) =
+                self.execute_phase(Phase::Finally, &processed_finally, &script_exec_context)
+            {
                 if verbose {
                     use super::output::OutputFormatter;
                     OutputFormatter::warning(&format!("Finally script failed: {}", e));
                 }
             }
 
-            return original_result;
+            return result;
         }
 
         if let Some(path) = command_path {
@@ -1413,6 +1613,55 @@ impl Runtime {
         result
     }
 
+    /// Checks `args` against the command's `validate` rules without running anything.
+    ///
+    /// `args` are the extracted argument values, as passed to
+    /// [`execute_command_with_parent_args`](Self::execute_command_with_parent_args).
+    pub fn validate_arguments(
+        &self,
+        command: &Command,
+        args: &HashMap<String, String>,
+        command_path: &[String],
+        parent_args: &HashMap<String, String>,
+    ) -> Result<(), NestError> {
+        let validate_directives = DirectiveResolver::get_validate_directives(&command.directives);
+        if validate_directives.is_empty() {
+            return Ok(());
+        }
+
+        let (parent_vars, parent_consts) = self.collect_parent_variables(command_path);
+
+        let mut all_env_directives = self.collect_parent_env_directives(command_path);
+        for directive in &command.directives {
+            match directive {
+                super::ast::Directive::Env(..) | super::ast::Directive::EnvFile(..) => {
+                    all_env_directives.push(directive.clone());
+                }
+                _ => {}
+            }
+        }
+        let env_vars = EnvironmentManager::extract_env_vars(&all_env_directives);
+
+        let tpl_context = TemplateContext {
+            global_variables: &self.variables,
+            global_constants: &self.constants,
+            local_variables: &[],
+            local_constants: &[],
+            parent_variables: &parent_vars,
+            parent_constants: &parent_consts,
+            namespace_variables: &self.namespace_variables,
+        };
+
+        RuntimeValidator::validate(
+            &validate_directives,
+            args,
+            &env_vars,
+            &tpl_context,
+            command_path,
+            parent_args,
+        )
+    }
+
     pub fn execute_command(
         &self,
         command: &Command,
@@ -1420,7 +1669,7 @@ impl Runtime {
         command_path: Option<&[String]>,
         dry_run: bool,
         verbose: bool,
-    ) -> Result<(), String> {
+    ) -> Result<(), NestError> {
         let mut visited = std::collections::HashSet::new();
         let parent_args = HashMap::new(); // Top-level command has no parent args
         let mut context = CommandExecutionContext {
@@ -1443,7 +1692,7 @@ impl Runtime {
         dry_run: bool,
         verbose: bool,
         parent_args: &HashMap<String, String>,
-    ) -> Result<(), String> {
+    ) -> Result<(), NestError> {
         let mut visited = std::collections::HashSet::new();
         let mut context = CommandExecutionContext {
             command,
@@ -1456,4 +1705,211 @@ impl Runtime {
         };
         self.execute_command_with_deps(&mut context)
     }
+
+    /// Produces the values offered by a parameter's `complete.PARAM` source.
+    ///
+    /// `$(command)` sources are run through the shell; any other source names a
+    /// function (looked up from the command's namespace) whose `@return` value
+    /// is used. Values are separated by whitespace.
+    pub fn completion_values(
+        &self,
+        command_path: &[String],
+        source: &str,
+    ) -> Result<Vec<String>, String> {
+        let args = HashMap::new();
+        let env_vars: HashMap<String, String> = std::env::vars().collect();
+        let context = ScriptExecutionContext {
+            env_vars: &env_vars,
+            cwd: None,
+            command_path: Some(command_path),
+            args: &args,
+            dry_run: false,
+            verbose: false,
+            parent_args: &args,
+            hide_output: true,
+            privileged: false,
+            pid_callback: None,
+            output_sink: None,
+        };
+
+        let output = match source.strip_prefix("$(").and_then(|s| s.strip_suffix(')')) {
+            Some(script) => self.evaluate_dynamic_value(script, &context)?,
+            None => {
+                let name = source.trim_end_matches("()");
+                let function = self
+                    .find_function(name, command_path)
+                    .ok_or_else(|| format!("Completion function '{}' not found", name))?;
+                self.execute_function(function, &context)
+                    .map_err(|e| e.to_string())?
+                    .unwrap_or_default()
+            }
+        };
+
+        Ok(output.split_whitespace().map(str::to_string).collect())
+    }
+}
+
+/// Collects the local variables and constants of every command under its
+/// qualified name (`group.VAR`, `group.sub.VAR`).
+///
+/// Constants are added before variables, so a variable wins when a command
+/// defines both under the same name.
+fn collect_namespace_variables(commands: &[Command], prefix: &str, out: &mut Vec<Variable>) {
+    for cmd in commands {
+        let namespace = format!("{}{}", prefix, cmd.name);
+        for constant in &cmd.local_constants {
+            out.push(Variable {
+                name: format!("{}.{}", namespace, constant.name),
+                value: constant.value.clone(),
+            });
+        }
+        for variable in &cmd.local_variables {
+            out.push(Variable {
+                name: format!("{}.{}", namespace, variable.name),
+                value: variable.value.clone(),
+            });
+        }
+        collect_namespace_variables(&cmd.children, &format!("{}.", namespace), out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nestparse::parser::Parser;
+
+    #[test]
+    fn test_dependency_tree() {
+        let content = r#"clean:
+    script: echo clean
+dev:
+    build:
+        depends: clean, lint
+        script: echo build
+    lint:
+        script: echo lint
+    serve:
+        depends: build, dev:serve
+        script: echo serve
+"#;
+        let mut parser = Parser::new(content);
+        let commands = parser.parse().unwrap().commands;
+        let runtime = Runtime::new(commands, Vec::new(), Vec::new(), Vec::new(), None);
+
+        let path = |p: &str| p.split(':').map(str::to_string).collect::<Vec<_>>();
+        let tree = runtime.dependency_tree(&path("dev:serve"));
+        assert_eq!(tree.path, path("dev:serve"));
+        // The self-dependency is dropped instead of recursing forever
+        assert_eq!(tree.children.len(), 1);
+        let build = &tree.children[0];
+        assert_eq!(build.path, path("dev:build"));
+        // A bare name is a sibling, so `clean` resolves under `dev` and is missing
+        let children: Vec<_> = build.children.iter().map(|c| c.path.clone()).collect();
+        assert_eq!(children, vec![path("dev:clean"), path("dev:lint")]);
+        assert!(build.children.iter().all(|c| c.children.is_empty()));
+    }
+
+    #[test]
+    fn test_function_returns_its_return_value() {
+        let content = r#"function targets():
+    echo "not returned" > /dev/null
+    @return debug release
+function nothing():
+    @return
+"#;
+        let mut parser = Parser::new(content);
+        let parsed = parser.parse().unwrap();
+        let runtime = Runtime::new(
+            parsed.commands,
+            parsed.variables,
+            parsed.constants,
+            parsed.functions,
+            None,
+        );
+
+        assert_eq!(
+            runtime.completion_values(&[], "targets()").unwrap(),
+            ["debug", "release"]
+        );
+        assert!(runtime
+            .completion_values(&[], "nothing")
+            .unwrap()
+            .is_empty());
+    }
+
+    struct Recorder(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl Recorder {
+        fn push(&self, event: String) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    impl RuntimeObserver for Recorder {
+        fn command_started(&self, command_path: &[String]) {
+            self.push(format!("start {}", command_path.join(" ")));
+        }
+
+        fn command_finished(&self, command_path: &[String], result: &Result<(), NestError>) {
+            let outcome = if result.is_ok() { "ok" } else { "failed" };
+            self.push(format!("finish {} {}", command_path.join(" "), outcome));
+        }
+
+        fn dependency_scheduled(&self, dependency: &[String], required_by: &[String]) {
+            let (dependency, required_by) = (dependency.join(" "), required_by.join(" "));
+            self.push(format!("depend {} <- {}", dependency, required_by));
+        }
+
+        fn phase_started(&self, command_path: &[String], phase: Phase) {
+            self.push(format!("{} {}", phase.name(), command_path.join(" ")));
+        }
+
+        fn output_line(&self, command_path: &[String], _stream: OutputStream, line: &str) {
+            self.push(format!("{}: {}", command_path.join(" "), line));
+        }
+
+        fn receives_output(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_observers_follow_the_run() {
+        let content = r#"clean:
+    script: echo cleaning
+build:
+    depends: clean
+    before: echo preparing
+    script: exit 2
+    fallback: echo recovering
+"#;
+        let mut parser = Parser::new(content);
+        let commands = parser.parse().unwrap().commands;
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let runtime = Runtime::new(commands, Vec::new(), Vec::new(), Vec::new(), None)
+            .with_observer(Box::new(Recorder(events.clone())));
+
+        let path = vec!["build".to_string()];
+        let build = runtime.find_command(&path).unwrap();
+        runtime
+            .execute_command(build, &HashMap::new(), Some(&path), false, false)
+            .unwrap();
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "start build",
+                "depend clean <- build",
+                "start clean",
+                "script clean",
+                "clean: cleaning",
+                "finish clean ok",
+                "before build",
+                "build: preparing",
+                "script build",
+                "fallback build",
+                "build: recovering",
+                "finish build ok",
+            ]
+        );
+    }
 }
@@ -4,6 +4,7 @@
 //! regex patterns and other rules defined in directives.
 
 use super::template::{TemplateContext, TemplateProcessor};
+use crate::error::NestError;
 use regex::Regex;
 use std::collections::HashMap;
 
@@ -26,8 +27,8 @@ impl RuntimeValidator {
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` if all validations pass,
-    /// `Err(message)` if validation fails.
+    /// Returns `Ok(())` if all validations pass, [`NestError::InvalidArguments`]
+    /// if a value breaks a rule.
     pub fn validate(
         validate_directives: &[(String, String)],
         args: &HashMap<String, String>,
@@ -35,7 +36,12 @@ impl RuntimeValidator {
         tpl_context: &TemplateContext,
         command_path: &[String],
         parent_args: &HashMap<String, String>,
-    ) -> Result<(), String> {
+    ) -> Result<(), NestError> {
+        let invalid = |error: String| NestError::InvalidArguments {
+            command_path: command_path.to_vec(),
+            errors: vec![error],
+        };
+
         for (param_name, rule) in validate_directives {
             // Process templates in the pattern part (allows dynamic rules)
             let processed_pattern =
@@ -69,10 +75,10 @@ impl RuntimeValidator {
             };
 
             let target_value = target_value_result.ok_or_else(|| {
-                format!(
-                    "Validation error: target '{}' not found in arguments or environment",
+                invalid(format!(
+                    "Target '{}' not found in arguments or environment",
                     param_name
-                )
+                ))
             })?;
 
             // Check if it's a membership validation (in ["a", "b"])
@@ -98,14 +104,12 @@ impl RuntimeValidator {
                         .collect();
 
                     if !allowed_values.contains(&target_value) {
-                        let command_str = command_path.join(" ");
-                        return Err(format!(
-                            "❌ Validation error in command 'nest {}':\n   Target '{}' with value '{}' is not in allowed list: [{}]",
-                            command_str,
+                        return Err(invalid(format!(
+                            "Target '{}' with value '{}' is not in allowed list: [{}]",
                             param_name,
                             target_value,
                             allowed_values.join(", ")
-                        ));
+                        )));
                     }
                     continue; // Validation passed for this directive
                 }
@@ -153,10 +157,10 @@ impl RuntimeValidator {
                     match regex {
                         Ok(re) => re,
                         Err(e) => {
-                            return Err(format!(
+                            return Err(NestError::Other(format!(
                                 "Invalid regex pattern in validation rule for '{}': '{}'. Error: {}",
                                 param_name, pattern_part, e
-                            ));
+                            )));
                         }
                     }
                 } else {
@@ -164,10 +168,10 @@ impl RuntimeValidator {
                     match Regex::new(pattern_part) {
                         Ok(re) => re,
                         Err(e) => {
-                            return Err(format!(
+                            return Err(NestError::Other(format!(
                                 "Invalid regex pattern: {}. Error: {}",
                                 pattern_part, e
-                            ))
+                            )))
                         }
                     }
                 }
@@ -176,26 +180,25 @@ impl RuntimeValidator {
                 match Regex::new(pattern_part) {
                     Ok(re) => re,
                     Err(e) => {
-                        return Err(format!(
+                        return Err(NestError::Other(format!(
                             "Invalid regex pattern: {}. Error: {}",
                             pattern_part, e
-                        ))
+                        )))
                     }
                 }
             };
 
             // Validate
             if !pattern.is_match(&target_value) {
-                let command_str = command_path.join(" ");
                 let target_type = if param_name.starts_with('$') {
                     "Environment variable"
                 } else {
                     "Parameter"
                 };
-                return Err(format!(
-                    "❌ Validation error in command 'nest {}':\n   {} '{}' with value '{}' does not match pattern '{}'",
-                    command_str, target_type, param_name, target_value, pattern_part
-                ));
+                return Err(invalid(format!(
+                    "{} '{}' with value '{}' does not match pattern '{}'",
+                    target_type, param_name, target_value, pattern_part
+                )));
             }
         }
 
@@ -0,0 +1,198 @@
+//! Include sources.
+//!
+//! An include can come from the local filesystem, from an `http(s)://` URL,
+//! or from a file inside a git repository (`git+https://host/repo.git@ref#path`,
+//! also written `git:https://host/repo.git@ref#path`).
+//! Includes inside a remote file are resolved relative to that file, so a
+//! library hosted on a web server or in a repository can import its siblings.
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// Prefix of git include references.
+const GIT_PREFIX: &str = "git+";
+
+/// Alternative prefix of git include references.
+const GIT_PREFIX_ALT: &str = "git:";
+
+/// Ref used when a git include does not specify one.
+const DEFAULT_GIT_REF: &str = "HEAD";
+
+/// File used when a git include does not specify a path.
+const DEFAULT_GIT_PATH: &str = "nestfile";
+
+/// Where an included file comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// A file on the local filesystem
+    Local(PathBuf),
+    /// A file served over HTTP(S)
+    Url(String),
+    /// A file inside a git repository at a given ref
+    Git {
+        /// Repository URL (as understood by `git clone`)
+        repo: String,
+        /// Branch, tag or commit
+        reference: String,
+        /// Path of the file inside the repository
+        path: String,
+    },
+}
+
+impl Source {
+    /// Parses an absolute remote include reference.
+    ///
+    /// Returns `Ok(None)` for anything that is not a URL or git reference,
+    /// i.e. for local paths.
+    pub fn parse(spec: &str) -> Result<Option<Source>, String> {
+        if let Some(rest) = spec
+            .strip_prefix(GIT_PREFIX)
+            .or_else(|| spec.strip_prefix(GIT_PREFIX_ALT))
+        {
+            return Self::parse_git(rest).map(Some);
+        }
+        if spec.starts_with("http://") || spec.starts_with("https://") {
+            return Ok(Some(Source::Url(spec.to_string())));
+        }
+        Ok(None)
+    }
+
+    /// Parses the part of a git reference after `git+`: `repo[@ref][#path]`.
+    ///
+    /// Repositories and refs starting with `-` are rejected, since git would
+    /// read them as options.
+    fn parse_git(spec: &str) -> Result<Source, String> {
+        if spec.starts_with('-') {
+            return Err(format!("Invalid git repository in include: {}", spec));
+        }
+
+        let (repo_and_ref, path) = match spec.split_once('#') {
+            Some((head, path)) if !path.is_empty() => (head, path.trim_start_matches('/')),
+            Some((head, _)) => (head, DEFAULT_GIT_PATH),
+            None => (spec, DEFAULT_GIT_PATH),
+        };
+
+        // The ref separator must come after the host, so that `user@host` is kept intact
+        let host_start = repo_and_ref.find("://").map(|i| i + 3).unwrap_or(0);
+        let path_start = repo_and_ref[host_start..]
+            .find('/')
+            .map(|i| host_start + i)
+            .unwrap_or(repo_and_ref.len());
+        let (repo, reference) = match repo_and_ref.rfind('@') {
+            Some(at) if at > path_start => (&repo_and_ref[..at], &repo_and_ref[at + 1..]),
+            _ => (repo_and_ref, DEFAULT_GIT_REF),
+        };
+        if reference.starts_with('-') {
+            return Err(format!("Invalid git ref in include: {}", spec));
+        }
+
+        Ok(Source::Git {
+            repo: repo.to_string(),
+            reference: if reference.is_empty() {
+                DEFAULT_GIT_REF.to_string()
+            } else {
+                reference.to_string()
+            },
+            path: path.to_string(),
+        })
+    }
+
+    /// Returns `true` if the source is not on the local filesystem.
+    pub fn is_remote(&self) -> bool {
+        !matches!(self, Source::Local(_))
+    }
+
+    /// Resolves an include reference found inside this source.
+    ///
+    /// Absolute URLs and git references resolve to themselves. Relative
+    /// references inside a remote source are joined with its location.
+    /// Returns `Ok(None)` for local paths inside a local source, which are
+    /// resolved against the filesystem by the include processor.
+    pub fn resolve(&self, spec: &str) -> Result<Option<Source>, String> {
+        if let Some(source) = Source::parse(spec)? {
+            return Ok(Some(source));
+        }
+
+        match self {
+            Source::Local(_) => Ok(None),
+            _ if spec.contains('*') || spec.ends_with('/') => Err(format!(
+                "Wildcard and directory includes are not supported in remote sources: {} (in {})",
+                spec, self
+            )),
+            Source::Url(base) => Ok(Some(Source::Url(join_url(base, spec)))),
+            Source::Git {
+                repo,
+                reference,
+                path,
+            } => Ok(Some(Source::Git {
+                repo: repo.clone(),
+                reference: reference.clone(),
+                path: join_path(path, spec).trim_start_matches('/').to_string(),
+            })),
+        }
+    }
+
+    /// Returns the key used for circular include detection.
+    pub fn visit_key(&self) -> PathBuf {
+        match self {
+            Source::Local(path) => path.clone(),
+            _ => PathBuf::from(self.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Local(path) => write!(f, "{}", path.display()),
+            Source::Url(url) => write!(f, "{}", url),
+            Source::Git {
+                repo,
+                reference,
+                path,
+            } => write!(f, "{}{}@{}#{}", GIT_PREFIX, repo, reference, path),
+        }
+    }
+}
+
+/// Joins a relative reference with a base URL.
+///
+/// Query strings and fragments of the base are dropped, `.` and `..`
+/// segments are normalized, and references starting with `/` replace
+/// the whole path.
+pub fn join_url(base: &str, relative: &str) -> String {
+    let base = base.split(['?', '#']).next().unwrap_or(base);
+    let scheme_end = base.find("://").map(|i| i + 3).unwrap_or(0);
+    let (origin, path) = match base[scheme_end..].find('/') {
+        Some(i) => base.split_at(scheme_end + i),
+        None => (base, "/"),
+    };
+    format!("{}{}", origin, join_path(path, relative))
+}
+
+/// Joins a relative reference with the directory of a `/`-separated base path.
+fn join_path(base: &str, relative: &str) -> String {
+    let combined = if relative.starts_with('/') {
+        relative.to_string()
+    } else {
+        let dir = match base.rfind('/') {
+            Some(i) => &base[..=i],
+            None => "",
+        };
+        format!("{}{}", dir, relative)
+    };
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in combined.split('/') {
+        match segment {
+            "." | "" => {}
+            ".." => {
+                segments.pop();
+            }
+            s => segments.push(s),
+        }
+    }
+
+    let prefix = if combined.starts_with('/') { "/" } else { "" };
+    format!("{}{}", prefix, segments.join("/"))
+}
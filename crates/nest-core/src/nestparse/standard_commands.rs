@@ -6,11 +6,24 @@
 //! - `doctor`: Diagnose environment issues
 //! - `clean`: Remove temporary files
 //! - `uninstall`: Remove Nest CLI
+//! - `lock update`: Refresh pinned remote includes
+//! - `deps update`: Bump git imports to their newest release tag
+//! - `completion install|uninstall|status`: Manage the installed shell completion
 
 use super::ast::Command;
+use super::completion::{CompletionManager, Shell, ALL_SHELLS};
+use super::deps::bump_git_refs;
+use super::file::read_file_unchecked;
+use super::help::HelpFormatter;
+use super::include::process_includes_with_remote;
+use super::lock::{RemoteMode, RemoteStore};
 use super::output::colors;
 use super::output::OutputFormatter;
-use crate::constants::APP_DESCRIPTION;
+use super::validator::{print_validation_warnings, ValidationError};
+use crate::constants::{
+    APP_DESCRIPTION, FLAG_PRINT, SUBCMD_INSTALL, SUBCMD_STATUS, SUBCMD_UNINSTALL,
+};
+use clap::Command as ClapCommand;
 use std::process;
 
 /// Handles the `--std` flag.
@@ -27,6 +40,12 @@ pub fn handle_std_help() {
     println!("  --uninstall         Remove Nest CLI");
     println!("  --update            Update Nest CLI to the latest version");
     println!("  --init              Initialize a new nestfile");
+    println!("  --offline           Resolve remote imports from the cache only");
+    println!("  lock update         Re-fetch remote imports and refresh Nestfile.lock");
+    println!("  deps update         Bump git imports to their newest release tag");
+    println!("  completion install  Install shell completion (--print to write it to stdout)");
+    println!("  completion status   Show where shell completion is installed");
+    println!("  completion uninstall Remove installed shell completion");
     println!("  --example           Download example nestfiles");
     println!("  --show              Show commands in different formats (requires nestfile)");
     println!("  --version           Show version");
@@ -37,62 +56,223 @@ pub fn handle_std_help() {
 ///
 /// Validates the configuration file and prints a success message if valid.
 /// The actual validation logic is already performed in `main.rs` before
-/// calling this, so if we reach here, it's valid.
-pub fn handle_check(config_path: &std::path::Path) {
+/// calling this, so if we reach here, it's valid. Warnings (such as symbols
+/// of imported namespaces shadowing globals) are printed first.
+pub fn handle_check(config_path: &std::path::Path, warnings: &[ValidationError]) {
+    if !warnings.is_empty() {
+        print_validation_warnings(warnings, config_path);
+    }
     OutputFormatter::success("Configuration file is valid!");
-    println!(
-        "  {}Path:{} {}",
+    OutputFormatter::print(&format!(
+        "  {} {}\n",
         OutputFormatter::help_label("Path:"),
-        colors::RESET,
         OutputFormatter::path(&config_path.display().to_string())
-    );
+    ));
     // TODO: Add more advanced checks here (unused variables, circular dependencies, etc.)
 }
 
-/// Handles the `list` command.
+/// Handles the `lock update` command.
 ///
-/// Lists all available commands in a readable format.
-pub fn handle_list(commands: &[Command]) {
-    println!("{}", APP_DESCRIPTION);
-    println!();
-    println!("Available commands:");
+/// Re-fetches every remote include reachable from the configuration file,
+/// ignoring the pinned hashes, and rewrites `Nestfile.lock` from scratch.
+pub fn handle_lock_update(config_path: &std::path::Path) {
+    match refresh_lockfile(config_path) {
+        Ok(remote) => print_lockfile_summary(&remote),
+        Err(e) => {
+            OutputFormatter::error(&e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Handles the `deps update` command.
+///
+/// Bumps every git import of the configuration file to the newest release
+/// tag of its repository, then refreshes `Nestfile.lock`.
+pub fn handle_deps_update(config_path: &std::path::Path) {
+    let result = read_file_unchecked(config_path)
+        .map_err(|e| format!("Error reading file: {}", e))
+        .and_then(|content| {
+            let (updated, bumps) = bump_git_refs(&content)?;
+            if !bumps.is_empty() {
+                std::fs::write(config_path, updated)
+                    .map_err(|e| format!("Failed to write {}: {}", config_path.display(), e))?;
+            }
+            Ok(bumps)
+        })
+        .and_then(|bumps| refresh_lockfile(config_path).map(|remote| (bumps, remote)));
 
-    for command in commands {
-        print_command(command, 0);
+    match result {
+        Ok((bumps, remote)) => {
+            if bumps.is_empty() {
+                OutputFormatter::info("All git imports are up to date");
+            }
+            for bump in &bumps {
+                OutputFormatter::success(&format!("{}: {} -> {}", bump.repo, bump.from, bump.to));
+            }
+            print_lockfile_summary(&remote);
+        }
+        Err(e) => {
+            OutputFormatter::error(&e);
+            process::exit(1);
+        }
     }
 }
 
-fn print_command(command: &Command, indent: usize) {
-    let padding = " ".repeat(indent * 2);
-    let name = if indent == 0 {
-        format!("{}{}{}", colors::BRIGHT_GREEN, command.name, colors::RESET)
-    } else {
-        format!("{}{}{}", colors::CYAN, command.name, colors::RESET)
+fn refresh_lockfile(config_path: &std::path::Path) -> Result<RemoteStore, String> {
+    let content =
+        read_file_unchecked(config_path).map_err(|e| format!("Error reading file: {}", e))?;
+    let mut remote = RemoteStore::for_config(config_path, RemoteMode::Update)?;
+    let mut visited = std::collections::HashSet::new();
+    process_includes_with_remote(&content, config_path, &mut visited, &mut remote)
+        .map_err(|e| format!("Include error: {}", e))?;
+    remote.save()?;
+    Ok(remote)
+}
+
+fn print_lockfile_summary(remote: &RemoteStore) {
+    OutputFormatter::success(&format!(
+        "Lockfile updated ({} remote include(s) pinned)",
+        remote.resolved_count()
+    ));
+    OutputFormatter::print(&format!(
+        "  {} {}\n",
+        OutputFormatter::help_label("Path:"),
+        OutputFormatter::path(&remote.lock_path().display().to_string())
+    ));
+}
+
+/// Handles the `completion` command.
+///
+/// `args` are the words after `completion`: the action (`install`,
+/// `uninstall` or `status`), an optional shell (detected from `$SHELL`
+/// otherwise) and `--print` for `install`.
+pub fn handle_completion(args: &[String], cli: &mut ClapCommand, commands: &[Command]) {
+    let mut words = args.iter().filter(|a| !a.starts_with('-'));
+    let action = words.next().map(String::as_str).unwrap_or(SUBCMD_STATUS);
+    let shell = match words.next().map(|name| name.parse::<Shell>()).transpose() {
+        Ok(shell) => shell,
+        Err(e) => {
+            OutputFormatter::error(&e);
+            process::exit(1);
+        }
     };
+    let print = args.iter().any(|a| a == &format!("--{}", FLAG_PRINT));
 
-    // Get description from directives
-    let desc = command
-        .directives
-        .iter()
-        .find_map(|d| match d {
-            super::ast::Directive::Desc(s) => Some(s.clone()),
-            _ => None,
+    let result = match action {
+        SUBCMD_INSTALL => resolve_shell(shell).and_then(|shell| {
+            if print {
+                use std::io::Write;
+                std::io::stdout()
+                    .write_all(&CompletionManager::render_script(shell, cli, commands))
+                    .map_err(|e| format!("Failed to write completion script: {}", e))
+            } else {
+                install_completion(shell, cli, commands)
+            }
+        }),
+        SUBCMD_UNINSTALL => resolve_shell(shell).and_then(uninstall_completion),
+        SUBCMD_STATUS => {
+            print_completion_status(shell);
+            Ok(())
+        }
+        other => Err(format!(
+            "Unknown completion action: {}. Expected: {}, {} or {}",
+            other, SUBCMD_INSTALL, SUBCMD_UNINSTALL, SUBCMD_STATUS
+        )),
+    };
+
+    if let Err(e) = result {
+        OutputFormatter::error(&e);
+        process::exit(1);
+    }
+}
+
+fn resolve_shell(shell: Option<Shell>) -> Result<Shell, String> {
+    shell
+        .or_else(CompletionManager::detect_shell)
+        .ok_or_else(|| {
+            "Could not detect your shell. Pass one of: bash, zsh, fish, powershell, elvish"
+                .to_string()
         })
-        .unwrap_or_default();
+}
 
-    if !desc.is_empty() {
-        println!("{}{} - {}", padding, name, desc);
-    } else {
-        println!("{}{} ", padding, name);
+fn install_completion(
+    shell: Shell,
+    cli: &mut ClapCommand,
+    commands: &[Command],
+) -> Result<(), String> {
+    let location = CompletionManager::new()?.install(shell, cli, commands)?;
+    OutputFormatter::success(&format!("Installed {} completion", shell.as_str()));
+    OutputFormatter::print(&format!(
+        "  {} {}\n",
+        OutputFormatter::help_label("Script:"),
+        OutputFormatter::path(&location.script.display().to_string())
+    ));
+    if let Some(rc_file) = &location.rc_file {
+        OutputFormatter::print(&format!(
+            "  {} {}\n",
+            OutputFormatter::help_label("Loaded from:"),
+            OutputFormatter::path(&rc_file.display().to_string())
+        ));
+    }
+    if let Shell::Bash = shell {
+        println!("  Requires the bash-completion package.");
     }
+    println!("  Restart your shell to use it.");
+    Ok(())
+}
+
+fn uninstall_completion(shell: Shell) -> Result<(), String> {
+    let changed = CompletionManager::uninstall(shell)?;
+    if changed.is_empty() {
+        OutputFormatter::info(&format!("No {} completion installed.", shell.as_str()));
+        return Ok(());
+    }
+    for path in &changed {
+        println!("Cleaned: {}", path.display());
+    }
+    OutputFormatter::success(&format!("Removed {} completion", shell.as_str()));
+    Ok(())
+}
 
-    for child in &command.children {
-        if child.name != "default" {
-            print_command(child, indent + 1);
+fn print_completion_status(shell: Option<Shell>) {
+    let shells = match shell {
+        Some(shell) => vec![shell],
+        None => ALL_SHELLS.to_vec(),
+    };
+    for shell in shells {
+        let Some(location) = CompletionManager::install_location(shell) else {
+            continue;
+        };
+        let installed = location.is_installed();
+        OutputFormatter::print(&format!(
+            "{:<11} {} {}\n",
+            shell.as_str(),
+            if installed {
+                format!("{}[INSTALLED]{}", colors::GREEN, colors::RESET)
+            } else {
+                format!("{}[NOT INSTALLED]{}", colors::YELLOW, colors::RESET)
+            },
+            location.script.display()
+        ));
+        if let (true, Some(rc_file)) = (installed, &location.rc_file) {
+            println!("{:<11} loaded from {}", "", rc_file.display());
         }
     }
 }
 
+/// Handles the `list` command.
+///
+/// Lists all available commands as sectioned tables, with subcommands
+/// nested under their group.
+pub fn handle_list(commands: &[Command]) {
+    OutputFormatter::print(&format!(
+        "{}\n\n{}",
+        APP_DESCRIPTION,
+        HelpFormatter::command_table(commands, true)
+    ));
+}
+
 /// Handles the `clean` command.
 ///
 /// Removes temporary files created by Nest.
@@ -152,7 +332,11 @@ pub fn handle_clean() {
 ///
 /// Checks for common issues.
 pub fn handle_doctor() {
-    println!("{}Doctor Check:{}", colors::BRIGHT_CYAN, colors::RESET);
+    OutputFormatter::print(&format!(
+        "{}Doctor Check:{}\n",
+        colors::BRIGHT_CYAN,
+        colors::RESET
+    ));
     println!("----------------------------------------");
 
     // Check OS/Arch
@@ -177,25 +361,28 @@ pub fn handle_doctor() {
                 .split(':')
                 .any(|p| std::path::Path::new(p) == local_bin)
             {
-                println!("PATH: Includes ~/.local/bin {}", check_mark(true));
+                OutputFormatter::print(&format!(
+                    "PATH: Includes ~/.local/bin {}\n",
+                    check_mark(true)
+                ));
             } else {
-                println!("PATH: Missing ~/.local/bin {}", check_mark(false));
-                println!(
-                    "  {}Tip: Add ~/.local/bin to your PATH{}",
+                OutputFormatter::print(&format!(
+                    "PATH: Missing ~/.local/bin {}\n  {}Tip: Add ~/.local/bin to your PATH{}\n",
+                    check_mark(false),
                     colors::YELLOW,
                     colors::RESET
-                );
+                ));
             }
         }
     } else {
-        println!("HOME: Not set {}", check_mark(false));
+        OutputFormatter::print(&format!("HOME: Not set {}\n", check_mark(false)));
     }
 }
 
 fn check_tool(name: &str) {
     let result = std::process::Command::new(name).arg("--version").output();
     let installed = result.is_ok();
-    println!("Tool: {:<10} {}", name, check_mark(installed));
+    OutputFormatter::print(&format!("Tool: {:<10} {}\n", name, check_mark(installed)));
 }
 
 fn check_mark(ok: bool) -> String {
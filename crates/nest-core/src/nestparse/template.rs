@@ -2,10 +2,11 @@
 //!
 //! This module handles replacing placeholders in scripts with actual values.
 //! Supports parameter placeholders ({{param}}), variables ({{VAR}}), constants ({{CONST}}),
-//! and special variables ({{now}}, {{user}}).
+//! namespaced symbols ({{group.VAR}}) and special variables ({{now}}, {{user}}).
 //! Also supports modifiers:
 //! - {{var|sep:","}} - for arrays: replace default separator (space) with a custom one
 //! - {{var|copy}} - for boolean values: copy the argument format (flag -> "--param", true -> "true", false -> "")
+//! - {{var|each:"-e {k}={v}"}} - for maps and arrays: format every entry and join them with spaces
 
 use super::ast::{Constant, Value, Variable};
 use crate::constants::{
@@ -27,6 +28,8 @@ pub struct TemplateContext<'a> {
     pub local_constants: &'a [Constant],
     pub parent_variables: &'a [Variable],
     pub parent_constants: &'a [Constant],
+    /// Group variables and constants under qualified names (`docker.IMAGE`)
+    pub namespace_variables: &'a [Variable],
 }
 
 /// Trait for resolving function calls in templates.
@@ -50,6 +53,7 @@ impl TemplateProcessor {
     /// - `{{param}}` - Replaced with the value from `args` for key "param"
     /// - `{{VAR}}` - Replaced with variable value (can be redefined)
     /// - `{{CONST}}` - Replaced with constant value (cannot be redefined)
+    /// - `{{group.VAR}}` - Replaced with a variable or constant of the `group` namespace
     /// - `{{now}}` - Replaced with current UTC time in RFC3339 format (only if not overridden)
     /// - `{{user}}` - Replaced with the USER environment variable (only if not overridden)
     /// - `{{SYSTEM_ERROR_MESSAGE}}` - Replaced with error message (available in fallback scripts)
@@ -58,6 +62,9 @@ impl TemplateProcessor {
     /// - `{{var|sep:","}}` - For arrays: replace spaces with comma (e.g., "redis celery backend" -> "redis,celery,backend")
     /// - `{{var|rep:" "=>","}}` - Explicit replacement: replace space with comma
     /// - `{{var|copy}}` - For boolean values: copy the argument format (flag -> "--param", true -> "true", false -> "")
+    /// - `{{var|each:"-e {k}={v}"}}` - For maps: format every `key=value` pair; for arrays `{v}` is the item
+    ///
+    /// Map parameters are also available per key as `{{var.key}}`.
     ///
     /// Priority order:
     /// 1. Parameters (from args) - highest priority
@@ -89,7 +96,7 @@ impl TemplateProcessor {
     /// let mut args = HashMap::new();
     /// args.insert("name".to_string(), "world".to_string());
     /// let script = "echo Hello {{name}}!";
-    /// let processed = TemplateProcessor::process(script, &args, &TemplateContext::default(), &std::collections::HashMap::new());
+    /// let processed = TemplateProcessor::process(script, &args, &TemplateContext::default(), &std::collections::HashMap::new(), None);
     /// assert_eq!(processed, "echo Hello world!");
     /// ```
     pub fn process(
@@ -104,6 +111,14 @@ impl TemplateProcessor {
         // Build variable map with priority: local > parent > global > special
         let mut var_map: HashMap<String, String> = HashMap::new();
 
+        // 0. Add namespaced symbols (qualified names never clash with plain ones)
+        for variable in context.namespace_variables {
+            var_map.insert(
+                variable.name.clone(),
+                Self::resolve_value(&variable.value, evaluator),
+            );
+        }
+
         // 1. Add global constants first (lowest priority for constants)
         for constant in context.global_constants {
             var_map.insert(
@@ -291,6 +306,15 @@ impl TemplateProcessor {
                                     // sep modifier: {{var|sep:","}}
                                     let sep = Self::parse_modifier_value(sep_value);
                                     value.replace(" ", &sep)
+                                } else if let Some(each_value) = modifier_part.strip_prefix("each:")
+                                {
+                                    // each modifier: {{var|each:"-e {k}={v}"}}
+                                    let format = Self::parse_modifier_value(each_value);
+                                    Self::entries(var_name, value, &combined_map)
+                                        .iter()
+                                        .map(|(k, v)| format.replace("{k}", k).replace("{v}", v))
+                                        .collect::<Vec<_>>()
+                                        .join(" ")
                                 } else if let Some(rep_value) = modifier_part.strip_prefix("rep:") {
                                     // rep modifier: {{var|rep:"from"=>"to"}}
                                     if let Some((from, to)) = Self::parse_rep_modifier(rep_value) {
@@ -345,6 +369,34 @@ impl TemplateProcessor {
         result
     }
 
+    /// Returns the entries of a map or array argument for the `each` modifier.
+    ///
+    /// Map arguments are stored as `key=value` pairs joined with spaces, with
+    /// each value also stored under `name.key`; the key order is read from the
+    /// joined form and values from the per-key entries, so values may contain
+    /// spaces. Other values are split on spaces and keyed by their index.
+    fn entries(name: &str, value: &str, values: &HashMap<String, String>) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = Vec::new();
+        for token in value.split(' ') {
+            if let Some((key, _)) = token.split_once('=') {
+                if let Some(entry) = values.get(&format!("{}.{}", name, key)) {
+                    if !entries.iter().any(|(k, _)| k == key) {
+                        entries.push((key.to_string(), entry.clone()));
+                    }
+                }
+            }
+        }
+
+        if entries.is_empty() {
+            entries = value
+                .split_whitespace()
+                .enumerate()
+                .map(|(i, item)| (i.to_string(), item.to_string()))
+                .collect();
+        }
+        entries
+    }
+
     /// Parses modifier value, removing quotes if present.
     /// Examples: "," -> ",", "\",\"" -> ","
     fn parse_modifier_value(value: &str) -> String {
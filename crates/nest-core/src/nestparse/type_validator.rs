@@ -2,9 +2,316 @@
 //!
 //! This module validates that command-line arguments match their declared types
 //! before execution, providing clear error messages when types don't match.
+//!
+//! Besides the basic `str`, `bool`, `num` and `arr` types, parameters can use:
+//! - `int`, `int(1..10)`, `int(1..)`, `int(..10)` - integers with an optional inclusive range
+//! - `enum("staging", "production")` - one of a fixed set of values
+//! - `path`, `file`, `dir` - paths (`file` and `dir` must exist)
+//! - `url` - URLs with a scheme (`https://...`)
+//! - `duration` - durations like `500ms`, `30s`, `1h30m`
+//! - `semver` - semantic versions like `1.2.3` or `2.0.0-rc.1`
+//! - `arr<int>` - arrays whose items have the given type
+//! - `map` - `key=value` pairs, passed as `--set key=value` one or more times
 
-use super::ast::{ParamKind, Parameter};
+use super::ast::{ParamKind, Parameter, Value};
 use std::collections::HashMap;
+use std::path::Path;
+
+/// A parameter type as declared in a command signature.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamType {
+    /// Any string
+    Str,
+    /// `true` or `false`
+    Bool,
+    /// Any number
+    Num,
+    /// Integer, optionally within an inclusive range
+    Int { min: Option<i64>, max: Option<i64> },
+    /// One of a fixed set of values
+    Enum(Vec<String>),
+    /// Filesystem path (not checked for existence)
+    Path,
+    /// Existing file
+    File,
+    /// Existing directory
+    Dir,
+    /// URL with a scheme
+    Url,
+    /// Duration such as `30s` or `1h30m`
+    Duration,
+    /// Semantic version
+    Semver,
+    /// Array of items, of any string (`arr`) or of the given type (`arr<int>`)
+    Arr(Option<Box<ParamType>>),
+    /// `key=value` pairs
+    Map,
+}
+
+impl ParamType {
+    /// Parses a type annotation such as `int(1..10)` or `enum("a", "b")`.
+    pub fn parse(spec: &str) -> Result<ParamType, String> {
+        let spec = spec.trim();
+
+        if let Some(inner) = spec.strip_prefix("arr<").and_then(|s| s.strip_suffix('>')) {
+            let item = ParamType::parse(inner)?;
+            if matches!(item, ParamType::Arr(_) | ParamType::Bool | ParamType::Map) {
+                return Err(format!("'{}' cannot be used as an array item type", inner));
+            }
+            return Ok(ParamType::Arr(Some(Box::new(item))));
+        }
+
+        if let Some((name, args)) = spec.split_once('(') {
+            let args = args
+                .strip_suffix(')')
+                .ok_or_else(|| format!("Missing closing parenthesis in type '{}'", spec))?;
+            return match name.trim() {
+                "int" => Self::parse_range(args),
+                "enum" => {
+                    let values: Vec<String> = args
+                        .split(',')
+                        .map(|v| v.trim().trim_matches('"').trim_matches('\'').to_string())
+                        .filter(|v| !v.is_empty())
+                        .collect();
+                    if values.is_empty() {
+                        Err("enum type needs at least one value".to_string())
+                    } else {
+                        Ok(ParamType::Enum(values))
+                    }
+                }
+                other => Err(format!("Type '{}' does not take arguments", other)),
+            };
+        }
+
+        match spec {
+            "str" => Ok(ParamType::Str),
+            "bool" => Ok(ParamType::Bool),
+            "num" => Ok(ParamType::Num),
+            "int" => Ok(ParamType::Int {
+                min: None,
+                max: None,
+            }),
+            "path" => Ok(ParamType::Path),
+            "file" => Ok(ParamType::File),
+            "dir" => Ok(ParamType::Dir),
+            "url" => Ok(ParamType::Url),
+            "duration" => Ok(ParamType::Duration),
+            "semver" => Ok(ParamType::Semver),
+            "arr" => Ok(ParamType::Arr(None)),
+            "map" => Ok(ParamType::Map),
+            other => Err(format!("Unknown type '{}'", other)),
+        }
+    }
+
+    /// Parses the `min..max` part of `int(min..max)`.
+    fn parse_range(range: &str) -> Result<ParamType, String> {
+        let (min, max) = range
+            .split_once("..")
+            .ok_or_else(|| format!("Invalid range '{}'. Expected 'min..max'", range))?;
+        let max = max.strip_prefix('=').unwrap_or(max);
+        let bound = |s: &str| -> Result<Option<i64>, String> {
+            let s = s.trim();
+            if s.is_empty() {
+                Ok(None)
+            } else {
+                s.parse()
+                    .map(Some)
+                    .map_err(|_| format!("Invalid range bound '{}'", s))
+            }
+        };
+        let (min, max) = (bound(min)?, bound(max)?);
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                return Err(format!("Empty range {}..{}", min, max));
+            }
+        }
+        Ok(ParamType::Int { min, max })
+    }
+
+    /// Returns `true` for `arr` and `arr<T>`.
+    pub fn is_array(&self) -> bool {
+        matches!(self, ParamType::Arr(_))
+    }
+
+    /// Returns `true` for types that collect every occurrence of a flag (`arr`, `map`).
+    pub fn is_repeatable(&self) -> bool {
+        matches!(self, ParamType::Arr(_) | ParamType::Map)
+    }
+
+    /// Returns the allowed values of an enum type.
+    pub fn possible_values(&self) -> Option<&[String]> {
+        match self {
+            ParamType::Enum(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Checks a value against the type, returning the reason it was rejected.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            ParamType::Str | ParamType::Path => Ok(()),
+            ParamType::Bool => match value {
+                "true" | "false" => Ok(()),
+                _ => Err("Expected 'true' or 'false'.".to_string()),
+            },
+            ParamType::Num => value
+                .parse::<f64>()
+                .map(|_| ())
+                .map_err(|_| "Expected a numeric value (e.g., 42, 3.14, -10).".to_string()),
+            ParamType::Int { min, max } => {
+                let n: i64 = value
+                    .parse()
+                    .map_err(|_| "Expected an integer (e.g., 42, -10).".to_string())?;
+                let range = format!(
+                    "{}..{}",
+                    min.map(|m| m.to_string()).unwrap_or_default(),
+                    max.map(|m| m.to_string()).unwrap_or_default()
+                );
+                if min.is_some_and(|min| n < min) || max.is_some_and(|max| n > max) {
+                    Err(format!("Expected an integer in the range {}.", range))
+                } else {
+                    Ok(())
+                }
+            }
+            ParamType::Enum(values) => {
+                if values.iter().any(|v| v == value) {
+                    Ok(())
+                } else {
+                    Err(format!("Expected one of: {}.", values.join(", ")))
+                }
+            }
+            ParamType::File => {
+                if Path::new(value).is_file() {
+                    Ok(())
+                } else {
+                    Err("File does not exist.".to_string())
+                }
+            }
+            ParamType::Dir => {
+                if Path::new(value).is_dir() {
+                    Ok(())
+                } else {
+                    Err("Directory does not exist.".to_string())
+                }
+            }
+            ParamType::Url => {
+                let valid = value.split_once("://").is_some_and(|(scheme, rest)| {
+                    !scheme.is_empty()
+                        && scheme
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                        && !rest.is_empty()
+                        && !rest.chars().any(char::is_whitespace)
+                });
+                if valid {
+                    Ok(())
+                } else {
+                    Err("Expected a URL (e.g., https://example.com).".to_string())
+                }
+            }
+            ParamType::Duration => {
+                if is_duration(value) {
+                    Ok(())
+                } else {
+                    Err("Expected a duration (e.g., 500ms, 30s, 5m, 1h30m, 2d).".to_string())
+                }
+            }
+            ParamType::Semver => {
+                if is_semver(value) {
+                    Ok(())
+                } else {
+                    Err("Expected a semantic version (e.g., 1.2.3, 2.0.0-rc.1).".to_string())
+                }
+            }
+            ParamType::Arr(None) => Ok(()),
+            ParamType::Arr(Some(item)) => value
+                .split([',', ' '])
+                .filter(|v| !v.is_empty())
+                .try_for_each(|v| {
+                    item.validate(v)
+                        .map_err(|reason| format!("Invalid item '{}'. {}", v, reason))
+                }),
+            ParamType::Map => parse_map_entry(value).map(|_| ()),
+        }
+    }
+
+    /// Checks whether a default value written in the Nestfile fits the type.
+    ///
+    /// Existence of `file`/`dir` defaults is not checked here, since they
+    /// may be created before the command runs.
+    pub fn accepts_default(&self, value: &Value) -> bool {
+        match (self, value) {
+            (ParamType::Bool, Value::Bool(_)) => true,
+            (ParamType::Num, Value::Number(_)) => true,
+            (ParamType::Int { .. }, Value::Number(n)) => {
+                n.fract() == 0.0 && self.validate(&(*n as i64).to_string()).is_ok()
+            }
+            (
+                ParamType::Str | ParamType::Path | ParamType::File | ParamType::Dir,
+                Value::String(_),
+            ) => true,
+            (
+                ParamType::Enum(_) | ParamType::Url | ParamType::Duration | ParamType::Semver,
+                Value::String(s),
+            ) => self.validate(s).is_ok(),
+            (ParamType::Arr(None), Value::Array(_)) => true,
+            (ParamType::Map, Value::Array(items)) => parse_map(items).is_ok(),
+            (ParamType::Arr(Some(item)), Value::Array(items)) => items.iter().all(|v| {
+                matches!(**item, ParamType::File | ParamType::Dir) || item.validate(v).is_ok()
+            }),
+            _ => false,
+        }
+    }
+}
+
+/// Returns `true` for durations made of `<number><unit>` parts (`ms`, `s`, `m`, `h`, `d`).
+fn is_duration(value: &str) -> bool {
+    let mut rest = value;
+    if rest.is_empty() {
+        return false;
+    }
+    while !rest.is_empty() {
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return false;
+        }
+        rest = &rest[digits..];
+        let unit = ["ms", "s", "m", "h", "d"]
+            .into_iter()
+            .find(|unit| rest.starts_with(unit));
+        match unit {
+            Some(unit) => rest = &rest[unit.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Returns `true` for `MAJOR.MINOR.PATCH[-prerelease][+build]` versions.
+fn is_semver(value: &str) -> bool {
+    let (version, build) = match value.split_once('+') {
+        Some((version, build)) => (version, Some(build)),
+        None => (value, None),
+    };
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+    let identifiers_ok = |s: &str| {
+        s.split('.').all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+    };
+    let parts: Vec<&str> = core.split('.').collect();
+    parts.len() == 3
+        && parts.iter().all(|p| {
+            !p.is_empty()
+                && p.chars().all(|c| c.is_ascii_digit())
+                && (p.len() == 1 || !p.starts_with('0'))
+        })
+        && pre.is_none_or(identifiers_ok)
+        && build.is_none_or(identifiers_ok)
+}
 
 /// Validates that a string value matches the expected parameter type.
 ///
@@ -97,10 +404,21 @@ pub fn validate_argument_type(value: &str, param: &Parameter) -> Result<String,
             Ok(unquoted.to_string())
         }
 
-        _ => Err(format!(
-            "Unknown parameter type '{}' for parameter '{}'",
-            param.param_type, param.name
-        )),
+        other => match ParamType::parse(other) {
+            Ok(param_type) => param_type
+                .validate(value)
+                .map(|_| value.to_string())
+                .map_err(|reason| {
+                    format!(
+                        "Invalid value '{}' for parameter '{}'. {}",
+                        value, param.name, reason
+                    )
+                }),
+            Err(_) => Err(format!(
+                "Unknown parameter type '{}' for parameter '{}'",
+                param.param_type, param.name
+            )),
+        },
     }
 }
 
@@ -143,6 +461,14 @@ pub fn validate_all_arguments(
                     ));
                 }
             }
+        } else if let Some((map_name, _)) = arg_name.split_once('.') {
+            // Entries of map parameters (`set.key`) are kept for templates
+            if parameters
+                .iter()
+                .any(|p| p.name == map_name && p.param_type == "map")
+            {
+                validated_args.insert(arg_name.clone(), arg_value.clone());
+            }
         }
         // If parameter not found, it might be from a different context, skip it
     }
@@ -169,31 +495,81 @@ pub fn validate_all_arguments(
     }
 }
 
-/// Parses an array string into a vector of strings.
+/// Parses the occurrences of an array argument into a vector of strings.
 ///
-/// Arrays are typically passed as comma-separated strings.
-/// This function handles parsing and validation.
+/// A single occurrence is treated as a comma-separated list (`--tags=a,b,c`).
+/// Repeated occurrences (`--tag a --tag b`) are taken as-is, so items may
+/// contain commas.
 ///
 /// # Arguments
 ///
-/// * `value` - The comma-separated string value
+/// * `values` - The values passed for the argument, one per occurrence
 ///
 /// # Returns
 ///
 /// Returns a vector of trimmed string values.
-#[allow(dead_code)]
-pub fn parse_array(value: &str) -> Vec<String> {
-    if value.trim().is_empty() {
-        return Vec::new();
-    }
+pub fn parse_array(values: &[String]) -> Vec<String> {
+    let items: Vec<&str> = match values {
+        [single] => single.split(',').collect(),
+        _ => values.iter().map(String::as_str).collect(),
+    };
 
-    value
-        .split(',')
+    items
+        .into_iter()
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty())
         .collect()
 }
 
+/// Parses the occurrences of a map argument into ordered `(key, value)` pairs.
+///
+/// Each occurrence is a `key=value` pair. A single occurrence may also hold
+/// several comma-separated pairs (`--set a=1,b=2`) as long as every part has
+/// a key. Later occurrences of a key replace earlier ones.
+///
+/// # Returns
+///
+/// Returns the pairs in the order their keys first appeared,
+/// or `Err(message)` for an entry without a valid key.
+pub fn parse_map(values: &[String]) -> Result<Vec<(String, String)>, String> {
+    let entries: Vec<&str> = match values {
+        [single] if single.split(',').all(|part| parse_map_entry(part).is_ok()) => {
+            single.split(',').collect()
+        }
+        _ => values.iter().map(String::as_str).collect(),
+    };
+
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    for entry in entries {
+        let (key, value) = parse_map_entry(entry)?;
+        match pairs.iter_mut().find(|(k, _)| k == key) {
+            Some(pair) => pair.1 = value.to_string(),
+            None => pairs.push((key.to_string(), value.to_string())),
+        }
+    }
+    Ok(pairs)
+}
+
+/// Splits a `key=value` map entry, checking that the key is usable in `{{name.key}}`.
+fn parse_map_entry(entry: &str) -> Result<(&str, &str), String> {
+    let (key, value) = entry
+        .trim()
+        .split_once('=')
+        .ok_or_else(|| format!("Expected 'key=value', got '{}'.", entry.trim()))?;
+    let key = key.trim();
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err(format!(
+            "Invalid key '{}'. Keys may contain letters, digits, '_', '-' and '.'.",
+            key
+        ));
+    }
+    Ok((key, value))
+}
+
 /// Formats an array for display in error messages.
 #[allow(dead_code)]
 pub fn format_array_for_display(items: &[String]) -> String {
@@ -203,3 +579,77 @@ pub fn format_array_for_display(items: &[String]) -> String {
         format!("[{}]", items.join(", "))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_validate_types() {
+        let int = ParamType::parse("int(1..10)").unwrap();
+        assert!(int.validate("10").is_ok());
+        assert!(int.validate("11").is_err());
+        assert!(int.validate("1.5").is_err());
+        assert!(ParamType::parse("int(5..)")
+            .unwrap()
+            .validate("100")
+            .is_ok());
+
+        let env = ParamType::parse("enum(\"staging\", \"production\")").unwrap();
+        assert_eq!(
+            env.possible_values().unwrap(),
+            &["staging".to_string(), "production".to_string()]
+        );
+        assert!(env.validate("dev").is_err());
+
+        let ports = ParamType::parse("arr<int(1..65535)>").unwrap();
+        assert!(ports.is_array());
+        assert!(ports.validate("80,443").is_ok());
+        assert!(ports.validate("80 70000").is_err());
+
+        let duration = ParamType::Duration;
+        assert!(duration.validate("1h30m").is_ok());
+        assert!(duration.validate("500ms").is_ok());
+        assert!(duration.validate("30").is_err());
+
+        let semver = ParamType::Semver;
+        assert!(semver.validate("1.2.3").is_ok());
+        assert!(semver.validate("2.0.0-rc.1+build.5").is_ok());
+        assert!(semver.validate("1.2").is_err());
+        assert!(semver.validate("01.2.3").is_err());
+
+        assert!(ParamType::Url.validate("https://example.com").is_ok());
+        assert!(ParamType::Url.validate("example.com").is_err());
+
+        assert!(ParamType::parse("int(10..1)").is_err());
+        assert!(ParamType::parse("float").is_err());
+        assert!(ParamType::parse("arr<bool>").is_err());
+    }
+
+    #[test]
+    fn test_parse_repeated_arrays_and_maps() {
+        let values = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(parse_array(&values(&["a,b, c"])), vec!["a", "b", "c"]);
+        assert_eq!(parse_array(&values(&["a,b", "c"])), vec!["a,b", "c"]);
+
+        let map = ParamType::parse("map").unwrap();
+        assert!(map.is_repeatable());
+        assert!(map.validate("KEY=a=b").is_ok());
+        assert!(map.validate("novalue").is_err());
+
+        assert_eq!(
+            parse_map(&values(&["a=1,b=2"])).unwrap(),
+            vec![("a".into(), "1".into()), ("b".into(), "2".into())]
+        );
+        assert_eq!(
+            parse_map(&values(&["list=x,y", "a=1", "list=z"])).unwrap(),
+            vec![("list".into(), "z".into()), ("a".into(), "1".into())]
+        );
+        assert_eq!(
+            parse_map(&values(&["opts=x,y"])).unwrap(),
+            vec![("opts".into(), "x,y".into())]
+        );
+        assert!(parse_map(&values(&["=1"])).is_err());
+    }
+}
@@ -3,9 +3,13 @@
 //! This module validates the parsed configuration and provides
 //! detailed error messages with line numbers and helpful suggestions.
 
-use super::ast::{Command, Directive};
-use super::output::colors;
-use crate::constants::{RESERVED_SHORT_OPTIONS, RESERVED_WORDS};
+use super::ast::{
+    find_command, Command, Constant, Directive, Function, ParamKind, Value, Variable,
+};
+use super::merge::{extends_target, MergeConflict};
+use super::output::{colors, OutputFormatter};
+use super::type_validator::ParamType;
+use crate::constants::{FLAG_PRESET, RESERVED_SHORT_OPTIONS, RESERVED_WORDS};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
@@ -68,6 +72,9 @@ pub fn validate_commands(
         );
     }
 
+    validate_extends(commands, &[], commands, &mut errors);
+    validate_command_aliases(commands, &[], &mut errors);
+
     if errors.is_empty() {
         Ok(())
     } else {
@@ -75,6 +82,316 @@ pub fn validate_commands(
     }
 }
 
+/// Checks that every `extends` reference names an existing command
+/// and that inheritance chains do not loop.
+fn validate_extends(
+    commands: &[Command],
+    parent_path: &[String],
+    root: &[Command],
+    errors: &mut Vec<ValidationError>,
+) {
+    for command in commands {
+        let mut current_path = parent_path.to_vec();
+        current_path.push(command.name.clone());
+
+        if let Some(reference) = &command.extends {
+            match extends_target(reference, parent_path, root) {
+                None => errors.push(ValidationError {
+                    line: 1,
+                    column: None,
+                    message: format!(
+                        "Command '{}' extends unknown command '{}'",
+                        command.name, reference
+                    ),
+                    suggestion: Some(
+                        "Use the name of a sibling or top-level command, or an absolute path like 'group:command'"
+                            .to_string(),
+                    ),
+                    command_path: current_path.clone(),
+                }),
+                Some(target) => {
+                    if extends_cycle(&current_path, target, root) {
+                        errors.push(ValidationError {
+                            line: 1,
+                            column: None,
+                            message: format!(
+                                "Command '{}' extends itself through '{}'",
+                                command.name, reference
+                            ),
+                            suggestion: Some(
+                                "Remove 'extends' from one of the commands in the chain".to_string(),
+                            ),
+                            command_path: current_path.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        validate_extends(&command.children, &current_path, root, errors);
+    }
+}
+
+/// Checks that command aliases don't clash with sibling commands or their aliases.
+fn validate_command_aliases(
+    commands: &[Command],
+    parent_path: &[String],
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut taken: HashMap<&str, &str> = commands
+        .iter()
+        .map(|c| (c.name.as_str(), c.name.as_str()))
+        .collect();
+
+    for command in commands {
+        let mut current_path = parent_path.to_vec();
+        current_path.push(command.name.clone());
+
+        for directive in &command.directives {
+            let Directive::Alias(aliases) = directive else {
+                continue;
+            };
+            for alias in aliases {
+                if parent_path.is_empty() && RESERVED_WORDS.contains(&alias.as_str()) {
+                    errors.push(ValidationError {
+                        line: 1,
+                        column: None,
+                        message: format!(
+                            "Alias '{}' of command '{}' is a reserved word",
+                            alias, command.name
+                        ),
+                        suggestion: Some(format!(
+                            "Use a different alias. Reserved words: {}",
+                            RESERVED_WORDS.join(", ")
+                        )),
+                        command_path: current_path.clone(),
+                    });
+                }
+                if let Some(owner) = taken.insert(alias, &command.name) {
+                    errors.push(ValidationError {
+                        line: 1,
+                        column: None,
+                        message: format!(
+                            "Alias '{}' of command '{}' is already used by command '{}'",
+                            alias, command.name, owner
+                        ),
+                        suggestion: Some(
+                            "Aliases must be unique among the command's siblings and their aliases"
+                                .to_string(),
+                        ),
+                        command_path: current_path.clone(),
+                    });
+                }
+            }
+        }
+
+        validate_command_aliases(&command.children, &current_path, errors);
+    }
+}
+
+/// Follows an `extends` chain from `target` and reports whether it leads back to `start`.
+fn extends_cycle(start: &[String], mut target: Vec<String>, root: &[Command]) -> bool {
+    let mut seen = HashSet::new();
+    loop {
+        if target == start {
+            return true;
+        }
+        if !seen.insert(target.clone()) {
+            // A loop that does not involve `start` is reported for its own members
+            return false;
+        }
+        let Some(command) = find_command(root, &target) else {
+            return false;
+        };
+        let Some(reference) = &command.extends else {
+            return false;
+        };
+        let Some(next) = extends_target(reference, &target[..target.len() - 1], root) else {
+            return false;
+        };
+        target = next;
+    }
+}
+
+/// Turns implicit command redefinitions into warnings.
+///
+/// A command defined twice without `override` or `append` silently replaces
+/// the earlier definition, which usually means two files clash by accident.
+pub fn check_merge_conflicts(conflicts: &[MergeConflict]) -> Vec<ValidationError> {
+    conflicts
+        .iter()
+        .map(|conflict| {
+            let name = conflict.command_path.join(" ");
+            let describe = |source: &Option<std::path::PathBuf>| {
+                source
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "<unknown>".to_string())
+            };
+            let (first, second) = (describe(&conflict.first), describe(&conflict.second));
+            let message = if first == second {
+                format!("Command '{}' is defined twice in {}", name, first)
+            } else {
+                format!(
+                    "Command '{}' is defined in both {} and {}",
+                    name, first, second
+                )
+            };
+            let last = conflict.command_path.last().cloned().unwrap_or_default();
+            ValidationError {
+                line: 1,
+                column: None,
+                message,
+                suggestion: Some(format!(
+                    "The later definition wins. Write 'override {}:' to replace it on purpose, or 'append {}:' to add to it",
+                    last, last
+                )),
+                command_path: conflict.command_path.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Checks imported namespaces for symbols that collide with other definitions.
+///
+/// Variables, constants and functions of a file imported `into` a group live
+/// in that group's namespace (`{{docker.IMAGE}}`, `docker.build_image()`).
+/// Inside the group they shadow global symbols of the same name, which is
+/// reported here together with functions that are defined more than once.
+///
+/// # Arguments
+///
+/// * `commands` - Parsed commands
+/// * `variables` - Global variables
+/// * `constants` - Global constants
+/// * `functions` - All functions (namespaced ones use qualified names)
+/// * `file_path` - Path to the configuration file (to tell imported commands apart)
+///
+/// # Returns
+///
+/// Returns the collisions found, as warnings that do not stop execution.
+pub fn check_symbol_collisions(
+    commands: &[Command],
+    variables: &[Variable],
+    constants: &[Constant],
+    functions: &[Function],
+    file_path: &Path,
+) -> Vec<ValidationError> {
+    let mut warnings = Vec::new();
+
+    let mut seen_functions = HashSet::new();
+    for function in functions {
+        if !seen_functions.insert(function.name.as_str()) {
+            warnings.push(ValidationError {
+                line: 1,
+                column: None,
+                message: format!("Function '{}' is defined more than once", function.name),
+                suggestion: Some(
+                    "The first definition is used. Rename one of them or import the file into a group"
+                        .to_string(),
+                ),
+                command_path: Vec::new(),
+            });
+        }
+    }
+
+    let globals: HashSet<&str> = variables
+        .iter()
+        .map(|v| v.name.as_str())
+        .chain(constants.iter().map(|c| c.name.as_str()))
+        .collect();
+    let main_file = file_path
+        .canonicalize()
+        .unwrap_or_else(|_| file_path.to_path_buf());
+
+    for command in commands {
+        check_namespace_recursive(command, &[], &globals, functions, &main_file, &mut warnings);
+    }
+
+    warnings
+}
+
+fn check_namespace_recursive(
+    command: &Command,
+    parent_path: &[String],
+    globals: &HashSet<&str>,
+    functions: &[Function],
+    main_file: &Path,
+    warnings: &mut Vec<ValidationError>,
+) {
+    let mut current_path = parent_path.to_vec();
+    current_path.push(command.name.clone());
+    let namespace = current_path.join(".");
+
+    let imported = command
+        .source_file
+        .as_deref()
+        .is_some_and(|source| source != main_file);
+
+    if imported {
+        let symbols = command
+            .local_variables
+            .iter()
+            .map(|v| ("Variable", v.name.as_str()))
+            .chain(
+                command
+                    .local_constants
+                    .iter()
+                    .map(|c| ("Constant", c.name.as_str())),
+            );
+        for (kind, name) in symbols {
+            if globals.contains(name) {
+                warnings.push(ValidationError {
+                    line: 1,
+                    column: None,
+                    message: format!(
+                        "{} '{}' of namespace '{}' shadows the global '{}'",
+                        kind, name, namespace, name
+                    ),
+                    suggestion: Some(format!(
+                        "Inside '{}' {{{{{}}}}} is the imported value. Use {{{{{}.{}}}}} to refer to it from elsewhere",
+                        namespace, name, namespace, name
+                    )),
+                    command_path: current_path.clone(),
+                });
+            }
+        }
+
+        let prefix = format!("{}.", namespace);
+        for function in functions {
+            let Some(name) = function.name.strip_prefix(&prefix) else {
+                continue;
+            };
+            if !name.contains('.') && functions.iter().any(|f| f.name == name) {
+                warnings.push(ValidationError {
+                    line: 1,
+                    column: None,
+                    message: format!(
+                        "Function '{}' of namespace '{}' shadows the global function '{}'",
+                        name, namespace, name
+                    ),
+                    suggestion: Some(format!(
+                        "Inside '{}' {}() calls the imported function. Use {}.{}() to call it from elsewhere",
+                        namespace, name, namespace, name
+                    )),
+                    command_path: current_path.clone(),
+                });
+            }
+        }
+    }
+
+    for child in &command.children {
+        check_namespace_recursive(
+            child,
+            &current_path,
+            globals,
+            functions,
+            main_file,
+            warnings,
+        );
+    }
+}
+
 fn validate_command_recursive(
     command: &Command,
     parent_path: &[String],
@@ -170,21 +487,26 @@ fn validate_command_recursive(
         }
 
         // Validate parameter type (wildcards always use internal type "arr")
-        if !matches!(param.param_type.as_str(), "str" | "bool" | "num" | "arr") {
+        let param_type = ParamType::parse(&param.param_type);
+        if let Err(reason) = &param_type {
             errors.push(ValidationError {
                 line: 1,
                 column: None,
                 message: format!(
-                    "Invalid parameter type '{}' for parameter '{}'",
-                    param.param_type, param.name
+                    "Invalid parameter type '{}' for parameter '{}': {}",
+                    param.param_type, param.name, reason
+                ),
+                suggestion: Some(
+                    "Valid types are: str, bool, num, arr, arr<T>, map, int, int(min..max), enum(\"a\", \"b\"), path, file, dir, url, duration, semver"
+                        .to_string(),
                 ),
-                suggestion: Some("Valid types are: str, bool, num, arr".to_string()),
                 command_path: current_path.clone(),
             });
         }
+        let param_type = param_type.ok();
 
-        // Validate that arr type can only be used in named arguments (exclude wildcard parameters)
-        if param.param_type == "arr"
+        // Validate that arr and map types can only be used in named arguments (exclude wildcard parameters)
+        if param_type.as_ref().is_some_and(ParamType::is_repeatable)
             && !matches!(param.kind, super::ast::ParamKind::Wildcard { .. })
             && !param.is_named
         {
@@ -192,12 +514,12 @@ fn validate_command_recursive(
                 line: 1,
                 column: None,
                 message: format!(
-                    "Array type 'arr' can only be used in named arguments for parameter '{}' in command '{}'",
-                    param.name, full_name
+                    "Type '{}' can only be used in named arguments for parameter '{}' in command '{}'",
+                    param.param_type, param.name, full_name
                 ),
                 suggestion: Some(format!(
-                    "Use '!{}' prefix to make it a named argument (e.g., '!{}: arr')",
-                    param.name, param.name
+                    "Use '!{}' prefix to make it a named argument (e.g., '!{}: {}')",
+                    param.name, param.name, param.param_type
                 )),
                 command_path: current_path.clone(),
             });
@@ -297,8 +619,8 @@ fn validate_command_recursive(
         }
 
         // Validate default value type matches parameter type
-        if let Some(default) = &param.default {
-            if !value_matches_type(default, &param.param_type) {
+        if let (Some(default), Some(param_type)) = (&param.default, &param_type) {
+            if !param_type.accepts_default(default) {
                 errors.push(ValidationError {
                     line: 1,
                     column: None,
@@ -325,13 +647,37 @@ fn validate_command_recursive(
         match directive {
             Directive::Script(..) => has_script = true,
             Directive::Desc(_) => {}
+            Directive::Alias(_) => {}
+            Directive::Category(_) => {}
+            Directive::HelpOrder(_) => {}
             Directive::Depends(_, _) => {}
             Directive::Before(..) => {}
             Directive::After(..) => {}
             Directive::Fallback(..) => {}
             Directive::Finally(..) => {}
             Directive::Validate(..) => {}
+            Directive::Complete(target, _) => {
+                if !command.parameters.iter().any(|p| &p.name == target) {
+                    errors.push(ValidationError {
+                        line: 1,
+                        column: None,
+                        message: format!(
+                            "Completion source for unknown parameter '{}' in command '{}'",
+                            target, full_name
+                        ),
+                        suggestion: Some(
+                            "Use 'complete.PARAM' with the name of one of the command's parameters"
+                                .to_string(),
+                        ),
+                        command_path: current_path.clone(),
+                    });
+                }
+            }
+            Directive::Preset(name, values) => {
+                validate_preset(name, values, command, &full_name, &current_path, errors);
+            }
             Directive::Privileged(_) => {}
+            Directive::Private(_) => {}
             Directive::RequireConfirm(_) => {}
             Directive::Cwd(path) => {
                 cwd_paths.push(path.clone());
@@ -346,6 +692,27 @@ fn validate_command_recursive(
         }
     }
 
+    // `--preset` selects a preset, so it can't also be a parameter
+    let has_presets = command
+        .directives
+        .iter()
+        .any(|d| matches!(d, Directive::Preset(..)));
+    if has_presets && command.parameters.iter().any(|p| p.name == FLAG_PRESET) {
+        errors.push(ValidationError {
+            line: 1,
+            column: None,
+            message: format!(
+                "Command '{}' defines presets and a parameter named '{}'",
+                full_name, FLAG_PRESET
+            ),
+            suggestion: Some(format!(
+                "Rename the parameter; '--{}' selects a preset on this command",
+                FLAG_PRESET
+            )),
+            command_path: current_path.clone(),
+        });
+    }
+
     // Check for multiple cwd directives
     if cwd_paths.len() > 1 {
         errors.push(ValidationError {
@@ -467,18 +834,69 @@ fn validate_command_recursive(
     }
 }
 
-fn value_matches_type(value: &super::ast::Value, param_type: &str) -> bool {
-    matches!(
-        (value, param_type),
-        (super::ast::Value::String(_), "str")
-            | (super::ast::Value::Bool(_), "bool")
-            | (super::ast::Value::Number(_), "num")
-            | (super::ast::Value::Array(_), "arr")
-    )
+/// Checks a preset against the parameters of its command: every value has
+/// to name a parameter and fit its type.
+fn validate_preset(
+    name: &str,
+    values: &[(String, Value)],
+    command: &Command,
+    full_name: &str,
+    current_path: &[String],
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut error = |message: String, suggestion: String| {
+        errors.push(ValidationError {
+            line: 1,
+            column: None,
+            message,
+            suggestion: Some(suggestion),
+            command_path: current_path.to_vec(),
+        });
+    };
+
+    for (param_name, value) in values {
+        let param = command
+            .parameters
+            .iter()
+            .find(|p| &p.name == param_name && matches!(p.kind, ParamKind::Normal));
+        let Some(param) = param else {
+            let available: Vec<&str> = command
+                .parameters
+                .iter()
+                .filter(|p| matches!(p.kind, ParamKind::Normal))
+                .map(|p| p.name.as_str())
+                .collect();
+            error(
+                format!(
+                    "Preset '{}' of command '{}' sets unknown parameter '{}'",
+                    name, full_name, param_name
+                ),
+                format!("Available parameters: {}", available.join(", ")),
+            );
+            continue;
+        };
+
+        if let Ok(param_type) = ParamType::parse(&param.param_type) {
+            if !param_type.accepts_default(value) {
+                error(
+                    format!(
+                        "Value of '{}' in preset '{}' doesn't match type '{}'",
+                        param_name, name, param.param_type
+                    ),
+                    format!("Expected a {} value, but got {:?}", param.param_type, value),
+                );
+            }
+        }
+    }
 }
 
 /// Formats and prints validation errors in a user-friendly way.
 pub fn print_validation_errors(errors: &[ValidationError], file_path: &Path) {
+    OutputFormatter::eprint(&format_validation_errors(errors, file_path));
+}
+
+/// Formats validation errors the way [`print_validation_errors`] prints them.
+pub fn format_validation_errors(errors: &[ValidationError], file_path: &Path) -> String {
     use std::fmt::Write;
 
     let mut output = String::new();
@@ -577,5 +995,65 @@ pub fn print_validation_errors(errors: &[ValidationError], file_path: &Path) {
     )
     .expect("Failed to format validation error footer message");
 
-    eprint!("{}", output);
+    output
+}
+
+/// Formats and prints validation warnings.
+///
+/// Warnings point at configurations that work but probably do not do what
+/// was intended, so they are printed without stopping execution.
+pub fn print_validation_warnings(warnings: &[ValidationError], file_path: &Path) {
+    use std::fmt::Write;
+
+    let mut output = String::new();
+
+    writeln!(
+        output,
+        "{}⚠️  Found {} warning(s) in {}{}",
+        colors::YELLOW,
+        warnings.len(),
+        file_path.display(),
+        colors::RESET
+    )
+    .expect("Failed to format validation warning header");
+
+    for (idx, warning) in warnings.iter().enumerate() {
+        writeln!(
+            output,
+            "{}[{}]{} {}",
+            colors::YELLOW,
+            idx + 1,
+            colors::RESET,
+            warning.message
+        )
+        .expect("Failed to format validation warning message");
+
+        if !warning.command_path.is_empty() {
+            writeln!(
+                output,
+                "   {}Command:{} {}nest {}{}",
+                colors::GRAY,
+                colors::RESET,
+                colors::BRIGHT_BLUE,
+                warning.command_path.join(" "),
+                colors::RESET
+            )
+            .expect("Failed to format command path in validation warning");
+        }
+
+        if let Some(suggestion) = &warning.suggestion {
+            writeln!(
+                output,
+                "   {}💡 Suggestion:{} {}{}{}",
+                colors::BRIGHT_CYAN,
+                colors::RESET,
+                colors::GRAY,
+                suggestion,
+                colors::RESET
+            )
+            .expect("Failed to format suggestion in validation warning");
+        }
+    }
+
+    OutputFormatter::eprint(&output);
 }
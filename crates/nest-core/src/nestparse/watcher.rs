@@ -4,9 +4,13 @@
 //! and trigger callbacks. It handles debouncing to prevent multiple triggers
 //! for a single change event.
 
-use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
-use std::sync::mpsc::channel;
-use std::time::Duration;
+use super::executor::OutputStream;
+use super::output::is_terminal;
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
 
 /// Configuration for the file watcher.
 pub struct WatcherConfig {
@@ -14,6 +18,9 @@ pub struct WatcherConfig {
     pub patterns: Vec<String>,
     /// Duration to wait before triggering the callback (debounce)
     pub debounce_ms: u64,
+    /// Whether to clear the terminal before each re-run; off when stdout
+    /// carries an event stream
+    pub clear_screen: bool,
 }
 
 impl Default for WatcherConfig {
@@ -21,6 +28,7 @@ impl Default for WatcherConfig {
         Self {
             patterns: Vec::new(),
             debounce_ms: 100,
+            clear_screen: true,
         }
     }
 }
@@ -72,8 +80,9 @@ where
         }
     }
 
-    println!("Watcher started. Waiting for changes...");
-    println!("Press Ctrl+C to stop.");
+    // Status lines go to stderr so that stdout only carries command output
+    eprintln!("Watcher started. Waiting for changes...");
+    eprintln!("Press Ctrl+C to stop.");
 
     // Initial run
     if let Err(e) = callback() {
@@ -96,8 +105,11 @@ where
                 last_event_time = now;
 
                 // Clear terminal
-                print!("\x1B[2J\x1B[1;1H");
-                println!("Change detected. Restarting...");
+                // Clearing the screen only makes sense on a terminal
+                if config.clear_screen && is_terminal(OutputStream::Stdout) {
+                    print!("\x1B[2J\x1B[1;1H");
+                }
+                eprintln!("Change detected. Restarting...");
                 
                 if let Err(e) = callback() {
                     eprintln!("Error during execution: {}", e);
@@ -108,3 +120,107 @@ where
         }
     }
 }
+
+/// Watches a set of files without blocking, for callers that run their own
+/// event loop (the TUI).
+///
+/// The parent directories are watched rather than the files, so editors that
+/// save by writing a new file and renaming it over the old one are noticed.
+pub struct FileWatcher {
+    _watcher: RecommendedWatcher,
+    rx: Receiver<notify::Result<Event>>,
+    files: HashSet<PathBuf>,
+    debounce: Duration,
+    /// Time of the latest change not reported yet
+    pending: Option<Instant>,
+}
+
+impl FileWatcher {
+    /// Starts watching `files`, which are compared by canonical path.
+    pub fn new(files: &[PathBuf], debounce_ms: u64) -> Result<Self, String> {
+        let (tx, rx) = channel();
+        let mut watcher = RecommendedWatcher::new(tx, Config::default())
+            .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+        let files: HashSet<PathBuf> = files
+            .iter()
+            .map(|file| file.canonicalize().unwrap_or_else(|_| file.clone()))
+            .collect();
+        let dirs: HashSet<&std::path::Path> = files.iter().filter_map(|f| f.parent()).collect();
+        for dir in dirs {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Failed to watch '{}': {}", dir.display(), e))?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            files,
+            debounce: Duration::from_millis(debounce_ms),
+            pending: None,
+        })
+    }
+
+    /// Returns `true` once the watched files changed and then stayed
+    /// unchanged for the debounce time, so a save is reported once.
+    pub fn changed(&mut self) -> bool {
+        while let Ok(event) = self.rx.try_recv() {
+            let Ok(event) = event else {
+                continue;
+            };
+            if !matches!(event.kind, EventKind::Access(_))
+                && event.paths.iter().any(|path| self.files.contains(path))
+            {
+                self.pending = Some(Instant::now());
+            }
+        }
+
+        match self.pending {
+            Some(at) if at.elapsed() >= self.debounce => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Polls `watcher` for up to five seconds.
+    fn wait_for_change(watcher: &mut FileWatcher) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if watcher.changed() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    #[test]
+    fn test_file_watcher_reports_watched_files_only() {
+        let dir = std::env::temp_dir().join(format!("nest-watcher-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let nestfile = dir.join("nestfile");
+        std::fs::write(&nestfile, "build:\n    script: echo b\n").unwrap();
+
+        let mut watcher = FileWatcher::new(std::slice::from_ref(&nestfile), 50).unwrap();
+        std::fs::write(dir.join("notes.txt"), "unrelated").unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(!watcher.changed());
+
+        std::fs::write(&nestfile, "build:\n    script: echo c\n").unwrap();
+        assert!(wait_for_change(&mut watcher));
+        // One save is reported once
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(!watcher.changed());
+
+        assert!(FileWatcher::new(&[dir.join("missing/nestfile")], 50).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
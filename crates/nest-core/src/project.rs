@@ -0,0 +1,235 @@
+//! Loading a Nestfile into a project.
+//!
+//! Every front end needs the same pipeline before it can do anything: find
+//! the config, read it, resolve includes, parse, merge duplicate commands and
+//! validate. [`Project::load`] runs it once for the `nest` binary, the TUI and
+//! embedders alike, and reports failures as a [`NestError`].
+
+use crate::error::NestError;
+use crate::nestparse::ast::{Command, Constant, Function, Variable};
+use crate::nestparse::file::read_file_unchecked;
+use crate::nestparse::include::process_includes_with_remote;
+use crate::nestparse::lock::{RemoteMode, RemoteStore};
+use crate::nestparse::merge::{merge_commands, MergeConflict};
+use crate::nestparse::parser::{ParseError, Parser};
+use crate::nestparse::path::find_config_file;
+use crate::nestparse::validator::validate_commands;
+use std::path::{Path, PathBuf};
+
+/// Marker the include processor puts before the content of each file.
+const SOURCE_MARKER: &str = "# @source: ";
+
+/// How [`Project::load`] resolves and checks the configuration.
+#[derive(Debug, Clone, Copy)]
+pub struct LoadOptions {
+    remote_mode: RemoteMode,
+    validate: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            remote_mode: RemoteMode::Default,
+            validate: true,
+        }
+    }
+}
+
+impl LoadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How remote includes are fetched (`--offline` uses [`RemoteMode::Offline`]).
+    pub fn remote_mode(mut self, mode: RemoteMode) -> Self {
+        self.remote_mode = mode;
+        self
+    }
+
+    /// Whether the commands are validated after parsing (on by default).
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+}
+
+/// Which file each line of the combined content (the Nestfile with its
+/// includes spliced in) came from.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    /// First line (1-based) of each run of lines and the file they came from
+    ranges: Vec<(usize, PathBuf)>,
+}
+
+impl SourceMap {
+    fn new(content: &str) -> Self {
+        let ranges = content
+            .lines()
+            .enumerate()
+            .filter_map(|(idx, line)| {
+                let path = line.strip_prefix(SOURCE_MARKER)?.trim();
+                (!path.is_empty()).then(|| (idx + 1, PathBuf::from(path)))
+            })
+            .collect();
+        Self { ranges }
+    }
+
+    /// The file line `line` (1-based, as in [`ParseError`]) of the combined
+    /// content came from.
+    pub fn source_of(&self, line: usize) -> Option<&Path> {
+        let idx = self.ranges.partition_point(|(start, _)| *start <= line);
+        idx.checked_sub(1).map(|idx| self.ranges[idx].1.as_path())
+    }
+
+    /// Every local file the project was read from: the Nestfile and its
+    /// includes, sorted. Remote includes are left out.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self
+            .ranges
+            .iter()
+            .map(|(_, path)| path.clone())
+            .filter(|path| path.is_file())
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+/// A loaded Nestfile: its merged commands and top-level symbols.
+#[derive(Debug, Clone)]
+pub struct Project {
+    /// Path of the Nestfile, as given or found
+    pub path: PathBuf,
+    pub commands: Vec<Command>,
+    pub variables: Vec<Variable>,
+    pub constants: Vec<Constant>,
+    pub functions: Vec<Function>,
+    /// Commands redefined without `override` or `append`
+    pub conflicts: Vec<MergeConflict>,
+    pub sources: SourceMap,
+}
+
+impl Project {
+    /// The Nestfile at `path`, or the one in the current directory with `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NestError::NotFound`] or [`NestError::NotAFile`].
+    pub fn locate(path: Option<&Path>) -> Result<PathBuf, NestError> {
+        match path {
+            Some(path) if !path.exists() => Err(NestError::NotFound(Some(path.to_path_buf()))),
+            Some(path) if !path.is_file() => Err(NestError::NotAFile(path.to_path_buf())),
+            Some(path) => Ok(path.to_path_buf()),
+            None => find_config_file().ok_or(NestError::NotFound(None)),
+        }
+    }
+
+    /// Finds, reads, parses, merges and (unless turned off) validates a Nestfile
+    /// with its includes.
+    ///
+    /// `path` is the Nestfile; with `None` it is looked up in the current directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first step of the pipeline that failed.
+    pub fn load(path: Option<&Path>, options: LoadOptions) -> Result<Self, NestError> {
+        let path = Self::locate(path)?;
+        let content = read_file_unchecked(&path)
+            .map_err(|e| NestError::Io(format!("Error reading file: {}", e)))?;
+
+        let mut remote =
+            RemoteStore::for_config(&path, options.remote_mode).map_err(NestError::Io)?;
+        let mut visited = std::collections::HashSet::new();
+        let processed = process_includes_with_remote(&content, &path, &mut visited, &mut remote)
+            .map_err(NestError::Include)?;
+        remote.save().map_err(NestError::Io)?;
+
+        let root = path.canonicalize().unwrap_or_else(|_| path.clone());
+        let content = format!("{}{}\n{}", SOURCE_MARKER, root.display(), processed);
+        let sources = SourceMap::new(&content);
+
+        let parsed = Parser::new(&content)
+            .parse()
+            .map_err(|error| NestError::Parse {
+                source: sources
+                    .source_of(parse_error_line(&error))
+                    .map(Path::to_path_buf),
+                error,
+            })?;
+        let commands = merge_commands(parsed.commands);
+
+        if options.validate {
+            if let Err(errors) = validate_commands(&commands, &path) {
+                return Err(NestError::Validation { path, errors });
+            }
+        }
+
+        Ok(Self {
+            path,
+            commands,
+            variables: parsed.variables,
+            constants: parsed.constants,
+            functions: parsed.functions,
+            conflicts: parsed.conflicts,
+            sources,
+        })
+    }
+}
+
+fn parse_error_line(error: &ParseError) -> usize {
+    match error {
+        ParseError::UnexpectedEndOfFile(line)
+        | ParseError::InvalidSyntax(_, line)
+        | ParseError::InvalidIndent(line)
+        | ParseError::DeprecatedSyntax(_, line) => *line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_load_tracks_sources() {
+        let dir = std::env::temp_dir().join(format!("nest-project-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("tasks.nest"), "lint:\n    script: echo lint\n").unwrap();
+        let root = dir.join("nestfile");
+        fs::write(
+            &root,
+            "import * from tasks.nest\n\nbuild:\n    script: echo build\n",
+        )
+        .unwrap();
+
+        let project = Project::load(Some(&root), LoadOptions::new()).unwrap();
+        let names: Vec<&str> = project.commands.iter().map(|c| c.name.as_str()).collect();
+        assert!(names.contains(&"lint") && names.contains(&"build"));
+        assert_eq!(
+            project.sources.files(),
+            [
+                dir.join("nestfile").canonicalize().unwrap(),
+                dir.join("tasks.nest").canonicalize().unwrap()
+            ]
+        );
+
+        fs::write(
+            dir.join("tasks.nest"),
+            "lint:\n    script: echo lint\n\n@var X = 1\n",
+        )
+        .unwrap();
+        match Project::load(Some(&root), LoadOptions::new()) {
+            Err(NestError::Parse { source, .. }) => {
+                assert_eq!(source, Some(dir.join("tasks.nest").canonicalize().unwrap()))
+            }
+            other => panic!("expected a parse error, got {:?}", other.map(|p| p.path)),
+        }
+
+        assert!(matches!(
+            Project::load(Some(&dir.join("missing")), LoadOptions::new()),
+            Err(NestError::NotFound(Some(_)))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,402 @@
+//! The argument form of the selected command.
+//!
+//! Each parameter becomes a typed field (a toggle for `bool`, a number input,
+//! a choice for `enum`, a list for `arr`, `map` and wildcards), prefilled with
+//! its default. The form is turned into the command line that launches it,
+//! passing only what differs from the defaults (or from the chosen preset).
+
+use nest_core::nestparse::ast::{ParamKind, Parameter, Value};
+use nest_core::nestparse::type_validator::{parse_array, ParamType};
+
+/// How a field is edited.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldKind {
+    /// `bool`: Enter flips it
+    Toggle,
+    /// `enum(...)`: Enter moves to the next value
+    Choice(Vec<String>),
+    /// `num` and `int`: only number characters can be typed
+    Number,
+    /// `arr`, `map` and wildcards: a list of items
+    List,
+    /// Everything else
+    Text,
+}
+
+/// One parameter of the form.
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub param: Parameter,
+    pub kind: FieldKind,
+    param_type: Option<ParamType>,
+    /// Current value of a non-list field
+    pub value: String,
+    /// Current items of a list field
+    pub items: Vec<String>,
+    /// What the command gets when the field is left alone (default or preset value)
+    baseline: String,
+    baseline_items: Vec<String>,
+}
+
+impl Field {
+    fn new(param: &Parameter) -> Field {
+        let param_type = ParamType::parse(&param.param_type).ok();
+        let kind = match (&param.kind, &param_type) {
+            (ParamKind::Wildcard { .. }, _) => FieldKind::List,
+            (_, Some(ParamType::Bool)) => FieldKind::Toggle,
+            (_, Some(ParamType::Enum(values))) => FieldKind::Choice(values.clone()),
+            (_, Some(ParamType::Num | ParamType::Int { .. })) => FieldKind::Number,
+            (_, Some(ty)) if ty.is_repeatable() => FieldKind::List,
+            _ => FieldKind::Text,
+        };
+
+        let mut field = Field {
+            param: param.clone(),
+            kind,
+            param_type,
+            value: String::new(),
+            items: Vec::new(),
+            baseline: String::new(),
+            baseline_items: Vec::new(),
+        };
+        match &param.default {
+            // Computed when the command runs; nothing to prefill
+            Some(Value::Dynamic(_)) | None => {
+                if field.kind == FieldKind::Toggle {
+                    field.set_baseline(&Value::Bool(false));
+                }
+            }
+            Some(default) => field.set_baseline(default),
+        }
+        field
+    }
+
+    /// Makes `value` both the current value and the one the command gets
+    /// without the field being passed.
+    fn set_baseline(&mut self, value: &Value) {
+        match value {
+            Value::Array(items) => {
+                self.baseline_items = items.clone();
+                self.baseline = items.join(",");
+            }
+            other => {
+                self.baseline = other.to_string_unquoted();
+                self.baseline_items = parse_array(std::slice::from_ref(&self.baseline));
+            }
+        }
+        self.value = self.baseline.clone();
+        self.items = self.baseline_items.clone();
+    }
+
+    /// Whether the field has to be passed on the command line.
+    pub fn changed(&self) -> bool {
+        match self.kind {
+            FieldKind::List => self.items != self.baseline_items,
+            _ => self.value != self.baseline,
+        }
+    }
+
+    /// Whether `c` can be typed into the field.
+    pub fn accepts(&self, c: char) -> bool {
+        match (&self.kind, &self.param_type) {
+            (FieldKind::Number, Some(ParamType::Int { .. })) => c.is_ascii_digit() || c == '-',
+            (FieldKind::Number, _) => c.is_ascii_digit() || matches!(c, '-' | '.'),
+            _ => true,
+        }
+    }
+
+    /// Flips a toggle or moves a choice to its next value.
+    pub fn cycle(&mut self) {
+        match &self.kind {
+            FieldKind::Toggle => {
+                let on = self.value == "true";
+                self.value = (!on).to_string();
+            }
+            FieldKind::Choice(values) => {
+                let next = values
+                    .iter()
+                    .position(|v| v == &self.value)
+                    .map_or(0, |i| (i + 1) % values.len());
+                self.value = values[next].clone();
+            }
+            _ => {}
+        }
+    }
+
+    /// The reason the current value doesn't fit the parameter's type.
+    pub fn error(&self) -> Option<String> {
+        let ty = self.param_type.as_ref()?;
+        match self.kind {
+            FieldKind::List => {
+                let item_type = match ty {
+                    ParamType::Arr(Some(item)) => Some(item.as_ref()),
+                    _ => None,
+                };
+                self.items.iter().find_map(|item| {
+                    if *ty == ParamType::Map && !item.contains('=') {
+                        return Some(format!("'{}': expected key=value", item));
+                    }
+                    let error = item_type?.validate(item).err()?;
+                    Some(format!("'{}': {}", item, error))
+                })
+            }
+            FieldKind::Toggle | FieldKind::Choice(_) => None,
+            _ if self.value.is_empty() => None,
+            _ => ty.validate(&self.value).err(),
+        }
+    }
+
+    /// The field's value as it is shown in the form.
+    pub fn display_value(&self) -> String {
+        match self.kind {
+            FieldKind::List => self.items.join(", "),
+            _ => self.value.clone(),
+        }
+    }
+
+    /// The values a positional field takes on the command line.
+    fn positional_values(&self) -> Vec<String> {
+        match (&self.kind, &self.param.kind) {
+            (FieldKind::List, ParamKind::Wildcard { .. }) => self.items.clone(),
+            (FieldKind::List, _) => vec![self.items.join(",")],
+            _ => vec![self.value.clone()],
+        }
+    }
+}
+
+/// The typed argument form of one command.
+#[derive(Debug, Clone, Default)]
+pub struct ArgForm {
+    /// Path of the command the form belongs to
+    pub command_path: Vec<String>,
+    pub fields: Vec<Field>,
+    /// Preset the form was filled from, passed as `@name`
+    pub preset: Option<String>,
+}
+
+impl ArgForm {
+    pub fn new(command_path: Vec<String>, parameters: &[Parameter]) -> ArgForm {
+        ArgForm {
+            command_path,
+            fields: parameters.iter().map(Field::new).collect(),
+            preset: None,
+        }
+    }
+
+    /// Refills the form from a preset, or from the defaults with `None`.
+    pub fn apply_preset(&mut self, preset: Option<(&str, &[(String, Value)])>) {
+        for field in &mut self.fields {
+            *field = Field::new(&field.param);
+        }
+        self.preset = preset.map(|(name, values)| {
+            for (param, value) in values {
+                if let Some(field) = self.fields.iter_mut().find(|f| &f.param.name == param) {
+                    field.set_baseline(value);
+                }
+            }
+            name.to_string()
+        });
+    }
+
+    /// Keeps what was typed into `previous`, the form of the same command
+    /// before a reload, in fields that still have the same name and type.
+    pub fn carry_over(&mut self, previous: &ArgForm) {
+        for field in &mut self.fields {
+            let old = previous.fields.iter().find(|old| {
+                old.param.name == field.param.name && old.param.param_type == field.param.param_type
+            });
+            if let Some(old) = old.filter(|old| old.changed()) {
+                field.value = old.value.clone();
+                field.items = old.items.clone();
+            }
+        }
+    }
+
+    /// The arguments following the command path.
+    ///
+    /// Named fields are passed only when changed; positionals are passed up
+    /// to the last changed one, since an earlier one can't be skipped.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(preset) = &self.preset {
+            args.push(format!("@{}", preset));
+        }
+
+        for field in self
+            .fields
+            .iter()
+            .filter(|f| f.param.is_named && f.changed())
+        {
+            let flag = format!("--{}", field.param.name);
+            match field.kind {
+                FieldKind::Toggle if field.value == "true" => args.push(flag),
+                FieldKind::Toggle => args.push(format!("{}=false", flag)),
+                FieldKind::List => {
+                    for item in &field.items {
+                        args.push(flag.clone());
+                        args.push(item.clone());
+                    }
+                }
+                // `--n -5` would read -5 as a flag
+                _ if field.value.starts_with('-') => args.push(format!("{}={}", flag, field.value)),
+                _ => {
+                    args.push(flag);
+                    args.push(field.value.clone());
+                }
+            }
+        }
+
+        let positionals: Vec<&Field> = self.fields.iter().filter(|f| !f.param.is_named).collect();
+        if let Some(last) = positionals.iter().rposition(|f| f.changed()) {
+            let values: Vec<String> = positionals[..=last]
+                .iter()
+                .flat_map(|f| f.positional_values())
+                .collect();
+            if values.iter().any(|v| v.starts_with('-')) {
+                args.push("--".to_string());
+            }
+            args.extend(values);
+        }
+        args
+    }
+
+    /// The command line that runs the form, quoted so it splits back into
+    /// the same arguments.
+    pub fn command_line(&self) -> String {
+        self.command_path
+            .iter()
+            .cloned()
+            .chain(self.args())
+            .map(|arg| shell_quote(&arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The first field whose value doesn't fit its type, with the error.
+    pub fn first_error(&self) -> Option<(&Field, String)> {
+        self.fields
+            .iter()
+            .find_map(|field| field.error().map(|error| (field, error)))
+    }
+}
+
+/// Quotes an argument for a POSIX shell when it needs it.
+pub fn shell_quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:=@,+%^".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::split_args;
+
+    fn param(name: &str, param_type: &str, is_named: bool, default: Option<Value>) -> Parameter {
+        Parameter {
+            name: name.to_string(),
+            alias: None,
+            param_type: param_type.to_string(),
+            default,
+            is_named,
+            kind: ParamKind::Normal,
+            prompt: false,
+            secret: false,
+            env: None,
+        }
+    }
+
+    #[test]
+    fn test_command_line_passes_changed_fields() {
+        let mut form = ArgForm::new(
+            vec!["deploy".to_string()],
+            &[
+                param(
+                    "target",
+                    "str",
+                    false,
+                    Some(Value::String("dev".to_string())),
+                ),
+                param("region", "str", false, None),
+                param("force", "bool", true, None),
+                param("tags", "arr", true, None),
+                param("note", "str", true, None),
+            ],
+        );
+        assert_eq!(form.command_line(), "deploy");
+
+        form.fields[1].value = "eu west".to_string();
+        form.fields[2].cycle();
+        form.fields[3].items = vec!["a".to_string(), "b".to_string()];
+        form.fields[4].value = "it's".to_string();
+        let line = form.command_line();
+        assert_eq!(
+            line,
+            r"deploy --force --tags a --tags b --note 'it'\''s' dev 'eu west'"
+        );
+        assert_eq!(
+            split_args(&line).unwrap(),
+            [
+                "deploy", "--force", "--tags", "a", "--tags", "b", "--note", "it's", "dev",
+                "eu west"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_carry_over_keeps_fields_by_name_and_type() {
+        let path = vec!["deploy".to_string()];
+        let mut previous = ArgForm::new(
+            path.clone(),
+            &[
+                param("target", "str", true, None),
+                param("count", "int", true, None),
+                param("tags", "arr", true, None),
+                param("mode", "str", true, None),
+                param("gone", "str", true, None),
+            ],
+        );
+        previous.fields[0].value = "prod".to_string();
+        previous.fields[1].value = "3".to_string();
+        previous.fields[2].items = vec!["a".to_string()];
+        previous.fields[4].value = "x".to_string();
+
+        let mut form = ArgForm::new(
+            path,
+            &[
+                param("target", "str", true, None),
+                param("count", "str", true, None),
+                param("tags", "arr", true, None),
+                param("mode", "str", true, Some(Value::String("fast".to_string()))),
+            ],
+        );
+        form.carry_over(&previous);
+
+        assert_eq!(form.fields[0].value, "prod");
+        // The type changed, so the old value is dropped
+        assert_eq!(form.fields[1].value, "");
+        assert_eq!(form.fields[2].items, ["a"]);
+        // Left alone before the reload, so the new default applies
+        assert_eq!(form.fields[3].value, "fast");
+        assert_eq!(form.command_line(), "deploy --target prod --tags a");
+    }
+
+    #[test]
+    fn test_field_errors() {
+        let mut count = Field::new(&param("count", "int(1..5)", true, None));
+        assert_eq!(count.kind, FieldKind::Number);
+        assert!(!count.accepts('.'));
+        count.value = "9".to_string();
+        assert!(count.error().is_some());
+        count.value = "3".to_string();
+        assert!(count.error().is_none());
+
+        let mut ports = Field::new(&param("ports", "arr<int>", true, None));
+        assert_eq!(ports.kind, FieldKind::List);
+        ports.items = vec!["80".to_string(), "http".to_string()];
+        assert!(ports.error().unwrap().starts_with("'http'"));
+    }
+}
@@ -0,0 +1,168 @@
+//! History and favorites of one Nestfile, kept between sessions.
+//!
+//! Each Nestfile gets its own JSON file under the local data directory,
+//! keyed by a hash of its canonical path.
+
+use nest_core::nestparse::lock::hash_content;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Invocations kept per Nestfile; older ones are dropped.
+const MAX_ENTRIES: usize = 500;
+
+/// How a recorded run ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Succeeded,
+    /// Failed, with the exit code of the script when there was one
+    Failed(Option<i32>),
+    Cancelled,
+}
+
+/// One past invocation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Command line as typed, without the leading `nest`
+    pub command_line: String,
+    /// Path of the command it ran, e.g. `db migrate`
+    pub command: String,
+    /// When the run started, in seconds since the Unix epoch
+    pub started: u64,
+    pub duration_ms: u64,
+    pub outcome: Outcome,
+}
+
+/// Favorites and history of one Nestfile.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProjectState {
+    /// Pinned command paths
+    #[serde(default)]
+    pub favorites: Vec<String>,
+    /// Newest first
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl ProjectState {
+    /// Loads the state of `nestfile`, starting empty when there is none yet
+    /// or it can't be read.
+    pub fn load(nestfile: &Path) -> ProjectState {
+        let nestfile = nestfile
+            .canonicalize()
+            .unwrap_or_else(|_| nestfile.to_path_buf());
+        let path = dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("nest")
+            .join("projects")
+            .join(format!(
+                "{}.json",
+                &hash_content(&nestfile.to_string_lossy())[..16]
+            ));
+
+        let mut state: ProjectState = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        state.path = Some(path);
+        state
+    }
+
+    /// Writes the state back; failures only cost the history, so they are ignored.
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(content) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, content);
+        }
+    }
+
+    /// Adds a finished invocation at the top of the history.
+    pub fn record(&mut self, entry: HistoryEntry) {
+        self.history.insert(0, entry);
+        self.history.truncate(MAX_ENTRIES);
+        self.save();
+    }
+
+    pub fn is_favorite(&self, command: &str) -> bool {
+        self.favorites.iter().any(|f| f == command)
+    }
+
+    /// Pins or unpins a command, returning whether it is now a favorite.
+    pub fn toggle_favorite(&mut self, command: &str) -> bool {
+        let pinned = match self.favorites.iter().position(|f| f == command) {
+            Some(pos) => {
+                self.favorites.remove(pos);
+                false
+            }
+            None => {
+                self.favorites.push(command.to_string());
+                true
+            }
+        };
+        self.save();
+        pinned
+    }
+
+    /// How often and how recently `command` ran: every run counts, recent
+    /// ones more (1 within 4 hours, down to 0.1 after a month).
+    pub fn frecency(&self, command: &str, now: u64) -> f64 {
+        self.history
+            .iter()
+            .filter(|entry| entry.command == command)
+            .map(|entry| match now.saturating_sub(entry.started) / 3600 {
+                0..4 => 1.0,
+                4..24 => 0.7,
+                24..168 => 0.5,
+                168..720 => 0.3,
+                _ => 0.1,
+            })
+            .sum()
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(command: &str, started: u64) -> HistoryEntry {
+        HistoryEntry {
+            command_line: command.to_string(),
+            command: command.to_string(),
+            started,
+            duration_ms: 0,
+            outcome: Outcome::Succeeded,
+        }
+    }
+
+    #[test]
+    fn test_frecency_weights_recent_runs() {
+        let now = 10_000_000;
+        let state = ProjectState {
+            history: vec![
+                entry("build", now - 60),
+                entry("test", now - 3 * 86_400),
+                entry("test", now - 4 * 86_400),
+                entry("build", now - 60 * 86_400),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(state.frecency("build", now), 1.1);
+        assert_eq!(state.frecency("test", now), 1.0);
+        assert_eq!(state.frecency("deploy", now), 0.0);
+    }
+}
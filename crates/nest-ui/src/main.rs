@@ -4,16 +4,45 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use nest_core::nestparse::ast::Command;
-use nest_core::nestparse::validator::{print_validation_errors, validate_commands};
+use nest_core::nestparse::cli::CliGenerator;
+use nest_core::nestparse::command_handler::CommandHandler;
+use nest_core::nestparse::directives::DirectiveResolver;
+use nest_core::nestparse::validator::print_validation_errors;
+use nest_core::{LoadOptions, NestError, Project};
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
     Frame, Terminal,
 };
-use std::{error::Error, io, path::Path, process};
+use std::{
+    error::Error,
+    io,
+    path::{Path, PathBuf},
+    process,
+    time::Duration,
+};
+
+mod form;
+mod history;
+mod runner;
+mod search;
+
+use form::{ArgForm, FieldKind};
+use history::{HistoryEntry, Outcome, ProjectState};
+use nest_core::nestparse::executor::OutputStream;
+use nest_core::nestparse::runtime::{DependencyNode, Runtime};
+use nest_core::nestparse::watcher::FileWatcher;
+use runner::{CommandStatus, Nestfile, Run, RunStatus};
+
+/// How often the screen refreshes while waiting for keys (live output, timers).
+const TICK: Duration = Duration::from_millis(50);
+/// Lines moved by PageUp/PageDown in the output pane.
+const OUTPUT_PAGE: usize = 10;
+/// Quiet time after a Nestfile change before it is reloaded.
+const RELOAD_DEBOUNCE_MS: u64 = 150;
 
 enum InputMode {
     Normal,
@@ -29,6 +58,14 @@ enum ViewMode {
     History,
 }
 
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Screen {
+    Browser,
+    Dashboard,
+    /// One task's output over the whole screen
+    TaskOutput,
+}
+
 #[derive(PartialEq, Clone, Copy, Debug)]
 enum Focus {
     CommandList,
@@ -47,7 +84,11 @@ struct App {
     flat_commands: Vec<(String, Command)>, // Cache for Flat View
     breadcrumbs: Vec<String>,              // Function/Command names path
     selection_history: Vec<usize>,         // To restore selection when going up
-    args_map: std::collections::HashMap<String, String>,
+    form: ArgForm,                         // Argument form of the selected command
+    form_error: Option<String>,            // Why the form can't be launched as it is
+    // Parse and `validate` checks of the form, without running anything
+    generator: CliGenerator,
+    runtime: Runtime,
 
     // Feature States
     show_source: bool,
@@ -55,66 +96,191 @@ struct App {
     search_query: String,
     filtered_commands: Vec<(String, Command)>,
 
-    // History State
-    history: Vec<String>,
+    // History and favorites of this Nestfile
+    project: ProjectState,
     history_state: ListState,
-    history_path: std::path::PathBuf,
+
+    // Auto-reload
+    nestfile_path: PathBuf,
+    watcher: Option<FileWatcher>,
+    load_error: Option<Vec<String>>, // Why the last reload failed; the previous version stays
+
+    // Dashboard State
+    screen: Screen,
+    nestfile: Nestfile,
+    runs: Vec<Run>, // Tasks, running or kept for review until removed
+    task_state: ListState,
 
     state: ListState,
     arg_state: ListState,
     should_quit: bool,
-    nestfile_path: std::path::PathBuf,
 }
 
 impl App {
-    fn new(commands: Vec<Command>, nestfile_path: std::path::PathBuf) -> App {
-        let history_path = dirs::data_local_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("nest")
-            .join("history");
-
+    fn new(nestfile: Nestfile, project: ProjectState) -> App {
+        let (generator, runtime) = form_checker(&nestfile);
         let mut app = App {
             mode: InputMode::Normal,
             view_mode: ViewMode::Tree,
             focus: Focus::CommandList,
             input_buffer: String::new(),
-            root_commands: commands,
+            root_commands: nestfile.commands.clone(),
             flat_commands: Vec::new(),
             breadcrumbs: Vec::new(),
             selection_history: Vec::new(),
-            args_map: std::collections::HashMap::new(),
+            form: ArgForm::default(),
+            form_error: None,
+            generator,
+            runtime,
             show_source: false,
             source_code: None,
             search_query: String::new(),
             filtered_commands: Vec::new(),
 
-            history: Vec::new(),
+            project,
             history_state: ListState::default(),
-            history_path,
+
+            nestfile_path: PathBuf::new(),
+            watcher: None,
+            load_error: None,
+
+            screen: Screen::Browser,
+            nestfile,
+            runs: Vec::new(),
+            task_state: ListState::default(),
 
             state: ListState::default(),
             arg_state: ListState::default(),
             should_quit: false,
-            nestfile_path,
         };
-        app.load_history();
         app.flatten_commands();
         // Select first item by default
         if !app.root_commands.is_empty() {
             app.state.select(Some(0));
         }
+        app.reset_args();
         app
     }
 
+    /// Reloads the Nestfile whenever `sources` (it and its includes) change.
+    ///
+    /// If the files can't be watched, auto-reload is off and the reason is
+    /// shown in the load error panel.
+    fn watch(&mut self, nestfile_path: PathBuf, sources: &[PathBuf]) {
+        self.nestfile_path = nestfile_path;
+        self.watcher = match FileWatcher::new(sources, RELOAD_DEBOUNCE_MS) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                self.load_error = Some(vec![format!("Auto-reload is off: {}", e)]);
+                None
+            }
+        };
+    }
+
+    /// Re-runs the load pipeline after a change, keeping the breadcrumbs,
+    /// the selection and what was typed into the form. On failure the
+    /// previous version stays and the error is shown instead.
+    fn reload(&mut self) {
+        let project = match Project::load(Some(&self.nestfile_path), LoadOptions::new()) {
+            Ok(project) => project,
+            Err(e) => {
+                self.load_error = Some(error_lines(&e));
+                return;
+            }
+        };
+        let sources = project.sources.files();
+        let nestfile = Nestfile::from(project);
+        self.load_error = None;
+        let selected = self.selected_command_path();
+
+        (self.generator, self.runtime) = form_checker(&nestfile);
+        self.root_commands = nestfile.commands.clone();
+        self.nestfile = nestfile;
+        self.flatten_commands();
+        let path = self.nestfile_path.clone();
+        self.watch(path, &sources);
+
+        // Keep as much of the path as still exists
+        let mut depth = 0;
+        let mut level = &self.root_commands;
+        for step in &self.breadcrumbs {
+            match level
+                .iter()
+                .find(|c| &c.name == step && !c.children.is_empty())
+            {
+                Some(cmd) => level = &cmd.children,
+                None => break,
+            }
+            depth += 1;
+        }
+        self.breadcrumbs.truncate(depth);
+        self.selection_history.truncate(depth);
+
+        if !self.search_query.is_empty() {
+            self.update_search();
+        }
+        let name = selected.as_ref().and_then(|path| path.last());
+        let idx = if !self.search_query.is_empty() || self.view_mode == ViewMode::Flat {
+            let commands = if self.search_query.is_empty() {
+                &self.flat_commands
+            } else {
+                &self.filtered_commands
+            };
+            let joined = selected.as_ref().map(|path| path.join(" "));
+            commands
+                .iter()
+                .position(|(p, _)| Some(p) == joined.as_ref())
+        } else {
+            self.get_current_items()
+                .iter()
+                .position(|c| Some(&c.name) == name)
+        };
+        let count = match self.view_mode {
+            _ if !self.search_query.is_empty() => self.filtered_commands.len(),
+            ViewMode::Flat => self.flat_commands.len(),
+            _ => self.get_current_items().len(),
+        };
+        let fallback = self
+            .state
+            .selected()
+            .map(|i| i.min(count.saturating_sub(1)));
+        self.state
+            .select(if count == 0 { None } else { idx.or(fallback) });
+
+        let previous = std::mem::take(&mut self.form);
+        self.reset_args();
+        if previous.command_path == self.form.command_path {
+            let preset = previous.preset.as_ref().and_then(|name| {
+                let cmd = self.get_selected_command()?;
+                Some((name, DirectiveResolver::get_preset(&cmd.directives, name)?))
+            });
+            if let Some((name, values)) = preset {
+                self.form.apply_preset(Some((name, &values)));
+            }
+            self.form.carry_over(&previous);
+            self.refresh_form();
+        }
+        if self.source_code.is_some() && self.show_source {
+            self.load_selected_source();
+        }
+    }
+
     fn flatten_commands(&mut self) {
         self.flat_commands.clear();
         let commands = self.root_commands.clone();
         for cmd in &commands {
             self.flatten_recursive(cmd, &[]);
         }
+        // Favorites are pinned to the top
+        let project = &self.project;
+        self.flat_commands
+            .sort_by_key(|(path, _)| !project.is_favorite(path));
     }
 
     fn flatten_recursive(&mut self, cmd: &Command, parent_path: &[String]) {
+        if cmd.is_private() {
+            return;
+        }
         let mut current_path = parent_path.to_vec();
         current_path.push(cmd.name.clone());
 
@@ -132,39 +298,84 @@ impl App {
         }
     }
 
-    fn load_history(&mut self) {
-        if let Ok(content) = std::fs::read_to_string(&self.history_path) {
-            self.history = content.lines().map(|s| s.to_string()).collect();
-            // Reverse to show newest at top? Or standard?
-            // Usually history file is append, so newest at bottom.
-            // But UI might want newest at top.
-            // Let's keep order as file (oldest first) but render or scrolling properly.
-            // Actually, recent at top is better for quick access.
-            self.history.reverse();
+    /// Pins or unpins the selected command, keeping it selected.
+    fn toggle_favorite(&mut self) {
+        let Some(path) = self.selected_command_path() else {
+            return;
+        };
+        let path = path.join(" ");
+        self.project.toggle_favorite(&path);
+        self.flatten_commands();
+        if !self.search_query.is_empty() {
+            self.update_search();
+        }
+        if self.view_mode == ViewMode::Flat || !self.search_query.is_empty() {
+            let commands = if self.search_query.is_empty() {
+                &self.flat_commands
+            } else {
+                &self.filtered_commands
+            };
+            let idx = commands.iter().position(|(p, _)| p == &path);
+            self.state.select(idx);
         }
     }
 
-    fn add_history(&mut self, cmd: String) {
-        // Remove duplicates
-        if let Some(pos) = self.history.iter().position(|x| x == &cmd) {
-            self.history.remove(pos);
+    /// Adds a finished dashboard task to the history.
+    fn record_run(&mut self, idx: usize) {
+        let run = &self.runs[idx];
+        let outcome = match run.status {
+            RunStatus::Running => return,
+            RunStatus::Succeeded => Outcome::Succeeded,
+            RunStatus::Failed(code) => Outcome::Failed(code),
+            RunStatus::Cancelled => Outcome::Cancelled,
+        };
+        let elapsed = run.elapsed();
+        let entry = HistoryEntry {
+            command_line: run.command_line.clone(),
+            command: self.command_path_of(&run.command_line).join(" "),
+            started: history::now().saturating_sub(elapsed.as_secs()),
+            duration_ms: elapsed.as_millis() as u64,
+            outcome,
+        };
+        self.project.record(entry);
+        if self.history_state.selected().is_none() {
+            self.history_state.select(Some(0));
         }
-        self.history.insert(0, cmd.clone());
-        if self.history.len() > 50 {
-            self.history.pop();
+    }
+
+    /// Starts `command_line` in-process as a new dashboard task and shows it.
+    fn start_run(&mut self, command_line: String) {
+        self.runs.push(Run::start(&command_line, &self.nestfile));
+        self.task_state.select(Some(self.runs.len() - 1));
+        self.screen = Screen::Dashboard;
+    }
+
+    fn select_task(&mut self, idx: usize) {
+        if !self.runs.is_empty() {
+            self.task_state.select(Some(idx.min(self.runs.len() - 1)));
         }
-        self.save_history();
     }
 
-    fn save_history(&self) {
-        if let Some(parent) = self.history_path.parent() {
-            let _ = std::fs::create_dir_all(parent);
+    /// Stops the task if it is still running and starts its command again.
+    fn restart_task(&mut self, idx: usize) {
+        let run = &mut self.runs[idx];
+        run.cancel();
+        let command_line = run.command_line.clone();
+        self.runs[idx] = Run::start(&command_line, &self.nestfile);
+    }
+
+    /// Drops a finished task from the dashboard.
+    fn remove_task(&mut self, idx: usize) {
+        if self.runs[idx].is_running() {
+            return;
+        }
+        self.runs.remove(idx);
+        if self.runs.is_empty() {
+            self.task_state.select(None);
+            self.screen = Screen::Browser;
+        } else {
+            self.select_task(idx);
         }
-        // Save in reverse (oldest first) for standard history files
-        let mut to_save = self.history.clone();
-        to_save.reverse();
-        let content = to_save.join("\n");
-        let _ = std::fs::write(&self.history_path, content);
     }
 
     /// Returns the list of commands at the current depth
@@ -179,7 +390,7 @@ impl App {
                 return Vec::new();
             }
         }
-        current_level.iter().collect()
+        current_level.iter().filter(|c| !c.is_private()).collect()
     }
 
     fn resolve_command_from_string(&self, cmd_str: &str) -> Option<&Command> {
@@ -188,7 +399,7 @@ impl App {
         let mut current_level = &self.root_commands;
 
         for part in parts {
-            if let Some(cmd) = current_level.iter().find(|c| c.name == part) {
+            if let Some(cmd) = current_level.iter().find(|c| c.matches_name(part)) {
                 best_match = Some(cmd);
                 current_level = &cmd.children;
             } else {
@@ -228,8 +439,8 @@ impl App {
             }
             ViewMode::History => {
                 if let Some(idx) = self.history_state.selected() {
-                    if let Some(cmd_str) = self.history.get(idx) {
-                        return self.resolve_command_from_string(cmd_str);
+                    if let Some(entry) = self.project.history.get(idx) {
+                        return self.resolve_command_from_string(&entry.command_line);
                     }
                 }
                 return None;
@@ -281,65 +492,124 @@ impl App {
             .unwrap_or_default()
     }
 
-    fn update_input_buffer(&mut self) {
-        if let Some(cmd) = self.get_selected_command() {
-            let mut full_cmd = if self.view_mode != ViewMode::History {
-                self.breadcrumbs.join(" ")
+    /// Path of the selected command, with canonical names.
+    fn selected_command_path(&self) -> Option<Vec<String>> {
+        if !self.search_query.is_empty() || self.view_mode == ViewMode::Flat {
+            let commands = if self.search_query.is_empty() {
+                &self.flat_commands
             } else {
-                // In History mode, we might want to respect the history command string base?
-                // But for now, let's treat it as rebuilding from the resolved command.
-                // Actually, if we are in History mode, get_selected_command resolves the command struct.
-                // We should probably reconstruct the path from the resolved command if possible,
-                // or just use the history string?
-                // Issue: History string has args. args_map might be empty initially.
-                // Simple approach: Always rebuild from scratch using breadcrumbs if available?
-                // But History command might not match current breadcrumbs.
-                // Let's assume breadcrumbs are correct for Tree/Flat.
-                // For History, we might need a way to get the full path of the resolved command.
-                // Since we don't store parent pointers, we can't easily walk up.
-                // Fallback: Use cmd.name.
-                cmd.name.clone()
+                &self.filtered_commands
             };
+            let (path, _) = commands.get(self.state.selected()?)?;
+            return Some(path.split_whitespace().map(str::to_string).collect());
+        }
 
-            if self.view_mode != ViewMode::History {
-                if !full_cmd.is_empty() {
-                    full_cmd.push(' ');
-                }
-                full_cmd.push_str(&cmd.name);
+        match self.view_mode {
+            ViewMode::History => {
+                let entry = self.project.history.get(self.history_state.selected()?)?;
+                let path = self.command_path_of(&entry.command_line);
+                (!path.is_empty()).then_some(path)
             }
-
-            // Append arguments from args_map
-            for param in &cmd.parameters {
-                if let Some(val) = self.args_map.get(&param.name) {
-                    // Check if bool flag
-                    if param.param_type == "bool" {
-                        if val == "true" {
-                            full_cmd.push(' ');
-                            if param.is_named {
-                                full_cmd.push_str("--");
-                                full_cmd.push_str(&param.name);
-                            }
-                        }
-                    } else {
-                        // String/Num/etc
-                        if !val.is_empty() {
-                            full_cmd.push(' ');
-                            if param.is_named {
-                                full_cmd.push_str("--");
-                                full_cmd.push_str(&param.name);
-                                full_cmd.push(' ');
-                                full_cmd.push_str(val);
-                            } else {
-                                full_cmd.push_str(val);
-                            }
-                        }
-                    }
-                }
+            _ => {
+                let cmd = self.get_selected_command()?;
+                let mut path = self.breadcrumbs.clone();
+                path.push(cmd.name.clone());
+                Some(path)
             }
-            self.input_buffer = full_cmd;
         }
     }
 
+    /// Path, with canonical names, of the command a command line runs.
+    fn command_path_of(&self, command_line: &str) -> Vec<String> {
+        let mut path = Vec::new();
+        let mut current_level = &self.root_commands;
+        for part in command_line.split_whitespace() {
+            let Some(cmd) = current_level.iter().find(|c| c.matches_name(part)) else {
+                break;
+            };
+            path.push(cmd.name.clone());
+            current_level = &cmd.children;
+        }
+        path
+    }
+
+    /// Rebuilds the argument form from the selected command's defaults.
+    fn reset_args(&mut self) {
+        let params = self.current_args();
+        self.form = ArgForm::new(self.selected_command_path().unwrap_or_default(), &params);
+        self.refresh_form();
+    }
+
+    /// Follows the selection with the argument form, however it moved.
+    fn sync_form(&mut self) {
+        if self.selected_command_path().unwrap_or_default() != self.form.command_path {
+            self.reset_args();
+        }
+    }
+
+    /// Index of the argument under the cursor in the argument pane.
+    fn selected_field(&self) -> Option<usize> {
+        self.arg_state
+            .selected()
+            .filter(|&idx| idx < self.form.fields.len())
+    }
+
+    /// Fills the argument form with the next preset of the selected command,
+    /// going back to the defaults after the last one.
+    fn cycle_preset(&mut self) {
+        let Some(cmd) = self.get_selected_command() else {
+            return;
+        };
+        let presets = DirectiveResolver::get_presets(&cmd.directives);
+        if presets.is_empty() {
+            return;
+        }
+
+        let next = match &self.form.preset {
+            Some(current) => presets
+                .iter()
+                .position(|(name, _)| name == current)
+                .map_or(0, |i| i + 1),
+            None => 0,
+        };
+        let preset = presets
+            .get(next)
+            .map(|(name, values)| (name.as_str(), values.as_slice()));
+        self.form.apply_preset(preset);
+        self.refresh_form();
+    }
+
+    /// Checks the argument form after a change: field types first, then the
+    /// full command line as `nest` would parse and validate it.
+    fn refresh_form(&mut self) {
+        if self.form.command_path.is_empty() {
+            self.form_error = None;
+            return;
+        }
+        if let Some((field, error)) = self.form.first_error() {
+            self.form_error = Some(format!("{}: {}", field.param.name, error));
+            return;
+        }
+
+        let mut args = vec!["nest".to_string()];
+        args.extend(self.form.command_path.iter().cloned());
+        args.extend(self.form.args());
+        self.form_error = CommandHandler::check_args(&args, &self.generator, &self.runtime)
+            .err()
+            .map(|error| {
+                // clap follows the message with usage lines
+                let lines: Vec<String> =
+                    error.to_string().lines().map(runner::plain_line).collect();
+                let message: Vec<&str> = lines
+                    .iter()
+                    .map(|line| line.trim())
+                    .skip_while(|line| line.is_empty())
+                    .take_while(|line| !line.is_empty())
+                    .collect();
+                message.join(" ")
+            });
+    }
+
     fn toggle_view(&mut self) {
         match self.view_mode {
             ViewMode::Tree => self.view_mode = ViewMode::Flat,
@@ -379,7 +649,7 @@ impl App {
             None => 0,
         };
         self.state.select(Some(i));
-        self.args_map.clear();
+        self.reset_args();
     }
 
     fn previous(&mut self) {
@@ -407,19 +677,31 @@ impl App {
             None => 0,
         };
         self.state.select(Some(i));
-        self.args_map.clear();
+        self.reset_args();
     }
 
+    /// Ranks commands against the search query: favorites first, then by
+    /// match score scaled up by frecency.
     fn update_search(&mut self) {
         if self.search_query.is_empty() {
             self.filtered_commands.clear();
         } else {
-            let query = self.search_query.to_lowercase();
-            self.filtered_commands = self
+            let now = history::now();
+            let mut ranked: Vec<(bool, f64, &(String, Command))> = self
                 .flat_commands
                 .iter()
-                .filter(|(path, _)| path.to_lowercase().contains(&query))
-                .cloned()
+                .filter_map(|entry| {
+                    let (path, cmd) = entry;
+                    let score = search::command_score(&self.search_query, path, cmd)?;
+                    let frecency = self.project.frecency(path, now);
+                    let rank = score as f64 * (1.0 + frecency.ln_1p());
+                    Some((self.project.is_favorite(path), rank, entry))
+                })
+                .collect();
+            ranked.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.total_cmp(&a.1)));
+            self.filtered_commands = ranked
+                .into_iter()
+                .map(|(_, _, entry)| entry.clone())
                 .collect();
         }
         if !self.filtered_commands.is_empty() {
@@ -503,99 +785,26 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
-    let nestfile_path = if let Some(path_str) = config_path_arg {
-        let path = Path::new(&path_str).to_path_buf();
-        if !path.exists() {
-            nest_core::nestparse::output::OutputFormatter::error(&format!(
-                "Configuration file not found: {}",
-                path_str
-            ));
-            process::exit(1);
-        }
-        path
-    } else {
-        match nest_core::nestparse::path::find_config_file() {
-            Some(path) => path,
-            None => {
-                println!("nestfile not found in current directory");
-                println!("Run 'nest --init' to create one, or use '--config <path>'.");
-                process::exit(1);
-            }
-        }
-    };
-
-    let content = match nest_core::nestparse::file::read_file_unchecked(&nestfile_path) {
-        Ok(c) => c,
-        Err(e) => {
-            nest_core::nestparse::output::OutputFormatter::error(&format!(
-                "Error reading file: {}",
-                e
-            ));
+    let project = match Project::load(
+        config_path_arg.as_deref().map(Path::new),
+        LoadOptions::new(),
+    ) {
+        Ok(project) => project,
+        Err(NestError::NotFound(None)) => {
+            println!("nestfile not found in current directory");
+            println!("Run 'nest --init' to create one, or use '--config <path>'.");
             process::exit(1);
         }
-    };
-
-    // Process includes logic
-    let mut visited = std::collections::HashSet::new();
-    let processed_content = match nest_core::nestparse::include::process_includes(
-        &content,
-        &nestfile_path,
-        &mut visited,
-    ) {
-        Ok(c) => c,
-        Err(e) => {
-            nest_core::nestparse::output::OutputFormatter::error(&format!(
-                "Error processing includes: {}",
-                e
-            ));
+        Err(NestError::Validation { path, errors }) => {
+            print_validation_errors(&errors, &path);
             process::exit(1);
         }
-    };
-
-    // Prepend root source marker
-    let mut full_content = String::new();
-    if let Ok(canonical_path) = nestfile_path.canonicalize() {
-        full_content.push_str(&format!("# @source: {}\n", canonical_path.display()));
-    } else {
-        full_content.push_str(&format!("# @source: {}\n", nestfile_path.display()));
-    }
-    full_content.push_str(&processed_content);
-
-    let mut parser = nest_core::nestparse::parser::Parser::new(&full_content);
-    let mut parse_result = match parser.parse() {
-        Ok(res) => res,
         Err(e) => {
-            let msg = match e {
-                nest_core::nestparse::parser::ParseError::UnexpectedEndOfFile(line) => {
-                    format!("Parse error at line {}: Unexpected end of file.", line)
-                }
-                nest_core::nestparse::parser::ParseError::InvalidSyntax(msg, line) => {
-                    format!("Parse error at line {}: {}", line, msg)
-                }
-                nest_core::nestparse::parser::ParseError::InvalidIndent(line) => {
-                    format!("Parse error at line {}: Invalid indentation.", line)
-                }
-                nest_core::nestparse::parser::ParseError::DeprecatedSyntax(msg, line) => {
-                    format!("Parse error at line {}: {} (deprecated syntax)", line, msg)
-                }
-            };
-            nest_core::nestparse::output::OutputFormatter::error(&msg);
+            nest_core::nestparse::output::OutputFormatter::error(&e.to_string());
             process::exit(1);
         }
     };
 
-    // Merge duplicate commands (same as CLI)
-    parse_result.commands = nest_core::nestparse::merge::merge_commands(parse_result.commands);
-
-    // Validate configuration using standard nest-core validator
-    if let Err(validation_errors) = validate_commands(&parse_result.commands, &nestfile_path) {
-        print_validation_errors(&validation_errors, &nestfile_path);
-        process::exit(1);
-    }
-
-    // Populate source file path recursively
-    resolve_command_sources(&mut parse_result.commands, &full_content, &nestfile_path);
-
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -603,8 +812,28 @@ fn main() -> Result<(), Box<dyn Error>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
+    // Resolved before changing directory: keys the history, and reloads read it
+    let nestfile_path = project
+        .path
+        .canonicalize()
+        .unwrap_or_else(|_| project.path.clone());
+    let history = ProjectState::load(&nestfile_path);
+
+    // Commands run in-process from the nestfile's directory, which is where the
+    // TUI used to start `nest --config`. The CLI itself stays in the caller's
+    // directory, so the two differ when the TUI is opened with a config elsewhere
+    if let Some(dir) = nestfile_path
+        .canonicalize()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+    {
+        let _ = std::env::set_current_dir(dir);
+    }
+
     // Create App
-    let mut app = App::new(parse_result.commands, nestfile_path);
+    let sources = project.sources.files();
+    let mut app = App::new(Nestfile::from(project), history);
+    app.watch(nestfile_path, &sources);
 
     // Run loop
     let res = run_app(&mut terminal, &mut app);
@@ -625,130 +854,66 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn resolve_command_sources(commands: &mut Vec<Command>, content: &str, initial_path: &Path) {
-    let mut line_map = Vec::new();
-    let mut current_path = initial_path.to_path_buf();
-
-    // 1. Build map of line index -> source file
-    for line in content.lines() {
-        if let Some(path_str) = line.strip_prefix("# @source: ") {
-            current_path = std::path::PathBuf::from(path_str.trim());
-        }
-        line_map.push(current_path.clone());
-    }
-
-    // 2. Resolve recursively
-    let indexed_lines: Vec<(usize, &str)> = content.lines().enumerate().collect();
-    resolve_scope(commands, &indexed_lines, &line_map);
-}
-
-fn resolve_scope(
-    commands: &mut Vec<Command>,
-    lines: &[(usize, &str)],
-    line_map: &[std::path::PathBuf],
-) {
-    for cmd in commands {
-        // Find definition line
-        for (idx, (line_num, line)) in lines.iter().enumerate() {
-            let trimmed = line.trim_start();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
-            }
-
-            // Check for name match at start of line
-            if trimmed.starts_with(&cmd.name) {
-                let remainder = &trimmed[cmd.name.len()..];
-                // Check valid endings: ":", "(...", " :"
-                if remainder.trim_start().starts_with(':') || remainder.starts_with('(') {
-                    // Found it!
-                    if let Some(path) = line_map.get(*line_num) {
-                        cmd.source_file = Some(path.clone());
-                    }
-
-                    // Recurse children with lines AFTER definition
-                    if !cmd.children.is_empty() {
-                        let next_lines = &lines[idx + 1..];
-                        resolve_scope(&mut cmd.children, next_lines, line_map);
-                    }
-                    break; // Found this command, move to next sibling
-                }
-            }
-        }
-    }
-}
-
-use std::io::Write;
-
-fn execute_shell_command<B: Backend + Write>(
-    terminal: &mut Terminal<B>,
-    command_str: &str,
-    nestfile_path: &Path,
-) -> io::Result<()> {
-    // Suspend TUI
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
-
-    let absolute_config = nestfile_path
-        .canonicalize()
-        .unwrap_or_else(|_| nestfile_path.to_path_buf());
-    let working_dir = absolute_config.parent().unwrap_or(Path::new("."));
-
-    println!(
-        "Executing: nest --config {} {}",
-        absolute_config.display(),
-        command_str
+/// Parser and runtime that check the argument form against `nestfile`.
+fn form_checker(nestfile: &Nestfile) -> (CliGenerator, Runtime) {
+    let generator = CliGenerator::new(nestfile.commands.clone()).prompting(false);
+    let runtime = Runtime::new(
+        nestfile.commands.clone(),
+        nestfile.variables.clone(),
+        nestfile.constants.clone(),
+        nestfile.functions.clone(),
+        None,
     );
+    (generator, runtime)
+}
 
-    // Run command
-    let parts: Vec<&str> = command_str.split_whitespace().collect();
-    if !parts.is_empty() {
-        let mut final_args = vec!["--config", absolute_config.to_str().unwrap_or("")];
-        final_args.extend_from_slice(&parts);
-
-        let status = std::process::Command::new("nest")
-            .current_dir(working_dir)
-            .args(&final_args)
-            .status();
-
-        match status {
-            Ok(s) => {
-                if !s.success() {
-                    println!("Command failed with exit code: {:?}", s.code());
+/// A load error as plain lines, for the panel shown when a reload fails.
+fn error_lines(error: &NestError) -> Vec<String> {
+    match error {
+        NestError::Validation { errors, .. } => errors
+            .iter()
+            .flat_map(|error| {
+                let mut lines = vec![if error.command_path.is_empty() {
+                    error.message.clone()
+                } else {
+                    format!("{} (nest {})", error.message, error.command_path.join(" "))
+                }];
+                if let Some(suggestion) = &error.suggestion {
+                    lines.push(format!("  Suggestion: {}", suggestion));
                 }
-            }
-            Err(e) => println!("Failed to execute command: {}", e),
-        }
+                lines
+            })
+            .collect(),
+        other => other.to_string().lines().map(runner::plain_line).collect(),
     }
-
-    println!("\nPress Enter to return...");
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-
-    // Restore TUI
-    enable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        EnterAlternateScreen,
-        EnableMouseCapture
-    )?;
-    terminal.hide_cursor()?;
-    terminal.clear()?;
-
-    Ok(())
 }
 
-fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
     loop {
+        let finished: Vec<usize> = (0..app.runs.len())
+            .filter(|&idx| app.runs[idx].poll())
+            .collect();
+        for &idx in &finished {
+            app.record_run(idx);
+        }
+        if !finished.is_empty() {
+            // Anything a run wrote around the panes is painted over
+            terminal.clear()?;
+        }
+        if app.watcher.as_mut().is_some_and(FileWatcher::changed) {
+            app.reload();
+        }
+        app.sync_form();
         terminal.draw(|f| ui(f, app))?;
 
+        if !event::poll(TICK)? {
+            continue;
+        }
         match event::read()? {
-            Event::Key(key) => {
-                if key.kind == KeyEventKind::Press {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                if app.screen != Screen::Browser {
+                    handle_task_key(app, key.code);
+                } else {
                     match app.mode {
                         InputMode::Normal => match key.code {
                             KeyCode::Char('q') | KeyCode::Esc => app.should_quit = true,
@@ -756,7 +921,7 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                 Focus::CommandList => {
                                     app.focus = Focus::History;
                                     if app.history_state.selected().is_none()
-                                        && !app.history.is_empty()
+                                        && !app.project.history.is_empty()
                                     {
                                         app.history_state.select(Some(0));
                                     }
@@ -768,7 +933,6 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                     {
                                         app.arg_state.select(Some(0));
                                     }
-                                    app.update_input_buffer();
                                 }
                                 Focus::ArgumentList => app.focus = Focus::CommandList,
                             },
@@ -780,10 +944,14 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                             app.selection_history
                                                 .push(app.state.selected().unwrap_or(0));
                                             app.state.select(Some(0));
-                                            app.args_map.clear();
+                                            app.reset_args();
                                         } else {
                                             app.focus = Focus::ArgumentList;
-                                            app.update_input_buffer();
+                                            if app.arg_state.selected().is_none()
+                                                && !app.form.fields.is_empty()
+                                            {
+                                                app.arg_state.select(Some(0));
+                                            }
                                         }
                                     }
                                 } else {
@@ -802,7 +970,7 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                             } else {
                                                 app.state.select(Some(0));
                                             }
-                                            app.args_map.clear();
+                                            app.reset_args();
                                         }
                                     }
                                     Focus::History => app.focus = Focus::CommandList,
@@ -821,7 +989,7 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                     app.focus = Focus::History;
                                     // Select first item if none selected
                                     if app.history_state.selected().is_none()
-                                        && !app.history.is_empty()
+                                        && !app.project.history.is_empty()
                                     {
                                         app.history_state.select(Some(0));
                                     }
@@ -832,6 +1000,17 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                 app.search_query.clear();
                                 app.update_search();
                             }
+                            KeyCode::Char('p') => app.cycle_preset(),
+                            KeyCode::Char('f') => app.toggle_favorite(),
+                            // Run the latest invocation again
+                            KeyCode::Char('r') => {
+                                if let Some(entry) = app.project.history.first() {
+                                    app.start_run(entry.command_line.clone());
+                                }
+                            }
+                            KeyCode::Char('o') if !app.runs.is_empty() => {
+                                app.screen = Screen::Dashboard;
+                            }
                             KeyCode::Char('d') => {
                                 app.show_source = !app.show_source;
                                 if app.show_source {
@@ -844,10 +1023,10 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                     app.arg_state.select(None);
                                 }
                                 Focus::History => {
-                                    if !app.history.is_empty() {
+                                    if !app.project.history.is_empty() {
                                         let i = match app.history_state.selected() {
                                             Some(i) => {
-                                                if i >= app.history.len() - 1 {
+                                                if i >= app.project.history.len() - 1 {
                                                     0
                                                 } else {
                                                     i + 1
@@ -866,11 +1045,11 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                     app.arg_state.select(None);
                                 }
                                 Focus::History => {
-                                    if !app.history.is_empty() {
+                                    if !app.project.history.is_empty() {
                                         let i = match app.history_state.selected() {
                                             Some(i) => {
                                                 if i == 0 {
-                                                    app.history.len() - 1
+                                                    app.project.history.len() - 1
                                                 } else {
                                                     i - 1
                                                 }
@@ -883,20 +1062,14 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                 Focus::ArgumentList => app.previous_arg(),
                             },
                             KeyCode::Char('e') => {
-                                if let Some(cmd) = app.get_selected_command() {
-                                    let mut full_cmd = app.breadcrumbs.join(" ");
-                                    if !full_cmd.is_empty() {
-                                        full_cmd.push(' ');
-                                    }
-                                    full_cmd.push_str(&cmd.name);
-
-                                    app.input_buffer = full_cmd;
+                                if app.get_selected_command().is_some() {
+                                    app.input_buffer = app.form.command_line();
                                     app.mode = InputMode::Editing;
                                 } else if app.focus == Focus::History {
                                     // edit history item
                                     if let Some(idx) = app.history_state.selected() {
-                                        if let Some(cmd_str) = app.history.get(idx) {
-                                            app.input_buffer = cmd_str.clone();
+                                        if let Some(entry) = app.project.history.get(idx) {
+                                            app.input_buffer = entry.command_line.clone();
                                             app.mode = InputMode::Editing;
                                         }
                                     }
@@ -911,62 +1084,36 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                                 app.selection_history
                                                     .push(app.state.selected().unwrap_or(0));
                                                 app.state.select(Some(0));
+                                            } else if app.form_error.is_some() {
+                                                // The error is shown under the form
+                                                app.focus = Focus::ArgumentList;
                                             } else {
-                                                app.update_input_buffer();
-                                                let full_cmd = app.input_buffer.clone();
-
-                                                app.add_history(full_cmd.clone());
-                                                execute_shell_command(
-                                                    terminal,
-                                                    &full_cmd,
-                                                    &app.nestfile_path,
-                                                )?;
+                                                app.start_run(app.form.command_line());
                                             }
                                         }
                                     }
                                     Focus::History => {
                                         // Run selected history item
                                         if let Some(idx) = app.history_state.selected() {
-                                            if let Some(cmd_str) = app.history.get(idx).cloned() {
-                                                app.add_history(cmd_str.clone());
-                                                execute_shell_command(
-                                                    terminal,
-                                                    &cmd_str,
-                                                    &app.nestfile_path,
-                                                )?;
+                                            if let Some(entry) = app.project.history.get(idx) {
+                                                app.start_run(entry.command_line.clone());
                                             }
                                         }
                                     }
                                     Focus::ArgumentList => {
-                                        // Interactive Argument Form
-                                        let args = app.current_args();
-                                        if let Some(idx) = app.arg_state.selected() {
-                                            if let Some(arg) = args.get(idx) {
-                                                // Check for boolean flag
-                                                if arg.param_type == "bool" {
-                                                    let current_val = app
-                                                        .args_map
-                                                        .get(&arg.name)
-                                                        .map(|s| s.as_str())
-                                                        .unwrap_or("false");
-                                                    let new_val = if current_val == "true" {
-                                                        "false"
-                                                    } else {
-                                                        "true"
-                                                    };
-                                                    app.args_map.insert(
-                                                        arg.name.clone(),
-                                                        new_val.to_string(),
-                                                    );
-                                                    app.update_input_buffer();
-                                                } else {
-                                                    // String/Number - enter editing mode
-                                                    let current_val = app
-                                                        .args_map
-                                                        .get(&arg.name)
-                                                        .cloned()
-                                                        .unwrap_or_default();
-                                                    app.input_buffer = current_val;
+                                        if let Some(idx) = app.selected_field() {
+                                            let field = &mut app.form.fields[idx];
+                                            match field.kind {
+                                                FieldKind::Toggle | FieldKind::Choice(_) => {
+                                                    field.cycle();
+                                                    app.refresh_form();
+                                                }
+                                                FieldKind::List => {
+                                                    app.input_buffer.clear();
+                                                    app.mode = InputMode::EditingArg;
+                                                }
+                                                FieldKind::Number | FieldKind::Text => {
+                                                    app.input_buffer = field.value.clone();
                                                     app.mode = InputMode::EditingArg;
                                                 }
                                             }
@@ -981,7 +1128,7 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                                 app.mode = InputMode::Normal;
                                 app.view_mode = ViewMode::Flat;
                                 app.focus = Focus::CommandList;
-                                app.args_map.clear();
+                                app.reset_args();
                             }
                             KeyCode::Esc => {
                                 app.mode = InputMode::Normal;
@@ -1000,9 +1147,7 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                         },
                         InputMode::Editing => match key.code {
                             KeyCode::Enter => {
-                                let cmd = app.input_buffer.clone();
-                                app.add_history(cmd.clone());
-                                execute_shell_command(terminal, &cmd, &app.nestfile_path)?;
+                                app.start_run(app.input_buffer.clone());
                                 app.mode = InputMode::Normal;
                             }
                             KeyCode::Esc => {
@@ -1016,31 +1161,41 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
                             }
                             _ => {}
                         },
-                        InputMode::EditingArg => match key.code {
-                            KeyCode::Enter => {
-                                // Save value
-                                if let Some(idx) = app.arg_state.selected() {
-                                    let args = app.current_args();
-                                    if let Some(arg) = args.get(idx) {
-                                        app.args_map
-                                            .insert(arg.name.clone(), app.input_buffer.clone());
+                        InputMode::EditingArg => {
+                            let input = app.input_buffer.clone();
+                            let Some(idx) = app.selected_field() else {
+                                app.mode = InputMode::Normal;
+                                continue;
+                            };
+                            let field = &mut app.form.fields[idx];
+                            match key.code {
+                                // A list takes one item per Enter until an empty one
+                                KeyCode::Enter if field.kind == FieldKind::List => {
+                                    if input.is_empty() {
+                                        app.mode = InputMode::Normal;
+                                    } else {
+                                        field.items.push(input);
+                                        app.input_buffer.clear();
                                     }
                                 }
-                                app.mode = InputMode::Normal;
-                                app.update_input_buffer();
-                            }
-                            KeyCode::Esc => {
-                                app.mode = InputMode::Normal;
-                                app.update_input_buffer(); // Restore command preview
-                            }
-                            KeyCode::Char(c) => {
-                                app.input_buffer.push(c);
-                            }
-                            KeyCode::Backspace => {
-                                app.input_buffer.pop();
+                                KeyCode::Enter => {
+                                    field.value = input;
+                                    app.mode = InputMode::Normal;
+                                }
+                                KeyCode::Esc => app.mode = InputMode::Normal,
+                                KeyCode::Char(c) if field.accepts(c) => app.input_buffer.push(c),
+                                KeyCode::Backspace
+                                    if input.is_empty() && field.kind == FieldKind::List =>
+                                {
+                                    field.items.pop();
+                                }
+                                KeyCode::Backspace => {
+                                    app.input_buffer.pop();
+                                }
+                                _ => {}
                             }
-                            _ => {}
-                        },
+                            app.refresh_form();
+                        }
                     }
                 }
             }
@@ -1054,11 +1209,60 @@ fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> io:
         }
 
         if app.should_quit {
+            for idx in 0..app.runs.len() {
+                if app.runs[idx].is_running() {
+                    app.runs[idx].cancel();
+                    app.runs[idx].poll();
+                    app.record_run(idx);
+                }
+            }
             return Ok(());
         }
     }
 }
 
+/// Keys on the dashboard and in a focused task: picking a task, scrolling its
+/// output, answering its confirmation prompt, and stopping, restarting or
+/// removing it.
+fn handle_task_key(app: &mut App, code: KeyCode) {
+    let Some(idx) = app.task_state.selected().filter(|&i| i < app.runs.len()) else {
+        app.screen = Screen::Browser;
+        return;
+    };
+    let focused = app.screen == Screen::TaskOutput;
+
+    let run = &mut app.runs[idx];
+    if run.pending_confirm.is_some() {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => run.answer_confirm(true),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => run.answer_confirm(false),
+            KeyCode::Char('s') | KeyCode::Char('c') => run.cancel(),
+            _ => {}
+        }
+        return;
+    }
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') if !focused => app.select_task(idx.saturating_sub(1)),
+        KeyCode::Down | KeyCode::Char('j') if !focused => app.select_task(idx + 1),
+        KeyCode::Up | KeyCode::Char('k') => run.scroll_up(1),
+        KeyCode::Down | KeyCode::Char('j') => run.scroll_down(1),
+        KeyCode::PageUp => run.scroll_up(OUTPUT_PAGE),
+        KeyCode::PageDown => run.scroll_down(OUTPUT_PAGE),
+        KeyCode::Home => run.scroll_up(run.lines.len()),
+        KeyCode::End => run.scroll_back = 0,
+        KeyCode::Char('s') | KeyCode::Char('c') => run.cancel(),
+        KeyCode::Char('r') => app.restart_task(idx),
+        KeyCode::Char('x') if !focused => app.remove_task(idx),
+        KeyCode::Enter | KeyCode::Char('f') if !focused => app.screen = Screen::TaskOutput,
+        KeyCode::Enter | KeyCode::Char('f') | KeyCode::Esc | KeyCode::Char('q') if focused => {
+            app.screen = Screen::Dashboard;
+        }
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('o') => app.screen = Screen::Browser,
+        _ => {}
+    }
+}
+
 fn ui(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -1071,24 +1275,87 @@ fn ui(f: &mut Frame, app: &mut App) {
         ])
         .split(f.area());
 
+    if app.screen != Screen::Browser {
+        if let Some(idx) = app.task_state.selected().filter(|&i| i < app.runs.len()) {
+            if app.screen == Screen::Dashboard {
+                render_dashboard(f, &app.runs, &mut app.task_state, chunks[0], chunks[1]);
+            } else {
+                render_task_output(f, &app.runs[idx], chunks[0], chunks[1]);
+            }
+            return;
+        }
+    }
+
+    // A failed reload (or a Nestfile that can't be watched) is shown above the
+    // commands of the last good version
+    let mut main_area = chunks[0];
+    if let Some(lines) = &app.load_error {
+        let height = (lines.len() as u16 + 2).min(main_area.height / 3).max(3);
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(height), Constraint::Min(1)])
+            .split(main_area);
+        let panel = Paragraph::new(
+            lines
+                .iter()
+                .map(|l| Line::from(l.as_str()))
+                .collect::<Vec<_>>(),
+        )
+        .style(Style::default().fg(Color::Red))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title("Nestfile reload problem (showing the last version that loaded)"),
+        )
+        .wrap(ratatui::widgets::Wrap { trim: false });
+        f.render_widget(panel, areas[0]);
+        main_area = areas[1];
+    }
+
     let main_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(chunks[0]);
+        .split(main_area);
 
     // Left Column: Commands OR History
     if app.view_mode == ViewMode::History {
+        let now = history::now();
         let history_items: Vec<ListItem> = app
+            .project
             .history
             .iter()
-            .map(|cmd| ListItem::new(Line::from(cmd.clone())))
+            .map(|entry| {
+                let (mark, color) = match entry.outcome {
+                    Outcome::Succeeded => ("✓", Color::Green),
+                    Outcome::Failed(_) => ("✗", Color::Red),
+                    Outcome::Cancelled => ("■", Color::Magenta),
+                };
+                let exit = match entry.outcome {
+                    Outcome::Failed(Some(code)) => format!("exit {}, ", code),
+                    _ => String::new(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{} ", mark), Style::default().fg(color)),
+                    Span::raw(entry.command_line.clone()),
+                    Span::styled(
+                        format!(
+                            "  {}{}, {}",
+                            exit,
+                            format_duration(Duration::from_millis(entry.duration_ms)),
+                            format_age(now.saturating_sub(entry.started))
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]))
+            })
             .collect();
 
         let history_list = List::new(history_items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("History")
+                    .title("History (Enter to run again)")
                     .border_style(match app.focus {
                         Focus::History => Style::default().fg(Color::Green),
                         _ => Style::default(),
@@ -1104,6 +1371,13 @@ fn ui(f: &mut Frame, app: &mut App) {
         f.render_stateful_widget(history_list, main_chunks[0], &mut app.history_state);
     } else {
         // Command List
+        let star = |path: &str| {
+            if app.project.is_favorite(path) {
+                Span::styled("★ ", Style::default().fg(Color::Yellow))
+            } else {
+                Span::raw("")
+            }
+        };
         let items: Vec<ListItem> = if !app.search_query.is_empty() {
             app.filtered_commands
                 .iter()
@@ -1115,7 +1389,10 @@ fn ui(f: &mut Frame, app: &mut App) {
                     } else {
                         Style::default()
                     };
-                    ListItem::new(Line::from(Span::styled(path.clone(), style)))
+                    ListItem::new(Line::from(vec![
+                        star(path),
+                        Span::styled(path.clone(), style),
+                    ]))
                 })
                 .collect()
         } else {
@@ -1125,6 +1402,8 @@ fn ui(f: &mut Frame, app: &mut App) {
                     current_items
                         .iter()
                         .map(|cmd| {
+                            let mut path = app.breadcrumbs.clone();
+                            path.push(cmd.name.clone());
                             let mut name = cmd.name.clone();
                             let style = if !cmd.children.is_empty() {
                                 name.push_str(" /");
@@ -1134,7 +1413,10 @@ fn ui(f: &mut Frame, app: &mut App) {
                             } else {
                                 Style::default()
                             };
-                            ListItem::new(Line::from(Span::styled(name, style)))
+                            ListItem::new(Line::from(vec![
+                                star(&path.join(" ")),
+                                Span::styled(name, style),
+                            ]))
                         })
                         .collect()
                 }
@@ -1149,7 +1431,10 @@ fn ui(f: &mut Frame, app: &mut App) {
                         } else {
                             Style::default()
                         };
-                        ListItem::new(Line::from(Span::styled(path.clone(), style)))
+                        ListItem::new(Line::from(vec![
+                            star(path),
+                            Span::styled(path.clone(), style),
+                        ]))
                     })
                     .collect(),
                 ViewMode::History => Vec::new(), // Should not happen in this branch
@@ -1341,6 +1626,9 @@ fn ui(f: &mut Frame, app: &mut App) {
                     nest_core::nestparse::ast::Directive::Validate(target, rule) => {
                         info.push(format!("Validate {}: {}", target, rule))
                     }
+                    nest_core::nestparse::ast::Directive::Preset(name, _) => {
+                        info.push(format!("Preset: {} (p to apply)", name))
+                    }
                     _ => {}
                 }
             }
@@ -1366,65 +1654,104 @@ fn ui(f: &mut Frame, app: &mut App) {
 
             f.render_widget(paragraph, right_chunks[0]);
 
-            // Arguments Pane (Selectable List)
-            let current_args = app.current_args();
-            let arg_items: Vec<ListItem> = current_args
+            // Arguments Pane (typed form over the command line it produces)
+            let arg_items: Vec<ListItem> = app
+                .form
+                .fields
                 .iter()
-                .map(|param| {
-                    let mut s = String::new();
-
-                    // Check current value
-                    let val = app.args_map.get(&param.name).map(|s| s.as_str());
-
-                    if param.param_type == "bool" {
-                        let is_checked = val == Some("true");
-                        s.push_str(if is_checked { "[x] " } else { "[ ] " });
-                        s.push_str("--");
-                        s.push_str(&param.name);
+                .map(|field| {
+                    let param = &field.param;
+                    let name = if param.is_named {
+                        format!("--{}", param.name)
                     } else {
-                        if param.is_named {
-                            s.push_str("--");
-                            s.push_str(&param.name);
-                        } else {
-                            s.push('<');
-                            s.push_str(&param.name);
-                            s.push('>');
+                        format!("<{}>", param.name)
+                    };
+                    let mut spans = Vec::new();
+                    match &field.kind {
+                        FieldKind::Toggle => {
+                            let checked = field.value == "true";
+                            spans.push(Span::raw(if checked { "[x] " } else { "[ ] " }));
+                            spans.push(Span::raw(name));
                         }
-
-                        if let Some(v) = val {
-                            if !v.is_empty() {
-                                s.push_str(": ");
-                                s.push_str(v);
+                        kind => {
+                            spans.push(Span::raw(name));
+                            let value = field.display_value();
+                            let value = match kind {
+                                FieldKind::List => format!("[{}]", value),
+                                FieldKind::Choice(_) => format!("< {} >", value),
+                                _ => value,
+                            };
+                            if !value.is_empty() {
+                                spans.push(Span::raw(": "));
+                                let style = if field.changed() {
+                                    Style::default().fg(Color::Cyan)
+                                } else {
+                                    Style::default()
+                                };
+                                spans.push(Span::styled(value, style));
                             }
+                            spans.push(Span::styled(
+                                format!(" ({})", param.param_type),
+                                Style::default().fg(Color::DarkGray),
+                            ));
                         }
                     }
-                    // Type hint
-                    if param.param_type != "bool" {
-                        s.push_str(&format!(" ({})", param.param_type));
-                    }
 
-                    ListItem::new(Line::from(s))
+                    let mut lines = vec![Line::from(spans)];
+                    if let Some(error) = field.error() {
+                        lines.push(Line::from(Span::styled(
+                            format!("    {}", error),
+                            Style::default().fg(Color::Red),
+                        )));
+                    }
+                    ListItem::new(lines)
                 })
                 .collect();
 
+            let args_block = Block::default()
+                .borders(Borders::ALL)
+                .title(match &app.form.preset {
+                    Some(name) => format!("Arguments (Enter to edit, preset: {})", name),
+                    None => "Arguments (Enter to edit)".to_string(),
+                })
+                .border_style(match app.focus {
+                    Focus::ArgumentList => Style::default().fg(Color::Green),
+                    _ => Style::default(),
+                });
+            let args_area = args_block.inner(right_chunks[1]);
+            f.render_widget(args_block, right_chunks[1]);
+
+            let mut preview = vec![Line::from(vec![
+                Span::styled("$ ", Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    format!("nest {}", app.form.command_line()),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ])];
+            if let Some(error) = &app.form_error {
+                preview.push(Line::from(Span::styled(
+                    error.clone(),
+                    Style::default().fg(Color::Red),
+                )));
+            }
+            let preview_height = (preview.len() as u16 + 1).min(args_area.height / 2);
+            let args_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(preview_height)])
+                .split(args_area);
+
             let arg_list = List::new(arg_items)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title("Arguments (Enter to add)")
-                        .border_style(match app.focus {
-                            Focus::ArgumentList => Style::default().fg(Color::Green),
-                            _ => Style::default(),
-                        }),
-                )
                 .highlight_style(
                     Style::default()
                         .add_modifier(Modifier::BOLD)
                         .fg(Color::Yellow),
                 )
                 .highlight_symbol("> ");
-
-            f.render_stateful_widget(arg_list, right_chunks[1], &mut app.arg_state);
+            f.render_stateful_widget(arg_list, args_chunks[0], &mut app.arg_state);
+            f.render_widget(
+                Paragraph::new(preview).wrap(ratatui::widgets::Wrap { trim: false }),
+                args_chunks[1],
+            );
         } // end if let Some(cmd)
     } // end else (not showing source)
 
@@ -1441,13 +1768,17 @@ fn ui(f: &mut Frame, app: &mut App) {
             });
         }
         InputMode::EditingArg => {
+            let title = match app.selected_field().map(|idx| &app.form.fields[idx]) {
+                Some(field) if field.kind == FieldKind::List => format!(
+                    "Add to {} (Enter adds, empty Enter finishes, Backspace removes last)",
+                    field.param.name
+                ),
+                Some(field) => format!("Edit {} ({})", field.param.name, field.param.param_type),
+                None => "Edit Argument Value".to_string(),
+            };
             let input = Paragraph::new(app.input_buffer.as_str())
                 .style(Style::default().fg(Color::Magenta))
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title("Edit Argument Value"),
-                );
+                .block(Block::default().borders(Borders::ALL).title(title));
             f.render_widget(input, chunks[1]);
             f.set_cursor_position(ratatui::layout::Position {
                 x: chunks[1].x + app.input_buffer.len() as u16 + 1,
@@ -1480,6 +1811,14 @@ fn ui(f: &mut Frame, app: &mut App) {
                 Span::styled("d", Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(" | Edit: "),
                 Span::styled("e", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" | Preset: "),
+                Span::styled("p", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" | Fav: "),
+                Span::styled("f", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" | Rerun: "),
+                Span::styled("r", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" | Tasks: "),
+                Span::styled("o", Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(" | Pane: "),
                 Span::styled("Tab", Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(" | Quit: "),
@@ -1491,3 +1830,316 @@ fn ui(f: &mut Frame, app: &mut App) {
         }
     }
 }
+
+/// Draws the task list next to the selected task's dependency tree and output.
+fn render_dashboard(
+    f: &mut Frame,
+    runs: &[Run],
+    task_state: &mut ListState,
+    area: Rect,
+    footer: Rect,
+) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(area);
+
+    let items: Vec<ListItem> = runs
+        .iter()
+        .map(|run| {
+            let (status, color) = run_status(run);
+            let last = run.last_line().unwrap_or("");
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(
+                        run.command_line.clone(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("  "),
+                    Span::styled(status, Style::default().fg(color)),
+                ]),
+                Line::from(Span::styled(
+                    format!("  {}", last),
+                    Style::default().fg(Color::DarkGray),
+                )),
+            ])
+        })
+        .collect();
+    let running = runs.iter().filter(|run| run.is_running()).count();
+    let tasks = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Tasks ({} running)", running))
+                .border_style(Style::default().fg(Color::Green)),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Yellow),
+        )
+        .highlight_symbol("> ");
+    f.render_stateful_widget(tasks, columns[0], task_state);
+
+    let Some(run) = task_state.selected().and_then(|i| runs.get(i)) else {
+        return;
+    };
+
+    // Dependency tree on top when the command has `depends`
+    let tree = run
+        .dependencies
+        .as_ref()
+        .filter(|tree| !tree.children.is_empty())
+        .map(|tree| dependency_lines(run, tree));
+    let tree_height = tree.as_ref().map_or(0, |lines| {
+        (lines.len() as u16 + 2).min(columns[1].height / 2)
+    });
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(tree_height),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(columns[1]);
+
+    if let Some(lines) = tree {
+        let tree = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title("Dependencies"));
+        f.render_widget(tree, sections[0]);
+    }
+    f.render_widget(output_pane(run, sections[1].height), sections[1]);
+    f.render_widget(Paragraph::new(status_line(run)), sections[2]);
+
+    let mut help_text = Vec::new();
+    for (label, key) in [
+        ("Select: ", "Up/Down"),
+        (" | Focus: ", "Enter"),
+        (" | Restart: ", "r"),
+        (" | Stop: ", "s"),
+        (" | Remove: ", "x"),
+        (" | Scroll: ", "PgUp/PgDn"),
+        (" | Back: ", "Esc"),
+    ] {
+        help_text.push(Span::raw(label));
+        help_text.push(Span::styled(
+            key,
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    }
+    let help = Paragraph::new(Line::from(help_text)).style(Style::default().fg(Color::DarkGray));
+    f.render_widget(help, footer);
+}
+
+/// Draws one task's output over the whole screen with a status bar.
+fn render_task_output(f: &mut Frame, run: &Run, area: Rect, footer: Rect) {
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    f.render_widget(output_pane(run, sections[0].height), sections[0]);
+    f.render_widget(Paragraph::new(status_line(run)), sections[1]);
+
+    let mut help_text = Vec::new();
+    for (label, key) in [
+        ("Scroll: ", "Up/Down/PgUp/PgDn"),
+        (" | Follow: ", "End"),
+        (" | Restart: ", "r"),
+        (" | Stop: ", "s"),
+        (" | Back: ", "Esc"),
+    ] {
+        help_text.push(Span::raw(label));
+        help_text.push(Span::styled(
+            key,
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    }
+    let help = Paragraph::new(Line::from(help_text)).style(Style::default().fg(Color::DarkGray));
+    f.render_widget(help, footer);
+}
+
+/// The bordered output of a run, showing the window that ends `scroll_back`
+/// lines above the newest line.
+fn output_pane(run: &Run, height: u16) -> Paragraph<'_> {
+    let height = height.saturating_sub(2) as usize;
+    let end = run.lines.len().saturating_sub(run.scroll_back);
+    let start = end.saturating_sub(height);
+    let lines: Vec<Line> = run
+        .lines
+        .range(start..end)
+        .map(|(stream, text)| match stream {
+            OutputStream::Stdout => Line::raw(text.as_str()),
+            OutputStream::Stderr => Line::styled(text.as_str(), Style::default().fg(Color::Red)),
+        })
+        .collect();
+
+    let mut title = format!("Output: nest {}", run.command_line);
+    if run.scroll_back > 0 {
+        title.push_str(&format!(
+            " (scrolled up {}, End to follow)",
+            run.scroll_back
+        ));
+    }
+    Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(run_status(run).1)),
+    )
+}
+
+/// The run's state and duration, or its pending confirmation question.
+fn status_line(run: &Run) -> Line<'_> {
+    match &run.pending_confirm {
+        Some(message) => Line::from(Span::styled(
+            format!("? {} [y/n]", message),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        None => {
+            let (status, color) = run_status(run);
+            Line::from(vec![
+                Span::styled(
+                    status,
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!(" | {} lines", run.lines.len()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ])
+        }
+    }
+}
+
+fn run_status(run: &Run) -> (String, Color) {
+    let elapsed = format_duration(run.elapsed());
+    match &run.status {
+        RunStatus::Running if run.pending_confirm.is_some() => (
+            format!("? waiting for confirmation {}", elapsed),
+            Color::Yellow,
+        ),
+        RunStatus::Running => (format!("● running {}", elapsed), Color::Yellow),
+        RunStatus::Succeeded => (format!("✓ exit 0 in {}", elapsed), Color::Green),
+        RunStatus::Failed(Some(code)) => (format!("✗ exit {} in {}", code, elapsed), Color::Red),
+        RunStatus::Failed(None) => (format!("✗ failed in {}", elapsed), Color::Red),
+        RunStatus::Cancelled => (format!("■ stopped after {}", elapsed), Color::Magenta),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    }
+}
+
+/// How long ago something happened, coarsely: `just now`, `5m ago`, `3d ago`.
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86_400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+/// Renders the dependency tree with the live status of each command.
+fn dependency_lines(run: &Run, tree: &DependencyNode) -> Vec<Line<'static>> {
+    fn walk(
+        run: &Run,
+        node: &DependencyNode,
+        prefix: &str,
+        connector: &str,
+        lines: &mut Vec<Line<'static>>,
+    ) {
+        let (icon, label, color) = match run.progress.get(&node.path) {
+            Some(CommandStatus::Waiting) => ("◌", "waiting", Color::Cyan),
+            Some(CommandStatus::Running) => ("●", "running", Color::Yellow),
+            Some(CommandStatus::Succeeded) => ("✓", "done", Color::Green),
+            Some(CommandStatus::Failed) => ("✗", "failed", Color::Red),
+            None if run.is_running() => ("·", "pending", Color::DarkGray),
+            None => ("·", "not run", Color::DarkGray),
+        };
+        let mut spans = vec![
+            Span::styled(
+                format!("{}{}", prefix, connector),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(format!("{} ", icon), Style::default().fg(color)),
+            Span::raw(node.path.join(" ")),
+            Span::styled(format!("  {}", label), Style::default().fg(color)),
+        ];
+        if node.parallel && node.children.len() > 1 {
+            spans.push(Span::styled(
+                " (deps in parallel)",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        lines.push(Line::from(spans));
+
+        let child_prefix = match connector {
+            "├─ " => format!("{}│  ", prefix),
+            "└─ " => format!("{}   ", prefix),
+            _ => prefix.to_string(),
+        };
+        for (i, child) in node.children.iter().enumerate() {
+            let connector = if i + 1 == node.children.len() {
+                "└─ "
+            } else {
+                "├─ "
+            };
+            walk(run, child, &child_prefix, connector, lines);
+        }
+    }
+
+    let mut lines = Vec::new();
+    walk(run, tree, "", "", &mut lines);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_keeps_form_and_reports_errors() {
+        let dir = std::env::temp_dir().join(format!("nestui-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("nestfile");
+        std::fs::write(&path, "deploy(!target: str):\n    script: echo $target\n").unwrap();
+
+        let project = Project::load(Some(&path), LoadOptions::new()).unwrap();
+        let mut app = App::new(Nestfile::from(project), ProjectState::default());
+        app.watch(path.clone(), std::slice::from_ref(&path));
+        assert!(app.load_error.is_none());
+        app.form.fields[0].value = "prod".to_string();
+
+        std::fs::write(
+            &path,
+            "deploy(!target: str):\n    script: echo $target\nbuild:\n    script: echo b\n",
+        )
+        .unwrap();
+        app.reload();
+        assert!(app.load_error.is_none());
+        assert_eq!(app.root_commands.len(), 2);
+        assert_eq!(app.form.command_path, ["deploy"]);
+        assert_eq!(app.form.fields[0].value, "prod");
+
+        // A broken Nestfile keeps the last version that loaded
+        std::fs::write(&path, "deploy:\n    bogus: x\n").unwrap();
+        app.reload();
+        assert!(app.load_error.is_some());
+        assert_eq!(app.root_commands.len(), 2);
+
+        app.watch(path.clone(), &[dir.join("missing/nestfile")]);
+        assert!(app.load_error.unwrap()[0].starts_with("Auto-reload is off"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
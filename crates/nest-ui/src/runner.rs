@@ -0,0 +1,481 @@
+//! In-process command runs for the TUI.
+//!
+//! A run parses its command line with the same clap CLI as the `nest` binary and
+//! executes it through `nest_core`'s `Runtime` on a worker thread. Script output
+//! and per-command progress (from a [`RuntimeObserver`]), confirmation requests
+//! and the final result come back over a channel and are collected into a
+//! [`Run`], one per dashboard task.
+
+use nest_core::constants::DEFAULT_SUBCOMMAND;
+use nest_core::nestparse::ast::{Command, Constant, Function, Variable};
+use nest_core::nestparse::cli::CliGenerator;
+use nest_core::nestparse::command_handler::CommandHandler;
+use nest_core::nestparse::executor::OutputStream;
+use nest_core::nestparse::observer::{Phase, RuntimeObserver};
+use nest_core::nestparse::output::strip_ansi;
+use nest_core::nestparse::runtime::{DependencyNode, Runtime};
+use nest_core::{NestError, Project};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Oldest lines are dropped once a run has printed this many.
+const MAX_OUTPUT_LINES: usize = 10_000;
+
+/// Everything the runtime needs from the parsed nestfile.
+#[derive(Clone)]
+pub struct Nestfile {
+    pub commands: Vec<Command>,
+    pub variables: Vec<Variable>,
+    pub constants: Vec<Constant>,
+    pub functions: Vec<Function>,
+}
+
+impl From<Project> for Nestfile {
+    fn from(project: Project) -> Self {
+        Nestfile {
+            commands: project.commands,
+            variables: project.variables,
+            constants: project.constants,
+            functions: project.functions,
+        }
+    }
+}
+
+/// Where a command is in its run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandStatus {
+    /// Waiting for its dependencies
+    Waiting,
+    /// Running its own scripts
+    Running,
+    Succeeded,
+    Failed,
+}
+
+enum RunEvent {
+    Output(OutputStream, String),
+    Confirm(String, Sender<bool>),
+    Plan(DependencyNode),
+    Progress(Vec<String>, CommandStatus),
+    Finished(Result<(), NestError>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    /// The command failed; the exit code is known when a script exited non-zero
+    Failed(Option<i32>),
+    Cancelled,
+}
+
+pub struct Run {
+    pub command_line: String,
+    pub lines: VecDeque<(OutputStream, String)>,
+    pub status: RunStatus,
+    /// Lines scrolled up from the bottom; 0 follows new output
+    pub scroll_back: usize,
+    /// Question from `require_confirm` waiting for a y/n answer
+    pub pending_confirm: Option<String>,
+    /// Commands the run executes through `depends`, once the line is parsed
+    pub dependencies: Option<DependencyNode>,
+    /// Latest status of each command in `dependencies`
+    pub progress: HashMap<Vec<String>, CommandStatus>,
+    confirm_reply: Option<Sender<bool>>,
+    started: Instant,
+    duration: Option<Duration>,
+    events: Receiver<RunEvent>,
+    /// Process groups of every script started so far (parallel dependencies run several)
+    pids: Arc<Mutex<Vec<u32>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Run {
+    /// Starts `command_line` (without the leading `nest`) on a worker thread.
+    ///
+    /// A command line that can't be split (unbalanced quotes) gives a run that
+    /// has already failed with that error.
+    pub fn start(command_line: &str, nestfile: &Nestfile) -> Run {
+        let (tx, events) = mpsc::channel();
+        let pids = Arc::new(Mutex::new(Vec::new()));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        match split_args(command_line) {
+            Ok(words) => Self::spawn_worker(words, nestfile.clone(), tx, &pids, &cancelled),
+            Err(e) => {
+                let _ = tx.send(RunEvent::Finished(Err(NestError::Usage(e))));
+            }
+        }
+
+        Run {
+            command_line: command_line.to_string(),
+            lines: VecDeque::new(),
+            status: RunStatus::Running,
+            scroll_back: 0,
+            pending_confirm: None,
+            dependencies: None,
+            progress: HashMap::new(),
+            confirm_reply: None,
+            started: Instant::now(),
+            duration: None,
+            events,
+            pids,
+            cancelled,
+        }
+    }
+
+    fn spawn_worker(
+        words: Vec<String>,
+        nestfile: Nestfile,
+        tx: Sender<RunEvent>,
+        pids: &Arc<Mutex<Vec<u32>>>,
+        cancelled: &Arc<AtomicBool>,
+    ) {
+        let worker_pids = Arc::clone(pids);
+        let worker_cancelled = Arc::clone(cancelled);
+        let confirm_cancelled = Arc::clone(cancelled);
+        std::thread::spawn(move || {
+            let mut args = vec!["nest".to_string()];
+            args.extend(words);
+            let generator = CliGenerator::new(nestfile.commands.clone()).prompting(false);
+
+            let confirm_tx = tx.clone();
+            let runtime = Runtime::new(
+                nestfile.commands,
+                nestfile.variables,
+                nestfile.constants,
+                nestfile.functions,
+                Some(Box::new(move |pid: u32| {
+                    if let Ok(mut pids) = worker_pids.lock() {
+                        pids.push(pid);
+                    }
+                    // A script started after cancelling (a dependency, `after`) stops right away
+                    if worker_cancelled.load(Ordering::SeqCst) {
+                        kill_process_group(pid);
+                    }
+                })),
+            )
+            .with_confirm_handler(Box::new(move |message, _command_path| {
+                if confirm_cancelled.load(Ordering::SeqCst) {
+                    return false;
+                }
+                let (reply_tx, reply_rx) = mpsc::channel();
+                if confirm_tx
+                    .send(RunEvent::Confirm(message.to_string(), reply_tx))
+                    .is_err()
+                {
+                    return false;
+                }
+                reply_rx.recv().unwrap_or(false)
+            }))
+            .with_observer(Box::new(RunObserver(tx.clone())));
+
+            let result = CommandHandler::parse_args(&args, &generator).and_then(
+                |(matches, command_path)| {
+                    // A group runs its default subcommand, which is what reports progress
+                    let mut run_path = command_path.clone();
+                    if runtime
+                        .find_command(&command_path)
+                        .is_some_and(|command| runtime.has_default_command(command))
+                    {
+                        run_path.push(DEFAULT_SUBCOMMAND.to_string());
+                    }
+                    let _ = tx.send(RunEvent::Plan(runtime.dependency_tree(&run_path)));
+                    CommandHandler::execute_matches(&matches, &command_path, &generator, &runtime)
+                },
+            );
+            let _ = tx.send(RunEvent::Finished(result));
+        });
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == RunStatus::Running
+    }
+
+    /// Time the run took, or has taken so far.
+    pub fn elapsed(&self) -> Duration {
+        self.duration.unwrap_or_else(|| self.started.elapsed())
+    }
+
+    /// Collects what the worker sent since the last call.
+    ///
+    /// Returns `true` once, when the run has just finished.
+    pub fn poll(&mut self) -> bool {
+        let mut finished = false;
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                RunEvent::Output(stream, line) => self.push_line(stream, &line),
+                RunEvent::Confirm(message, reply) => {
+                    self.pending_confirm = Some(message);
+                    self.confirm_reply = Some(reply);
+                }
+                RunEvent::Plan(tree) => self.dependencies = Some(tree),
+                RunEvent::Progress(path, status) => {
+                    self.progress.insert(path, status);
+                }
+                RunEvent::Finished(result) => {
+                    self.duration = Some(self.started.elapsed());
+                    self.status = if self.cancelled.load(Ordering::SeqCst) {
+                        RunStatus::Cancelled
+                    } else {
+                        match result {
+                            Ok(()) => RunStatus::Succeeded,
+                            Err(NestError::ConfirmationDeclined { .. }) => RunStatus::Cancelled,
+                            Err(e) => {
+                                for line in e.render().lines() {
+                                    self.push_line(OutputStream::Stderr, line);
+                                }
+                                RunStatus::Failed(e.exit_code())
+                            }
+                        }
+                    };
+                    finished = true;
+                }
+            }
+        }
+        finished
+    }
+
+    /// Answers the pending confirmation prompt.
+    pub fn answer_confirm(&mut self, confirmed: bool) {
+        self.pending_confirm = None;
+        if let Some(reply) = self.confirm_reply.take() {
+            let _ = reply.send(confirmed);
+        }
+    }
+
+    /// Stops the run: declines a pending prompt and kills the script's process group.
+    pub fn cancel(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.answer_confirm(false);
+
+        if let Ok(pids) = self.pids.lock() {
+            for &pid in pids.iter() {
+                kill_process_group(pid);
+            }
+        }
+    }
+
+    /// The last line of output that isn't blank.
+    pub fn last_line(&self) -> Option<&str> {
+        self.lines
+            .iter()
+            .rev()
+            .map(|(_, line)| line.as_str())
+            .find(|line| !line.trim().is_empty())
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        let max = self.lines.len().saturating_sub(1);
+        self.scroll_back = (self.scroll_back + lines).min(max);
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll_back = self.scroll_back.saturating_sub(lines);
+    }
+
+    fn push_line(&mut self, stream: OutputStream, line: &str) {
+        if self.lines.len() >= MAX_OUTPUT_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back((stream, plain_line(line)));
+        if self.scroll_back > 0 {
+            // Keep the lines being read in place while new output arrives
+            self.scroll_back += 1;
+        }
+    }
+}
+
+/// Passes the output and progress of a run to its [`Run`].
+struct RunObserver(Sender<RunEvent>);
+
+impl RunObserver {
+    fn progress(&self, command_path: &[String], status: CommandStatus) {
+        let _ = self
+            .0
+            .send(RunEvent::Progress(command_path.to_vec(), status));
+    }
+}
+
+impl RuntimeObserver for RunObserver {
+    fn command_started(&self, command_path: &[String]) {
+        self.progress(command_path, CommandStatus::Waiting);
+    }
+
+    fn command_finished(&self, command_path: &[String], result: &Result<(), NestError>) {
+        let status = if result.is_ok() {
+            CommandStatus::Succeeded
+        } else {
+            CommandStatus::Failed
+        };
+        self.progress(command_path, status);
+    }
+
+    fn phase_started(&self, command_path: &[String], _phase: Phase) {
+        // Dependencies are done once the command's own scripts start
+        self.progress(command_path, CommandStatus::Running);
+    }
+
+    fn output_line(&self, _command_path: &[String], stream: OutputStream, line: &str) {
+        let _ = self.0.send(RunEvent::Output(stream, line.to_string()));
+    }
+
+    fn receives_output(&self) -> bool {
+        true
+    }
+}
+
+/// Scripts run in their own process group, led by the PID the runtime reports.
+fn kill_process_group(pid: u32) {
+    #[cfg(unix)]
+    {
+        let _ = std::process::Command::new("kill")
+            .args(["-TERM", "--"])
+            .arg(format!("-{}", pid))
+            .output();
+    }
+    #[cfg(windows)]
+    {
+        let _ = std::process::Command::new("taskkill")
+            .args(["/F", "/T", "/PID", &pid.to_string()])
+            .output();
+    }
+}
+
+/// Splits a command line into arguments the way a POSIX shell would for
+/// plain words, quotes and backslash escapes (no expansions).
+pub fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(c) = chars.next() {
+                    current.push(c);
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Turns a line of output into plain text for the output pane: escape
+/// sequences are removed, carriage-return overwrites resolved and tabs expanded.
+pub fn plain_line(line: &str) -> String {
+    // Progress output redraws the line after '\r'; keep what was shown last
+    let line = line.rsplit('\r').next().unwrap_or(line);
+    let mut out = String::with_capacity(line.len());
+    for c in strip_ansi(line).chars() {
+        match c {
+            '\t' => out.push_str("    "),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nest_core::nestparse::parser::Parser;
+
+    #[test]
+    fn test_split_args_quotes_and_escapes() {
+        assert_eq!(
+            split_args(r#"deploy --msg "hello world" 'a b' c\ d "say \"hi\"""#).unwrap(),
+            vec!["deploy", "--msg", "hello world", "a b", "c d", "say \"hi\""]
+        );
+        assert_eq!(split_args(r#"x "" y"#).unwrap(), vec!["x", "", "y"]);
+        assert!(split_args("echo 'open").is_err());
+    }
+
+    #[test]
+    fn test_run_collects_output_and_progress() {
+        let content = r#"clean:
+    script: echo cleaning
+build:
+    depends: clean
+    script: echo building
+"#;
+        let nestfile = Nestfile {
+            commands: Parser::new(content).parse().unwrap().commands,
+            variables: Vec::new(),
+            constants: Vec::new(),
+            functions: Vec::new(),
+        };
+        let mut run = Run::start("build", &nestfile);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !run.poll() {
+            assert!(Instant::now() < deadline, "run did not finish");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(run.status, RunStatus::Succeeded);
+        let lines: Vec<&str> = run.lines.iter().map(|(_, line)| line.as_str()).collect();
+        assert_eq!(lines, ["cleaning", "building"]);
+        for command in ["clean", "build"] {
+            assert_eq!(
+                run.progress.get(&vec![command.to_string()]),
+                Some(&CommandStatus::Succeeded)
+            );
+        }
+    }
+
+    #[test]
+    fn test_plain_line() {
+        assert_eq!(plain_line("\x1b[1;31merror\x1b[0m: bad"), "error: bad");
+        assert_eq!(plain_line("10%\r50%\r100%"), "100%");
+        assert_eq!(plain_line("a\tb\x07"), "a    b");
+    }
+}
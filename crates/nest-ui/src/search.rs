@@ -0,0 +1,68 @@
+//! Fuzzy command search.
+//!
+//! Every word of the query has to match, in order but not necessarily
+//! contiguously, the command path, its description or its script. Matches in
+//! the path count most, then the description, then the script.
+
+use nest_core::nestparse::ast::Command;
+use nest_core::nestparse::directives::DirectiveResolver;
+
+/// Scores `query` as a subsequence of `text`, ignoring case.
+///
+/// Consecutive characters and characters starting a word score higher, so
+/// `dm` prefers `db migrate` over `admin`. Returns `None` without a match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut matched = 0;
+    let mut previous: Option<usize> = None;
+
+    for (i, c) in text.iter().enumerate() {
+        if matched == query.len() {
+            break;
+        }
+        if !c.to_lowercase().eq(std::iter::once(query[matched])) {
+            continue;
+        }
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == i) {
+            score += 5;
+        }
+        if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(i);
+        matched += 1;
+    }
+    (matched == query.len()).then_some(score)
+}
+
+/// Scores a command against every word of `query`.
+pub fn command_score(query: &str, path: &str, command: &Command) -> Option<i64> {
+    let description =
+        DirectiveResolver::get_directive_value(&command.directives, "desc").unwrap_or_default();
+    let script =
+        DirectiveResolver::get_directive_value(&command.directives, "script").unwrap_or_default();
+
+    query.split_whitespace().try_fold(0, |total, word| {
+        let best = [(path, 3), (description.as_str(), 2), (script.as_str(), 1)]
+            .into_iter()
+            .filter_map(|(text, weight)| fuzzy_score(word, text).map(|s| s * weight))
+            .max()?;
+        Some(total + best)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("dbm", "db migrate").is_some());
+        assert!(fuzzy_score("mdb", "db migrate").is_none());
+        assert!(fuzzy_score("dm", "db migrate") > fuzzy_score("dm", "admin"));
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+}